@@ -0,0 +1,85 @@
+// Times the scalar multiplication paths on secp256k1. Run it with
+//
+//     cargo run --release --example bench_scalar_mul
+//
+// Debug builds are an order of magnitude slower and say little about the relative costs.
+
+use std::time::{Duration, Instant};
+
+use bitcoin_in_rust::elliptic_curve::secp256k1::{mul_generator, GeneratorTable};
+use bitcoin_in_rust::hash::hash256;
+use bitcoin_in_rust::{Point, WeierstrassCurve, SECP256K1_GENERATOR, SECP256K1_ORDER};
+use num_bigint::{BigInt, Sign};
+
+const SCALARS: u32 = 20;
+
+// Deterministic 256-bit scalars, so runs are comparable.
+fn scalars() -> Vec<BigInt> {
+    (0..SCALARS)
+        .map(|i| BigInt::from_bytes_be(Sign::Plus, &hash256(&i.to_le_bytes())))
+        .collect()
+}
+
+// Affine double-and-add over the bits of k, with an inversion per group operation: the
+// baseline the other paths improve on.
+fn affine_double_and_add(point: &Point<WeierstrassCurve>, k: &BigInt) -> Point<WeierstrassCurve> {
+    let mut result = Point::new_infinity(point.curve());
+    let mut current = point.clone();
+    for i in 0..k.bits() {
+        if k.bit(i) {
+            result += &current;
+        }
+        current.double_in_place();
+    }
+    result
+}
+
+fn bench(name: &str, scalars: &[BigInt], mul: impl Fn(&BigInt) -> Point<WeierstrassCurve>) {
+    let start = Instant::now();
+    for k in scalars {
+        std::hint::black_box(mul(k));
+    }
+    let per_mul = start.elapsed() / scalars.len() as u32;
+    println!("{name:<28} {:>10.3} ms", as_millis(per_mul));
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn main() {
+    let g = &*SECP256K1_GENERATOR;
+    let scalars = scalars();
+    // A second point for the two-term sums
+    let p = g.scalar_mul(&scalars[0]);
+
+    let start = Instant::now();
+    let table = GeneratorTable::new(g, SECP256K1_ORDER.bits());
+    println!(
+        "{:<28} {:>10.3} ms (once)",
+        "generator table build",
+        as_millis(start.elapsed())
+    );
+    // Warms up the shared table behind `mul_generator`
+    mul_generator(&scalars[0]);
+
+    println!("per multiplication, average of {SCALARS} scalars:");
+    bench("affine double-and-add", &scalars, |k| {
+        affine_double_and_add(g, k)
+    });
+    bench("scalar_mul (Jacobian wNAF)", &scalars, |k| g.scalar_mul(k));
+    bench("mul_wnaf, width 4", &scalars, |k| g.mul_wnaf(k, 4));
+    bench("GeneratorTable::mul", &scalars, |k| table.mul(k));
+    bench("mul_generator", &scalars, mul_generator);
+    bench("mul_ct (ladder)", &scalars, |k| g.mul_ct(k));
+    bench("u*G + v*P, separately", &scalars, |k| {
+        g.scalar_mul(k) + p.scalar_mul(k)
+    });
+    bench("u*G + v*P, Strauss", &scalars, |k| {
+        Point::multi_scalar_mul(&[(k, g), (k, &p)]).unwrap()
+    });
+    // The benchmark of the in-place double-and-add: scalar_mul reduces n to 0 first
+    bench("n*G, affine", &scalars, |_| {
+        affine_double_and_add(g, &SECP256K1_ORDER)
+    });
+}
@@ -0,0 +1,198 @@
+use crate::block::header::{BlockHash, BlockHeader};
+use crate::encoding::hex::hash_from_display_hex;
+use crate::network::params::Network;
+
+// This module holds the hardcoded starting points of each network: its genesis header and the
+// checkpoints a header chain must go through.
+//
+// All four genesis blocks share the same coinbase, and therefore the same merkle root; they
+// only differ in timestamp, bits and nonce. Checkpoints are (height, hash) pairs taken from
+// Bitcoin Core. A chain that disagrees with one of them is rejected, and blocks below the
+// last checkpoint are known-good, so full validation may skip their script checks.
+
+const GENESIS_MERKLE_ROOT: [u8; 32] = [
+    0x3b, 0xa3, 0xed, 0xfd, 0x7a, 0x7b, 0x12, 0xb2, 0x7a, 0xc7, 0x2c, 0x3e, 0x67, 0x76, 0x8f, 0x61,
+    0x7f, 0xc8, 0x1b, 0xc3, 0x88, 0x8a, 0x51, 0x32, 0x3a, 0x9f, 0xb8, 0xaa, 0x4b, 0x1e, 0x5e, 0x4a,
+];
+
+const fn genesis(timestamp: u32, bits: u32, nonce: u32) -> BlockHeader {
+    BlockHeader {
+        version: 1,
+        prev_blockhash: [0; 32],
+        merkle_root: GENESIS_MERKLE_ROOT,
+        timestamp,
+        bits,
+        nonce,
+    }
+}
+
+pub const MAINNET_GENESIS: BlockHeader = genesis(1231006505, 0x1d00ffff, 2083236893);
+pub const TESTNET_GENESIS: BlockHeader = genesis(1296688602, 0x1d00ffff, 414098458);
+pub const SIGNET_GENESIS: BlockHeader = genesis(1598918400, 0x1e0377ae, 52613770);
+pub const REGTEST_GENESIS: BlockHeader = genesis(1296688602, 0x207fffff, 2);
+
+// Hashes in the display order used by explorers.
+const MAINNET_CHECKPOINTS: &[(u64, &str)] = &[
+    (
+        11111,
+        "0000000069e244f73d78e8fd29ba2fd2ed618bd6fa2ee92559f542fdb26e7c1d",
+    ),
+    (
+        33333,
+        "000000002dd5588a74784eaa7ab0507a18ad16a236e7b1ce69f00d7ddfb5d0a6",
+    ),
+    (
+        74000,
+        "0000000000573993a3c9e41ce34471c079dcf5f52a0e824a81e7f953b8661a20",
+    ),
+    (
+        105000,
+        "00000000000291ce28027faea320c8d2b054b2e0fe44a773f3eefb151d6bdc97",
+    ),
+    (
+        134444,
+        "00000000000005b12ffd4cd315cd34ffd4a594f430ac814c91184a0d42d2b0fe",
+    ),
+    (
+        168000,
+        "000000000000099e61ea72015e79632f216fe6cb33d7899acb35b75c8303b763",
+    ),
+    (
+        193000,
+        "000000000000059f452a5f7340de6682a977387c17010ff6e6c3bd83ca8b1317",
+    ),
+    (
+        210000,
+        "000000000000048b95347e83192f69cf0366076336c639f9b7228e9ba171342e",
+    ),
+    (
+        216116,
+        "00000000000001b4f4b433e81ee46494af945cf96014816a4e2370f11b23df4e",
+    ),
+    (
+        225430,
+        "00000000000001c108384350f74090433e7fcf79a606b8e797f065b130575932",
+    ),
+    (
+        250000,
+        "000000000000003887df1f29024b06fc2200b55f8af8f35453d7be294df2d214",
+    ),
+    (
+        279000,
+        "0000000000000001ae8c72a0b0c301f67e3afca10e819efa9041e458e9bd7e40",
+    ),
+    (
+        295000,
+        "00000000000000004d9b4ef50f0f9d686fd69db2e03af35a100370c64632a983",
+    ),
+];
+
+const TESTNET_CHECKPOINTS: &[(u64, &str)] = &[(
+    546,
+    "000000002a936ca763904c3c35fce2f3556c559c0214345d31b1bcebf76acb70",
+)];
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Checkpoints {
+    // Sorted by height.
+    checkpoints: Vec<(u64, BlockHash)>,
+}
+
+impl Checkpoints {
+    // `checkpoints` hold hashes in internal byte order, in any order.
+    pub fn new(mut checkpoints: Vec<(u64, BlockHash)>) -> Self {
+        checkpoints.sort_unstable_by_key(|(height, _)| *height);
+        Checkpoints { checkpoints }
+    }
+
+    pub fn for_network(network: Network) -> Self {
+        let table = match network {
+            Network::Mainnet => MAINNET_CHECKPOINTS,
+            Network::Testnet => TESTNET_CHECKPOINTS,
+            Network::Signet | Network::Regtest => &[],
+        };
+        Checkpoints::new(
+            table
+                .iter()
+                // The tables are constants, checked by the tests below
+                .map(|(height, hex)| (*height, hash_from_display_hex(hex).unwrap()))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, height: u64) -> Option<&BlockHash> {
+        self.checkpoints
+            .binary_search_by_key(&height, |(height, _)| *height)
+            .ok()
+            .map(|index| &self.checkpoints[index].1)
+    }
+
+    pub fn last_height(&self) -> Option<u64> {
+        self.checkpoints.last().map(|(height, _)| *height)
+    }
+
+    // Whether a block at `height` is buried under the last checkpoint, so its scripts don't
+    // need to be verified.
+    pub fn covers(&self, height: u64) -> bool {
+        self.last_height()
+            .is_some_and(|last_height| height <= last_height)
+    }
+}
+
+#[cfg(test)]
+mod genesis_tests {
+    use super::*;
+
+    #[test]
+    fn test_genesis_hashes() {
+        let expected = [
+            (
+                Network::Mainnet,
+                "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+            ),
+            (
+                Network::Testnet,
+                "000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943",
+            ),
+            (
+                Network::Signet,
+                "00000008819873e925422c1ff0f99f7cc9bbb232af63a077a480a3633bee1ef6",
+            ),
+            (
+                Network::Regtest,
+                "0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206",
+            ),
+        ];
+
+        for (network, hash) in expected {
+            let genesis = network.genesis_header();
+            assert_eq!(genesis.display_hash(), hash);
+            assert_eq!(genesis.bits, network.max_target_bits());
+            assert!(genesis.check_pow());
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_lookup() {
+        let checkpoints = Checkpoints::for_network(Network::Mainnet);
+        assert_eq!(
+            checkpoints.get(11111),
+            Some(&hash_from_display_hex(MAINNET_CHECKPOINTS[0].1).unwrap())
+        );
+        assert_eq!(checkpoints.get(11112), None);
+        assert_eq!(checkpoints.last_height(), Some(295000));
+        assert!(checkpoints.covers(295000));
+        assert!(!checkpoints.covers(295001));
+
+        let regtest = Checkpoints::for_network(Network::Regtest);
+        assert_eq!(regtest.last_height(), None);
+        assert!(!regtest.covers(0));
+    }
+
+    #[test]
+    fn test_checkpoints_are_sorted() {
+        let checkpoints = Checkpoints::new(vec![(20, [2; 32]), (10, [1; 32])]);
+        assert_eq!(checkpoints.get(10), Some(&[1; 32]));
+        assert_eq!(checkpoints.last_height(), Some(20));
+    }
+}
@@ -0,0 +1,181 @@
+use std::io::Read;
+
+use num_bigint::BigInt;
+
+use crate::block::pow::{bits_to_target, check_pow, difficulty};
+use crate::block::versionbits::signals;
+use crate::encoding::hex::encode_hex;
+use crate::encoding::read_array;
+use crate::hash::hash256;
+use crate::types::errors::{BlockError, EncodingError};
+
+// This module implements the 80-byte block header:
+//
+//     version (4, LE) | prev_blockhash (32) | merkle_root (32) | timestamp (4, LE)
+//     | bits (4, LE) | nonce (4, LE)
+//
+// The block hash is hash256 of these 80 bytes. Hashes are kept in internal byte order, the
+// reverse of the hex shown by explorers.
+
+pub const HEADER_SIZE: usize = 80;
+
+// A block hash in internal byte order.
+pub type BlockHash = [u8; 32];
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BlockHeader {
+    pub version: i32,
+    pub prev_blockhash: BlockHash,
+    pub merkle_root: [u8; 32],
+    pub timestamp: u32,
+    pub bits: u32,
+    pub nonce: u32,
+}
+
+impl BlockHeader {
+    pub fn parse<R: Read>(reader: &mut R) -> Result<Self, EncodingError> {
+        Ok(Self {
+            version: i32::from_le_bytes(read_array(reader)?),
+            prev_blockhash: read_array(reader)?,
+            merkle_root: read_array(reader)?,
+            timestamp: u32::from_le_bytes(read_array(reader)?),
+            bits: u32::from_le_bytes(read_array(reader)?),
+            nonce: u32::from_le_bytes(read_array(reader)?),
+        })
+    }
+
+    pub fn serialize(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0u8; HEADER_SIZE];
+        bytes[..4].copy_from_slice(&self.version.to_le_bytes());
+        bytes[4..36].copy_from_slice(&self.prev_blockhash);
+        bytes[36..68].copy_from_slice(&self.merkle_root);
+        bytes[68..72].copy_from_slice(&self.timestamp.to_le_bytes());
+        bytes[72..76].copy_from_slice(&self.bits.to_le_bytes());
+        bytes[76..].copy_from_slice(&self.nonce.to_le_bytes());
+        bytes
+    }
+
+    pub fn hash(&self) -> BlockHash {
+        hash256(&self.serialize())
+    }
+
+    // The hash as shown by explorers.
+    pub fn display_hash(&self) -> String {
+        let mut hash = self.hash();
+        hash.reverse();
+        encode_hex(&hash)
+    }
+
+    pub fn target(&self) -> Result<BigInt, BlockError> {
+        bits_to_target(self.bits)
+    }
+
+    pub fn difficulty(&self) -> Result<f64, BlockError> {
+        difficulty(self.bits)
+    }
+
+    pub fn check_pow(&self) -> bool {
+        check_pow(&self.hash(), self.bits)
+    }
+
+    // Whether the version uses the BIP9 format (top 3 bits set to 001) and signals `bit`.
+    pub fn is_bip9_signaling(&self, bit: u8) -> bool {
+        signals(self.version, bit)
+    }
+
+    // BIP34 (height in coinbase) came with version 2 blocks.
+    pub fn is_bip34_ready(&self) -> bool {
+        self.version >= 2
+    }
+
+    // BIP66 (strict DER signatures) came with version 3 blocks.
+    pub fn is_bip66_ready(&self) -> bool {
+        self.version >= 3
+    }
+
+    // BIP65 (OP_CHECKLOCKTIMEVERIFY) came with version 4 blocks.
+    pub fn is_bip65_ready(&self) -> bool {
+        self.version >= 4
+    }
+}
+
+#[cfg(test)]
+mod header_tests {
+    use super::*;
+    use crate::encoding::hex::decode_hex;
+
+    const GENESIS_HEADER: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c";
+    const BLOCK_1_HEADER: &str = "010000006fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000982051fd1e4ba744bbbe680e1fee14677ba1a3c3540bf7b1cdb606e857233e0e61bc6649ffff001d01e36299";
+    // The Programming Bitcoin chapter 9 fixture.
+    const BIP9_HEADER: &str = "020000208ec39428b17323fa0ddec8e887b4a7c53b8c0a0a220cfd0000000000000000005b0750fce0a889502d40508d39576821155e9c9e3f5c3157f961db38fd8b25be1e77a759e93c0118a4ffd71d";
+
+    fn header(hex: &str) -> BlockHeader {
+        BlockHeader::parse(&mut decode_hex(hex).unwrap().as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_genesis_header() {
+        let genesis = header(GENESIS_HEADER);
+        assert_eq!(genesis.version, 1);
+        assert_eq!(genesis.prev_blockhash, [0u8; 32]);
+        assert_eq!(genesis.timestamp, 1231006505);
+        assert_eq!(genesis.bits, 0x1d00ffff);
+        assert_eq!(genesis.nonce, 2083236893);
+        assert_eq!(
+            genesis.display_hash(),
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        );
+        assert!(genesis.check_pow());
+        assert_eq!(genesis.difficulty().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        for hex in [GENESIS_HEADER, BLOCK_1_HEADER, BIP9_HEADER] {
+            assert_eq!(encode_hex(&header(hex).serialize()), hex);
+        }
+    }
+
+    #[test]
+    fn test_headers_chain_by_hash() {
+        let genesis = header(GENESIS_HEADER);
+        let block_1 = header(BLOCK_1_HEADER);
+        assert_eq!(block_1.prev_blockhash, genesis.hash());
+        assert_eq!(
+            block_1.display_hash(),
+            "00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048"
+        );
+    }
+
+    #[test]
+    fn test_tampered_header_fails_pow() {
+        let mut genesis = header(GENESIS_HEADER);
+        genesis.nonce += 1;
+        assert!(!genesis.check_pow());
+    }
+
+    #[test]
+    fn test_version_signaling() {
+        let block = header(BIP9_HEADER);
+        assert_eq!(
+            block.display_hash(),
+            "0000000000000000007e9e4c586439b0cdbe13b1370bdd9435d76a644d047523"
+        );
+        assert!(block.is_bip9_signaling(1));
+        assert!(!block.is_bip9_signaling(0));
+        assert!(block.is_bip34_ready() && block.is_bip66_ready() && block.is_bip65_ready());
+
+        let genesis = header(GENESIS_HEADER);
+        assert!(!genesis.is_bip9_signaling(0));
+        assert!(!genesis.is_bip34_ready());
+    }
+
+    #[test]
+    fn test_parse_truncated_header() {
+        let bytes = decode_hex(GENESIS_HEADER).unwrap();
+        assert!(matches!(
+            BlockHeader::parse(&mut &bytes[..79]),
+            Err(EncodingError::UnexpectedEof { .. })
+        ));
+    }
+}
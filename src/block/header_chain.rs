@@ -0,0 +1,1137 @@
+use std::collections::{HashMap, HashSet};
+
+use num_bigint::BigInt;
+
+use crate::block::genesis::Checkpoints;
+use crate::block::header::{BlockHash, BlockHeader};
+use crate::block::header_store::HeaderStore;
+use crate::block::pow::{bits_to_target, calculate_new_bits_with_limit, work, RETARGET_INTERVAL};
+use crate::block::timestamp::{check_timestamp, median_time_past, MEDIAN_TIME_SPAN};
+use crate::network::headers::locator_heights;
+use crate::network::params::Network;
+use crate::types::errors::{BlockError, StoreError};
+
+// This module implements `HeaderChain`, the validated chain of block headers an SPV client
+// syncs before asking for anything else.
+//
+// Every header is checked against its parent and the consensus rules that only need headers:
+//
+// - its bits are the ones the chain expects: unchanged within a 2016 block period and
+//   retargeted at period boundaries. Testnet additionally lets a block use the easiest target
+//   when it comes more than 20 minutes after its parent.
+// - its hash meets the target of those bits.
+// - its timestamp is later than the median of the previous 11 blocks and at most 2 hours
+//   ahead of the adjusted network time.
+// - its version is not below the one made mandatory by BIP34, BIP66 and BIP65.
+// - its hash matches the network's checkpoint at that height, if any.
+//
+// Headers are kept in an index keyed by hash, so competing branches can be followed. The
+// active chain is always the one with the most cumulative work, and switching to another
+// branch is reported as a `Reorg`. Side branches are pruned once they are buried deep enough,
+// and headers whose parent is unknown wait in an orphan pool until the parent connects.
+
+// Upper bound on the orphan pool, so a peer can't make us buffer headers forever. When it is
+// full, the orphan that arrived first makes room for the new one.
+pub const MAX_ORPHANS: usize = 1000;
+// Testnet's minimum difficulty rule applies after twice the target block spacing.
+pub const MIN_DIFFICULTY_SPACING: u32 = 20 * 60;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ChainParams {
+    pub max_target_bits: u32,
+    // Testnet's 20 minute rule.
+    pub allow_min_difficulty_blocks: bool,
+    // Regtest keeps the same bits forever.
+    pub no_retargeting: bool,
+    // Heights from which versions below 2, 3 and 4 are rejected.
+    pub bip34_height: u64,
+    pub bip66_height: u64,
+    pub bip65_height: u64,
+    pub checkpoints: Checkpoints,
+}
+
+impl ChainParams {
+    pub fn for_network(network: Network) -> Self {
+        let (bip34_height, bip66_height, bip65_height) = match network {
+            Network::Mainnet => (227_931, 363_725, 388_381),
+            Network::Testnet => (21_111, 330_776, 581_885),
+            Network::Signet | Network::Regtest => (1, 1, 1),
+        };
+        ChainParams {
+            max_target_bits: network.max_target_bits(),
+            allow_min_difficulty_blocks: network == Network::Testnet || network == Network::Regtest,
+            no_retargeting: network == Network::Regtest,
+            bip34_height,
+            bip66_height,
+            bip65_height,
+            checkpoints: Checkpoints::for_network(network),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum HeaderStatus {
+    // The header extended the tip to `height`, along with any orphans that were waiting on it.
+    Connected { height: u64 },
+    // The header (or orphans waiting on it) made another branch the most-work chain.
+    Reorg(Reorg),
+    // The header is valid but its branch has no more work than the active chain.
+    SideBranch { height: u64 },
+    Orphan,
+    AlreadyKnown,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Reorg {
+    // Height of the last block both branches share.
+    pub fork_height: u64,
+    // The blocks that left the active chain, from the old tip down.
+    pub disconnected: Vec<BlockHash>,
+    // The blocks that replaced them, from the fork up to the new tip.
+    pub connected: Vec<BlockHash>,
+}
+
+// Side branches more than this many blocks below the tip are forgotten.
+pub const DEFAULT_PRUNE_DEPTH: u64 = 288;
+
+struct IndexEntry {
+    header: BlockHeader,
+    hash: BlockHash,
+    height: u64,
+    // Cumulative work up to and including this block.
+    chainwork: BigInt,
+}
+
+pub struct HeaderChain {
+    params: ChainParams,
+    // Every known header, on the active chain or not. Parents are found through
+    // `prev_blockhash`.
+    index: HashMap<BlockHash, IndexEntry>,
+    // Hashes of the most-work chain, by height.
+    active: Vec<BlockHash>,
+    // Indexed headers that are not on the active chain.
+    side: HashSet<BlockHash>,
+    prune_depth: u64,
+    // Orphans keyed by the hash of the parent they are waiting for, each with its arrival
+    // number.
+    orphans: HashMap<BlockHash, Vec<(u64, BlockHeader)>>,
+    orphan_arrivals: u64,
+}
+
+impl HeaderChain {
+    // Starts a chain at `genesis`, whose proof of work is checked but which has no parent to
+    // validate against.
+    pub fn new(genesis: BlockHeader, params: ChainParams) -> Result<Self, BlockError> {
+        if !genesis.check_pow() {
+            return Err(BlockError::InsufficientPow { bits: genesis.bits });
+        }
+        let chainwork = work(genesis.bits)?;
+        Ok(HeaderChain::from_active(params, vec![(genesis, chainwork)]))
+    }
+
+    // A chain at the network's genesis, validated with its own parameters and checkpoints.
+    pub fn for_network(network: Network) -> Result<Self, BlockError> {
+        HeaderChain::new(network.genesis_header(), ChainParams::for_network(network))
+    }
+
+    // Resumes a chain saved to `store`, which must hold at least the genesis header. Its
+    // headers were validated before being stored, so only the parameters for new headers are
+    // needed.
+    pub fn from_store(store: &HeaderStore, params: ChainParams) -> Result<Self, StoreError> {
+        if store.height().is_none() {
+            return Err(StoreError::Empty);
+        }
+        let entries = store
+            .headers()
+            .iter()
+            .cloned()
+            .zip(store.chainwork().iter().cloned())
+            .collect();
+        Ok(HeaderChain::from_active(params, entries))
+    }
+
+    fn from_active(params: ChainParams, entries: Vec<(BlockHeader, BigInt)>) -> Self {
+        let mut chain = HeaderChain {
+            params,
+            index: HashMap::with_capacity(entries.len()),
+            active: Vec::with_capacity(entries.len()),
+            side: HashSet::new(),
+            prune_depth: DEFAULT_PRUNE_DEPTH,
+            orphans: HashMap::new(),
+            orphan_arrivals: 0,
+        };
+        for (height, (header, chainwork)) in entries.into_iter().enumerate() {
+            let hash = header.hash();
+            chain.active.push(hash);
+            chain.index.insert(
+                hash,
+                IndexEntry {
+                    header,
+                    hash,
+                    height: height as u64,
+                    chainwork,
+                },
+            );
+        }
+        chain
+    }
+
+    pub fn with_prune_depth(mut self, prune_depth: u64) -> Self {
+        self.prune_depth = prune_depth;
+        self
+    }
+
+    pub fn params(&self) -> &ChainParams {
+        &self.params
+    }
+
+    pub fn tip(&self) -> &BlockHeader {
+        &self.tip_entry().header
+    }
+
+    pub fn tip_hash(&self) -> BlockHash {
+        *self.active.last().unwrap()
+    }
+
+    pub fn height(&self) -> u64 {
+        self.active.len() as u64 - 1
+    }
+
+    pub fn chainwork(&self) -> &BigInt {
+        &self.tip_entry().chainwork
+    }
+
+    pub fn chainwork_at(&self, height: u64) -> Option<&BigInt> {
+        Some(&self.active_entry(height)?.chainwork)
+    }
+
+    // Whether `hash` is on the active chain.
+    pub fn contains(&self, hash: &BlockHash) -> bool {
+        self.height_of(hash).is_some()
+    }
+
+    // Height of `hash` on the active chain.
+    pub fn height_of(&self, hash: &BlockHash) -> Option<u64> {
+        let entry = self.index.get(hash)?;
+        self.is_active(entry).then_some(entry.height)
+    }
+
+    // Whether `hash` is on the active chain or on a side branch that hasn't been pruned.
+    pub fn is_known(&self, hash: &BlockHash) -> bool {
+        self.index.contains_key(hash)
+    }
+
+    pub fn header_at(&self, height: u64) -> Option<&BlockHeader> {
+        Some(&self.active_entry(height)?.header)
+    }
+
+    // Hashes of the active chain at `locator_heights`, to ask a peer for the headers after
+    // our tip with `getheaders`.
+    pub fn locator(&self) -> Vec<BlockHash> {
+        locator_heights(self.height())
+            .into_iter()
+            .map(|height| self.active[height as usize])
+            .collect()
+    }
+
+    // The median-time-past of the active block at `height`: the clock its children's
+    // timestamps and time-based locktimes are checked against.
+    pub fn median_time_past(&self, height: u64) -> Option<u64> {
+        let entry = self.active_entry(height)?;
+        Some(median_time_past(&self.median_time_window(entry)))
+    }
+
+    // The bits a header at `height` of the active chain must have if its timestamp is
+    // `timestamp`, which matters where minimum-difficulty blocks are allowed. Its parent
+    // must be known, so `height` can be at most one past the tip.
+    pub fn expected_bits_for_height(&self, height: u64, timestamp: u32) -> Result<u32, BlockError> {
+        let parent = height
+            .checked_sub(1)
+            .and_then(|parent| self.active_entry(parent))
+            .ok_or(BlockError::MissingHeaders {
+                needed: height,
+                available: self.height() + 1,
+            })?;
+        self.next_bits(parent, timestamp)
+    }
+
+    // Of two indexed headers, the one whose chain has more work, or `a` on a tie. None if
+    // either is unknown. Height doesn't matter: a shorter chain of harder blocks wins.
+    pub fn best_of(&self, a: &BlockHash, b: &BlockHash) -> Option<BlockHash> {
+        let (a, b) = (self.index.get(a)?, self.index.get(b)?);
+        Some(if b.chainwork > a.chainwork {
+            b.hash
+        } else {
+            a.hash
+        })
+    }
+
+    pub fn orphan_count(&self) -> usize {
+        self.orphans.values().map(Vec::len).sum()
+    }
+
+    pub fn side_branch_count(&self) -> usize {
+        self.side.len()
+    }
+
+    // `adjusted_time` is the node's network-adjusted clock, in seconds since the epoch.
+    pub fn accept(
+        &mut self,
+        header: BlockHeader,
+        adjusted_time: u64,
+    ) -> Result<HeaderStatus, BlockError> {
+        let hash = header.hash();
+        if self.is_known(&hash) {
+            return Ok(HeaderStatus::AlreadyKnown);
+        }
+        if !self.is_known(&header.prev_blockhash) {
+            self.add_orphan(header)?;
+            return Ok(HeaderStatus::Orphan);
+        }
+
+        let old_tip = self.tip_hash();
+        let old_height = self.height();
+        let height = self.connect(header, adjusted_time)?;
+        self.connect_orphans(hash, adjusted_time);
+
+        let status = if self.tip_hash() == old_tip {
+            HeaderStatus::SideBranch { height }
+        } else if self.active.get(old_height as usize) == Some(&old_tip) {
+            HeaderStatus::Connected {
+                height: self.height(),
+            }
+        } else {
+            HeaderStatus::Reorg(self.reorg_from(old_tip))
+        };
+        self.prune();
+        Ok(status)
+    }
+
+    // Validates `header` against its parent, indexes it and makes its branch the active one
+    // if it now has the most work. Returns its height.
+    fn connect(&mut self, header: BlockHeader, adjusted_time: u64) -> Result<u64, BlockError> {
+        let parent = &self.index[&header.prev_blockhash];
+        let height = parent.height + 1;
+
+        let expected = self.next_bits(parent, header.timestamp)?;
+        if header.bits != expected {
+            return Err(BlockError::UnexpectedBits {
+                expected,
+                actual: header.bits,
+            });
+        }
+        if !header.check_pow() {
+            return Err(BlockError::InsufficientPow { bits: header.bits });
+        }
+
+        check_timestamp(
+            u64::from(header.timestamp),
+            &self.median_time_window(parent),
+            adjusted_time,
+        )?;
+
+        let minimum_version = if height >= self.params.bip65_height {
+            4
+        } else if height >= self.params.bip66_height {
+            3
+        } else if height >= self.params.bip34_height {
+            2
+        } else {
+            1
+        };
+        if header.version < minimum_version {
+            return Err(BlockError::BadVersion {
+                version: header.version,
+                height,
+            });
+        }
+
+        let hash = header.hash();
+        if let Some(checkpoint) = self.params.checkpoints.get(height) {
+            if hash != *checkpoint {
+                return Err(BlockError::CheckpointMismatch { height });
+            }
+        }
+
+        let chainwork = &parent.chainwork + work(header.bits)?;
+        let tip = self.tip_hash();
+        let extends_tip = header.prev_blockhash == tip;
+        self.index.insert(
+            hash,
+            IndexEntry {
+                header,
+                hash,
+                height,
+                chainwork,
+            },
+        );
+
+        if extends_tip {
+            self.active.push(hash);
+        } else {
+            self.side.insert(hash);
+            // On a tie the branch seen first stays active
+            if self.best_of(&tip, &hash) == Some(hash) {
+                self.activate(hash);
+            }
+        }
+        Ok(height)
+    }
+
+    // Makes the branch ending at `tip` the active chain.
+    fn activate(&mut self, tip: BlockHash) {
+        let mut branch = Vec::new();
+        let mut entry = &self.index[&tip];
+        while !self.is_active(entry) {
+            branch.push(entry.hash);
+            entry = &self.index[&entry.header.prev_blockhash];
+        }
+        let fork_height = entry.height;
+
+        for hash in self.active.drain(fork_height as usize + 1..) {
+            self.side.insert(hash);
+        }
+        for hash in branch.into_iter().rev() {
+            self.side.remove(&hash);
+            self.active.push(hash);
+        }
+    }
+
+    // Describes the switch from the branch ending at `old_tip` to the active chain.
+    fn reorg_from(&self, old_tip: BlockHash) -> Reorg {
+        let disconnected: Vec<BlockHash> = self
+            .ancestors(&self.index[&old_tip])
+            .take_while(|entry| !self.is_active(entry))
+            .map(|entry| entry.hash)
+            .collect();
+        let fork_height = self.index[&old_tip].height - disconnected.len() as u64;
+        Reorg {
+            fork_height,
+            disconnected,
+            connected: self.active[fork_height as usize + 1..].to_vec(),
+        }
+    }
+
+    // Orphans can't be validated against their parent yet, but must at least carry the proof
+    // of work their bits claim, with bits no easier than the network allows. Without these
+    // checks, free headers could push out the real ones.
+    fn add_orphan(&mut self, header: BlockHeader) -> Result<(), BlockError> {
+        if bits_to_target(header.bits)? > bits_to_target(self.params.max_target_bits)? {
+            return Err(BlockError::TargetTooEasy { bits: header.bits });
+        }
+        if !header.check_pow() {
+            return Err(BlockError::InsufficientPow { bits: header.bits });
+        }
+        let siblings = self.orphans.entry(header.prev_blockhash).or_default();
+        if siblings.iter().any(|(_, sibling)| *sibling == header) {
+            return Ok(());
+        }
+        siblings.push((self.orphan_arrivals, header));
+        self.orphan_arrivals += 1;
+        if self.orphan_count() > MAX_ORPHANS {
+            self.evict_oldest_orphan();
+        }
+        Ok(())
+    }
+
+    fn evict_oldest_orphan(&mut self) {
+        let oldest = self
+            .orphans
+            .iter()
+            .flat_map(|(parent, siblings)| {
+                (siblings.iter().enumerate()).map(|(i, (arrival, _))| (*arrival, *parent, i))
+            })
+            .min();
+        if let Some((_, parent, i)) = oldest {
+            let siblings = self.orphans.get_mut(&parent).unwrap();
+            siblings.remove(i);
+            if siblings.is_empty() {
+                self.orphans.remove(&parent);
+            }
+        }
+    }
+
+    // Connects the orphans waiting on `parent`, then theirs, and so on. Orphans that fail
+    // validation are dropped.
+    fn connect_orphans(&mut self, parent: BlockHash, adjusted_time: u64) {
+        let mut parents = vec![parent];
+        while let Some(parent) = parents.pop() {
+            for (_, orphan) in self.orphans.remove(&parent).unwrap_or_default() {
+                let hash = orphan.hash();
+                if self.connect(orphan, adjusted_time).is_ok() {
+                    parents.push(hash);
+                }
+            }
+        }
+    }
+
+    // Forgets the side branches that forked off the active chain deeper than the prune depth.
+    // Whole branches go at once, judged by their fork point: dropping only their buried part
+    // would leave blocks whose parent is no longer indexed.
+    fn prune(&mut self) {
+        let min_height = self.height().saturating_sub(self.prune_depth);
+        let pruned: Vec<BlockHash> = self
+            .side
+            .iter()
+            .filter(|hash| self.fork_height(&self.index[*hash]) < min_height)
+            .copied()
+            .collect();
+        for hash in pruned {
+            self.side.remove(&hash);
+            self.index.remove(&hash);
+        }
+    }
+
+    // Height of the last block on the active chain that `entry` descends from.
+    fn fork_height(&self, entry: &IndexEntry) -> u64 {
+        self.ancestors(entry)
+            .find(|entry| self.is_active(entry))
+            .map_or(0, |entry| entry.height)
+    }
+
+    // The timestamps of `entry` and the ancestors its median-time-past is taken over, oldest
+    // first.
+    fn median_time_window(&self, entry: &IndexEntry) -> Vec<u64> {
+        let mut timestamps: Vec<u64> = self
+            .ancestors(entry)
+            .take(MEDIAN_TIME_SPAN)
+            .map(|entry| u64::from(entry.header.timestamp))
+            .collect();
+        timestamps.reverse();
+        timestamps
+    }
+
+    fn tip_entry(&self) -> &IndexEntry {
+        &self.index[&self.tip_hash()]
+    }
+
+    fn active_entry(&self, height: u64) -> Option<&IndexEntry> {
+        let hash = self.active.get(usize::try_from(height).ok()?)?;
+        Some(&self.index[hash])
+    }
+
+    fn is_active(&self, entry: &IndexEntry) -> bool {
+        self.active.get(entry.height as usize) == Some(&entry.hash)
+    }
+
+    // `entry` and then its ancestors, down to genesis.
+    fn ancestors<'a>(&'a self, entry: &'a IndexEntry) -> impl Iterator<Item = &'a IndexEntry> {
+        std::iter::successors(Some(entry), |entry| {
+            self.index.get(&entry.header.prev_blockhash)
+        })
+    }
+
+    // The ancestor of `entry` at `height`, jumping through the active chain once the walk
+    // reaches it.
+    fn ancestor_at<'a>(&'a self, mut entry: &'a IndexEntry, height: u64) -> &'a IndexEntry {
+        while entry.height > height && !self.is_active(entry) {
+            entry = &self.index[&entry.header.prev_blockhash];
+        }
+        if entry.height > height {
+            self.active_entry(height).unwrap()
+        } else {
+            entry
+        }
+    }
+
+    // The bits a header extending `parent` at `timestamp` must have.
+    fn next_bits(&self, parent: &IndexEntry, timestamp: u32) -> Result<u32, BlockError> {
+        let height = parent.height + 1;
+        if self.params.no_retargeting {
+            return Ok(parent.header.bits);
+        }
+
+        if !height.is_multiple_of(RETARGET_INTERVAL) {
+            if !self.params.allow_min_difficulty_blocks {
+                return Ok(parent.header.bits);
+            }
+            if timestamp
+                > parent
+                    .header
+                    .timestamp
+                    .saturating_add(MIN_DIFFICULTY_SPACING)
+            {
+                return Ok(self.params.max_target_bits);
+            }
+            // Otherwise the bits of the last block that did not use the minimum difficulty
+            let last_regular = self
+                .ancestors(parent)
+                .find(|entry| {
+                    entry.height.is_multiple_of(RETARGET_INTERVAL)
+                        || entry.header.bits != self.params.max_target_bits
+                })
+                .unwrap_or(parent);
+            return Ok(last_regular.header.bits);
+        }
+
+        // Bitcoin Core measures the period from its first to its last block, which spans one
+        // block less than the 2016 it is compared against. The off-by-one is consensus now.
+        let first = self.ancestor_at(parent, height - RETARGET_INTERVAL);
+        let time_differential = u64::from(
+            parent
+                .header
+                .timestamp
+                .saturating_sub(first.header.timestamp),
+        );
+        calculate_new_bits_with_limit(
+            parent.header.bits,
+            time_differential,
+            self.params.max_target_bits,
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod header_chain_tests {
+    use super::*;
+    use crate::block::pow::chainwork;
+
+    pub(crate) const EASY_BITS: u32 = 0x207fffff;
+    // 256 times harder than `EASY_BITS`, still quick to mine.
+    const HARDER_BITS: u32 = 0x2000ffff;
+    const GENESIS_TIME: u32 = 1_296_688_602;
+    const NOW: u64 = u64::MAX / 2;
+
+    // Finds a nonce meeting `bits` by brute force.
+    pub(crate) fn mine_header(prev: &BlockHeader, bits: u32, timestamp: u32) -> BlockHeader {
+        let mut header = BlockHeader {
+            version: 4,
+            prev_blockhash: prev.hash(),
+            merkle_root: [0x42; 32],
+            timestamp,
+            bits,
+            nonce: 0,
+        };
+        while !header.check_pow() {
+            header.nonce += 1;
+        }
+        header
+    }
+
+    pub(crate) fn genesis(bits: u32) -> BlockHeader {
+        let mut genesis = BlockHeader {
+            version: 1,
+            prev_blockhash: [0; 32],
+            merkle_root: [0x42; 32],
+            timestamp: GENESIS_TIME,
+            bits,
+            nonce: 0,
+        };
+        while !genesis.check_pow() {
+            genesis.nonce += 1;
+        }
+        genesis
+    }
+
+    // Regtest's easy target, but with mainnet's retargeting rules.
+    pub(crate) fn retargeting_params() -> ChainParams {
+        ChainParams {
+            max_target_bits: EASY_BITS,
+            allow_min_difficulty_blocks: false,
+            no_retargeting: false,
+            bip34_height: 1,
+            bip66_height: 1,
+            bip65_height: 1,
+            checkpoints: Checkpoints::default(),
+        }
+    }
+
+    // Mines `count` headers on top of the tip, spaced by `spacing` seconds, with the bits
+    // the chain expects.
+    pub(crate) fn extend(chain: &mut HeaderChain, count: usize, spacing: u32) {
+        let tip = chain.tip_hash();
+        extend_branch(chain, tip, count, spacing, 0x42);
+    }
+
+    // Mines `count` headers on top of `parent`, spaced by `spacing` seconds, with the bits the
+    // chain expects. `tag` goes in the merkle root to tell branches apart.
+    fn extend_branch(
+        chain: &mut HeaderChain,
+        parent: BlockHash,
+        count: usize,
+        spacing: u32,
+        tag: u8,
+    ) -> Vec<HeaderStatus> {
+        let mut parent = parent;
+        let mut statuses = Vec::new();
+        for _ in 0..count {
+            let header = mine_on(chain, parent, spacing, tag);
+            parent = header.hash();
+            statuses.push(chain.accept(header, NOW).unwrap());
+        }
+        statuses
+    }
+
+    fn mine_on(chain: &HeaderChain, parent: BlockHash, spacing: u32, tag: u8) -> BlockHeader {
+        let parent = &chain.index[&parent];
+        let mut header = BlockHeader {
+            version: 4,
+            prev_blockhash: parent.hash,
+            merkle_root: [tag; 32],
+            timestamp: parent.header.timestamp + spacing,
+            bits: 0,
+            nonce: 0,
+        };
+        header.bits = chain.next_bits(parent, header.timestamp).unwrap();
+        while !header.check_pow() {
+            header.nonce += 1;
+        }
+        header
+    }
+
+    #[test]
+    fn test_two_retarget_periods() {
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        // Blocks come every 5 minutes, so the difficulty doubles at height 2016
+        extend(&mut chain, 4031, 300);
+
+        assert_eq!(chain.height(), 4031);
+        let retargeted = chain.header_at(2016).unwrap().bits;
+        assert_eq!(chain.header_at(2015).unwrap().bits, EASY_BITS);
+        assert_eq!(
+            retargeted,
+            calculate_new_bits_with_limit(EASY_BITS, 2015 * 300, EASY_BITS).unwrap()
+        );
+        assert_eq!(chain.tip().bits, retargeted);
+        assert_eq!(
+            *chain.chainwork(),
+            chainwork((0..=4031).map(|height| chain.header_at(height).unwrap().bits)).unwrap()
+        );
+        assert_eq!(chain.height_of(&chain.tip_hash()), Some(4031));
+    }
+
+    #[test]
+    fn test_reject_insufficient_pow() {
+        let mut chain = HeaderChain::new(genesis(HARDER_BITS), retargeting_params()).unwrap();
+        let mut header = mine_header(chain.tip(), HARDER_BITS, GENESIS_TIME + 600);
+        while header.check_pow() {
+            header.nonce += 1;
+        }
+
+        assert_eq!(
+            chain.accept(header, NOW),
+            Err(BlockError::InsufficientPow { bits: HARDER_BITS })
+        );
+        assert_eq!(chain.height(), 0);
+    }
+
+    #[test]
+    fn test_reject_wrong_bits() {
+        let mut chain = HeaderChain::new(genesis(HARDER_BITS), retargeting_params()).unwrap();
+        let header = mine_header(chain.tip(), EASY_BITS, GENESIS_TIME + 600);
+
+        assert_eq!(
+            chain.accept(header, NOW),
+            Err(BlockError::UnexpectedBits {
+                expected: HARDER_BITS,
+                actual: EASY_BITS,
+            })
+        );
+    }
+
+    #[test]
+    fn test_reject_timestamp_not_after_median_time_past() {
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        extend(&mut chain, 11, 600);
+        // The median of the last 11 blocks is the 6th of them
+        let median_time_past = chain.header_at(6).unwrap().timestamp;
+        assert_eq!(
+            chain.median_time_past(11),
+            Some(u64::from(median_time_past))
+        );
+        assert_eq!(chain.median_time_past(0), Some(GENESIS_TIME.into()));
+        assert_eq!(chain.median_time_past(12), None);
+        let header = mine_header(chain.tip(), EASY_BITS, median_time_past);
+
+        assert_eq!(
+            chain.accept(header, NOW),
+            Err(BlockError::TimestampTooOld {
+                timestamp: u64::from(median_time_past),
+                median_time_past: u64::from(median_time_past),
+            })
+        );
+        let header = mine_header(chain.tip(), EASY_BITS, median_time_past + 1);
+        assert!(chain.accept(header, NOW).is_ok());
+    }
+
+    #[test]
+    fn test_reject_timestamp_too_far_in_the_future() {
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        let now = u64::from(GENESIS_TIME) + 600;
+        let header = mine_header(chain.tip(), EASY_BITS, GENESIS_TIME + 600 + 2 * 60 * 60 + 1);
+
+        assert!(matches!(
+            chain.accept(header, now),
+            Err(BlockError::TimestampTooNew { .. })
+        ));
+    }
+
+    #[test]
+    fn test_reject_outdated_version() {
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        let mut header = mine_header(chain.tip(), EASY_BITS, GENESIS_TIME + 600);
+        header.version = 3;
+        while !header.check_pow() {
+            header.nonce += 1;
+        }
+
+        assert_eq!(
+            chain.accept(header, NOW),
+            Err(BlockError::BadVersion {
+                version: 3,
+                height: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_testnet_min_difficulty_rule() {
+        let params = ChainParams {
+            allow_min_difficulty_blocks: true,
+            ..retargeting_params()
+        };
+        let mut chain = HeaderChain::new(genesis(HARDER_BITS), params).unwrap();
+        extend(&mut chain, 3, 600);
+
+        // After 20 minutes the easiest target is allowed...
+        let time = chain.tip().timestamp + MIN_DIFFICULTY_SPACING + 1;
+        let easy = mine_header(chain.tip(), EASY_BITS, time);
+        assert_eq!(
+            chain.accept(easy, NOW),
+            Ok(HeaderStatus::Connected { height: 4 })
+        );
+
+        assert_eq!(
+            chain.expected_bits_for_height(5, time + 600),
+            Ok(HARDER_BITS)
+        );
+        assert_eq!(
+            chain.expected_bits_for_height(5, time + MIN_DIFFICULTY_SPACING + 1),
+            Ok(EASY_BITS)
+        );
+        assert_eq!(
+            chain.expected_bits_for_height(6, time),
+            Err(BlockError::MissingHeaders {
+                needed: 6,
+                available: 5
+            })
+        );
+
+        // ...but not before, and the next block goes back to the regular difficulty
+        let too_soon = mine_header(chain.tip(), EASY_BITS, time + 600);
+        assert_eq!(
+            chain.accept(too_soon, NOW),
+            Err(BlockError::UnexpectedBits {
+                expected: HARDER_BITS,
+                actual: EASY_BITS,
+            })
+        );
+        let regular = mine_header(chain.tip(), HARDER_BITS, time + 600);
+        assert!(chain.accept(regular, NOW).is_ok());
+    }
+
+    #[test]
+    fn test_mainnet_rejects_min_difficulty_blocks() {
+        let mut chain = HeaderChain::new(genesis(HARDER_BITS), retargeting_params()).unwrap();
+        let late = mine_header(chain.tip(), EASY_BITS, GENESIS_TIME + 3600);
+
+        assert!(matches!(
+            chain.accept(late, NOW),
+            Err(BlockError::UnexpectedBits { .. })
+        ));
+    }
+
+    #[test]
+    fn test_out_of_order_headers_wait_in_orphan_pool() {
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        let first = mine_header(chain.tip(), EASY_BITS, GENESIS_TIME + 600);
+        let second = mine_header(&first, EASY_BITS, GENESIS_TIME + 1200);
+        let third = mine_header(&second, EASY_BITS, GENESIS_TIME + 1800);
+
+        assert_eq!(chain.accept(third.clone(), NOW), Ok(HeaderStatus::Orphan));
+        assert_eq!(chain.accept(second, NOW), Ok(HeaderStatus::Orphan));
+        assert_eq!(chain.orphan_count(), 2);
+
+        assert_eq!(
+            chain.accept(first.clone(), NOW),
+            Ok(HeaderStatus::Connected { height: 3 })
+        );
+        assert_eq!(chain.orphan_count(), 0);
+        assert_eq!(chain.tip(), &third);
+        assert_eq!(chain.accept(first, NOW), Ok(HeaderStatus::AlreadyKnown));
+    }
+
+    #[test]
+    fn test_orphan_pool_checks_pow_and_evicts_the_oldest() {
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        let first = mine_header(chain.tip(), EASY_BITS, GENESIS_TIME + 600);
+        let second = mine_header(&first, EASY_BITS, GENESIS_TIME + 1200);
+
+        // Headers without proof of work, or easier than the network allows, are refused
+        let free = BlockHeader {
+            bits: 0,
+            ..second.clone()
+        };
+        assert_eq!(
+            chain.accept(free, NOW),
+            Err(BlockError::InsufficientPow { bits: 0 })
+        );
+        let too_easy = mine_header(&first, 0x2100ffff, GENESIS_TIME + 1200);
+        assert_eq!(
+            chain.accept(too_easy, NOW),
+            Err(BlockError::TargetTooEasy { bits: 0x2100ffff })
+        );
+        assert_eq!(chain.orphan_count(), 0);
+
+        // A full pool drops its oldest orphans rather than the new ones
+        for tag in 0..=MAX_ORPHANS {
+            let mut orphan = mine_header(&second, EASY_BITS, GENESIS_TIME + 1800);
+            orphan.merkle_root[..8].copy_from_slice(&(tag as u64).to_le_bytes());
+            while !orphan.check_pow() {
+                orphan.nonce += 1;
+            }
+            assert_eq!(chain.accept(orphan, NOW), Ok(HeaderStatus::Orphan));
+        }
+        assert_eq!(chain.orphan_count(), MAX_ORPHANS);
+        assert_eq!(chain.accept(second.clone(), NOW), Ok(HeaderStatus::Orphan));
+        assert_eq!(chain.orphan_count(), MAX_ORPHANS);
+
+        // The newest orphans connect once their parent arrives
+        assert!(matches!(
+            chain.accept(first, NOW),
+            Ok(HeaderStatus::Connected { height: 3 })
+        ));
+        assert_eq!(chain.header_at(2), Some(&second));
+    }
+
+    #[test]
+    fn test_fork_with_more_work_triggers_reorg() {
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        extend(&mut chain, 20, 600);
+        let old_branch: Vec<BlockHash> = (18..=20)
+            .map(|height| chain.header_at(height).unwrap().hash())
+            .collect();
+        let fork_point = chain.header_at(17).unwrap().hash();
+
+        // As much work as the active chain is not enough to switch
+        let statuses = extend_branch(&mut chain, fork_point, 3, 601, 0x43);
+        assert_eq!(statuses[2], HeaderStatus::SideBranch { height: 20 });
+        assert_eq!(chain.header_at(20).unwrap().hash(), old_branch[2]);
+
+        let fork_tip = chain
+            .index
+            .values()
+            .find(|entry| entry.height == 20 && entry.header.merkle_root == [0x43; 32]);
+        let fork_tip = fork_tip.unwrap().hash;
+        let statuses = extend_branch(&mut chain, fork_tip, 1, 601, 0x43);
+        let HeaderStatus::Reorg(reorg) = &statuses[0] else {
+            panic!("expected a reorg, got {:?}", statuses[0]);
+        };
+
+        assert_eq!(reorg.fork_height, 17);
+        assert_eq!(
+            reorg.disconnected,
+            old_branch.iter().rev().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(reorg.connected.len(), 4);
+        assert_eq!(chain.height(), 21);
+        for (offset, hash) in reorg.connected.iter().enumerate() {
+            assert_eq!(&chain.header_at(18 + offset as u64).unwrap().hash(), hash);
+            assert_eq!(chain.height_of(hash), Some(18 + offset as u64));
+        }
+        // The old branch is kept as a side branch, not on the active chain
+        assert!(!chain.contains(&old_branch[0]));
+        assert!(chain.is_known(&old_branch[0]));
+        assert_eq!(
+            *chain.chainwork(),
+            chainwork((0..=21).map(|height| chain.header_at(height).unwrap().bits)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_lighter_fork_does_not_switch() {
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        extend(&mut chain, 20, 600);
+        let tip = chain.tip_hash();
+        let fork_point = chain.header_at(10).unwrap().hash();
+
+        let statuses = extend_branch(&mut chain, fork_point, 2, 601, 0x43);
+        assert_eq!(
+            statuses,
+            vec![
+                HeaderStatus::SideBranch { height: 11 },
+                HeaderStatus::SideBranch { height: 12 }
+            ]
+        );
+        assert_eq!(chain.tip_hash(), tip);
+        assert_eq!(chain.side_branch_count(), 2);
+    }
+
+    #[test]
+    fn test_shorter_branch_with_more_work_wins() {
+        let params = ChainParams {
+            allow_min_difficulty_blocks: true,
+            ..retargeting_params()
+        };
+        let mut chain = HeaderChain::new(genesis(HARDER_BITS), params).unwrap();
+        let genesis_hash = chain.tip_hash();
+        // Slow blocks at the minimum difficulty...
+        extend_branch(
+            &mut chain,
+            genesis_hash,
+            3,
+            MIN_DIFFICULTY_SPACING + 1,
+            0x42,
+        );
+        let long_tip = chain.tip_hash();
+        assert_eq!(chain.tip().bits, EASY_BITS);
+        assert_eq!(chain.best_of(&long_tip, &long_tip), Some(long_tip));
+
+        // ...lose to fewer blocks at the regular one
+        extend_branch(&mut chain, genesis_hash, 2, 600, 0x43);
+        let short_tip = chain.tip_hash();
+        assert_ne!(short_tip, long_tip);
+        assert_eq!(chain.height(), 2);
+        assert_eq!(chain.tip().bits, HARDER_BITS);
+        assert_eq!(chain.best_of(&long_tip, &short_tip), Some(short_tip));
+        assert_eq!(chain.best_of(&short_tip, &long_tip), Some(short_tip));
+        assert_eq!(chain.best_of(&short_tip, &[0xab; 32]), None);
+    }
+
+    #[test]
+    fn test_orphans_completing_a_heavier_fork_trigger_reorg() {
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        extend(&mut chain, 5, 600);
+        let mut fork = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        extend(&mut fork, 3, 600);
+        let fork_point = fork.tip_hash();
+        extend_branch(&mut fork, fork_point, 4, 601, 0x43);
+
+        // The fork's own blocks arrive last first
+        for height in (5..=7).rev() {
+            let header = fork.header_at(height).unwrap().clone();
+            assert_eq!(chain.accept(header, NOW), Ok(HeaderStatus::Orphan));
+        }
+        let first = fork.header_at(4).unwrap().clone();
+        let Ok(HeaderStatus::Reorg(reorg)) = chain.accept(first, NOW) else {
+            panic!("expected a reorg");
+        };
+        assert_eq!(reorg.fork_height, 3);
+        assert_eq!(reorg.disconnected.len(), 2);
+        assert_eq!(reorg.connected.len(), 4);
+        assert_eq!(chain.tip(), fork.tip());
+    }
+
+    #[test]
+    fn test_side_branches_are_pruned() {
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params())
+            .unwrap()
+            .with_prune_depth(5);
+        extend(&mut chain, 10, 600);
+        let fork_point = chain.header_at(8).unwrap().hash();
+        extend_branch(&mut chain, fork_point, 1, 601, 0x43);
+        assert_eq!(chain.side_branch_count(), 1);
+
+        // The branch goes once its fork point at height 8 is more than 5 blocks deep
+        extend(&mut chain, 3, 600);
+        assert_eq!(chain.side_branch_count(), 1);
+        extend(&mut chain, 1, 600);
+        assert_eq!(chain.side_branch_count(), 0);
+        assert_eq!(chain.index.len(), 15);
+    }
+
+    #[test]
+    fn test_pruned_fork_that_gains_work_reconnects_through_orphans() {
+        let mut fork = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        extend(&mut fork, 7, 600);
+        let fork_point = fork.tip_hash();
+        extend_branch(&mut fork, fork_point, 9, 601, 0x43);
+        let fork_header = |height| fork.header_at(height).unwrap().clone();
+
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params())
+            .unwrap()
+            .with_prune_depth(5);
+        extend(&mut chain, 10, 600);
+        for height in 8..=10 {
+            assert_eq!(
+                chain.accept(fork_header(height), NOW),
+                Ok(HeaderStatus::SideBranch { height })
+            );
+        }
+        extend(&mut chain, 5, 600);
+        assert_eq!(chain.height(), 15);
+        assert_eq!(chain.side_branch_count(), 0);
+        assert!(!chain.is_known(&fork_header(10).hash()));
+
+        // The rest of the fork, now heavier than the active chain, can't connect to the
+        // pruned blocks and waits for them
+        for height in (9..=16).rev() {
+            assert_eq!(
+                chain.accept(fork_header(height), NOW),
+                Ok(HeaderStatus::Orphan)
+            );
+        }
+        let Ok(HeaderStatus::Reorg(reorg)) = chain.accept(fork_header(8), NOW) else {
+            panic!("expected a reorg");
+        };
+        assert_eq!(reorg.fork_height, 7);
+        assert_eq!(reorg.disconnected.len(), 8);
+        assert_eq!(chain.tip(), fork.tip());
+        assert_eq!(chain.orphan_count(), 0);
+    }
+
+    #[test]
+    fn test_genesis_must_meet_its_target() {
+        let mut bad = genesis(HARDER_BITS);
+        while bad.check_pow() {
+            bad.nonce += 1;
+        }
+        assert!(HeaderChain::new(bad, retargeting_params()).is_err());
+    }
+
+    #[test]
+    fn test_checkpoint_mismatch_is_rejected() {
+        let genesis = genesis(EASY_BITS);
+        let first = mine_header(&genesis, EASY_BITS, GENESIS_TIME + 600);
+        let mut other = first.clone();
+        other.merkle_root = [0x43; 32];
+        while !other.check_pow() {
+            other.nonce += 1;
+        }
+        let params = ChainParams {
+            checkpoints: Checkpoints::new(vec![(1, first.hash())]),
+            ..retargeting_params()
+        };
+
+        let mut chain = HeaderChain::new(genesis, params).unwrap();
+        assert_eq!(
+            chain.accept(other, NOW),
+            Err(BlockError::CheckpointMismatch { height: 1 })
+        );
+        assert!(chain.accept(first, NOW).is_ok());
+    }
+
+    #[test]
+    fn test_regtest_chain_from_genesis() {
+        let mut chain = HeaderChain::for_network(Network::Regtest).unwrap();
+        assert_eq!(chain.tip(), &Network::Regtest.genesis_header());
+        extend(&mut chain, 5, 600);
+        assert_eq!(chain.height(), 5);
+
+        for network in Network::ALL {
+            assert!(HeaderChain::for_network(network).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_params_per_network() {
+        let regtest = ChainParams::for_network(Network::Regtest);
+        assert!(regtest.no_retargeting);
+        assert_eq!(regtest.max_target_bits, 0x207fffff);
+
+        let mainnet = ChainParams::for_network(Network::Mainnet);
+        assert!(!mainnet.allow_min_difficulty_blocks);
+        assert!(ChainParams::for_network(Network::Testnet).allow_min_difficulty_blocks);
+        assert_eq!(mainnet.bip65_height, 388_381);
+    }
+}
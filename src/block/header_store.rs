@@ -0,0 +1,296 @@
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+use num_bigint::{BigInt, Sign};
+
+use crate::block::header::{BlockHeader, HEADER_SIZE};
+use crate::block::header_chain::HeaderChain;
+use crate::types::errors::StoreError;
+
+// This module implements `HeaderStore`, the on-disk copy of a validated header chain that
+// lets an SPV client resume syncing where it stopped.
+//
+// The file starts with a 12-byte preamble, followed by one 120-byte record per height:
+//
+//     magic "BHDR" | format version (4, LE) | record count (4, LE)
+//     header (80) | height (8, LE) | cumulative chainwork (32, BE)
+//
+// Headers were fully validated before being stored, so loading only checks that each
+// record links to the previous one. A torn write or corrupted tail doesn't lose the whole
+// chain: the file is truncated to its longest valid prefix. New headers are appended in
+// place, rewriting only the record count.
+
+pub const STORE_MAGIC: [u8; 4] = *b"BHDR";
+pub const STORE_VERSION: u32 = 1;
+const PREAMBLE_SIZE: usize = 12;
+const RECORD_SIZE: usize = HEADER_SIZE + 8 + 32;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HeaderStore {
+    headers: Vec<BlockHeader>,
+    // Cumulative work up to and including each height.
+    chainwork: Vec<BigInt>,
+}
+
+impl HeaderStore {
+    pub fn from_chain(chain: &HeaderChain) -> Self {
+        let mut store = HeaderStore {
+            headers: Vec::new(),
+            chainwork: Vec::new(),
+        };
+        store.extend_from(chain);
+        store
+    }
+
+    pub fn headers(&self) -> &[BlockHeader] {
+        &self.headers
+    }
+
+    pub fn chainwork(&self) -> &[BigInt] {
+        &self.chainwork
+    }
+
+    // None for an empty store.
+    pub fn height(&self) -> Option<u64> {
+        (self.headers.len() as u64).checked_sub(1)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), StoreError> {
+        let mut contents = preamble(self.headers.len());
+        for height in 0..self.headers.len() {
+            contents.extend(self.record(height));
+        }
+        Ok(fs::write(path, contents)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, StoreError> {
+        let contents = fs::read(path)?;
+        if contents.len() < PREAMBLE_SIZE || contents[..4] != STORE_MAGIC {
+            return Err(StoreError::NotAHeaderStore);
+        }
+        let version = u32::from_le_bytes(contents[4..8].try_into().unwrap());
+        if version != STORE_VERSION {
+            return Err(StoreError::UnsupportedVersion { version });
+        }
+        let count = u32::from_le_bytes(contents[8..12].try_into().unwrap()) as usize;
+
+        let mut store = HeaderStore {
+            headers: Vec::new(),
+            chainwork: Vec::new(),
+        };
+        for record in contents[PREAMBLE_SIZE..]
+            .chunks_exact(RECORD_SIZE)
+            .take(count)
+        {
+            let header = BlockHeader::parse(&mut &record[..HEADER_SIZE]).unwrap();
+            let height =
+                u64::from_le_bytes(record[HEADER_SIZE..HEADER_SIZE + 8].try_into().unwrap());
+            let links = match store.headers.last() {
+                Some(previous) => header.prev_blockhash == previous.hash(),
+                None => true,
+            };
+            if !links || height != store.headers.len() as u64 {
+                break;
+            }
+            store.headers.push(header);
+            store.chainwork.push(BigInt::from_bytes_be(
+                Sign::Plus,
+                &record[HEADER_SIZE + 8..],
+            ));
+        }
+
+        if store.headers.len() != count || contents.len() != PREAMBLE_SIZE + count * RECORD_SIZE {
+            store.truncate_file(path)?;
+        }
+        Ok(store)
+    }
+
+    // Appends the headers `chain` has above this store to the file at `path`, which must hold
+    // this store. Returns how many were appended.
+    pub fn append(&mut self, path: &Path, chain: &HeaderChain) -> Result<usize, StoreError> {
+        let start = self.headers.len();
+        if let Some(height) = self.height() {
+            if chain.header_at(height) != self.headers.last() {
+                return Err(StoreError::Unlinked { height });
+            }
+        }
+        self.extend_from(chain);
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        file.write_all(&preamble(self.headers.len()))?;
+        file.seek(SeekFrom::Start(
+            (PREAMBLE_SIZE + start * RECORD_SIZE) as u64,
+        ))?;
+        for height in start..self.headers.len() {
+            file.write_all(&self.record(height))?;
+        }
+        file.sync_data()?;
+        Ok(self.headers.len() - start)
+    }
+
+    fn extend_from(&mut self, chain: &HeaderChain) {
+        let start = self.headers.len() as u64;
+        for height in start..=chain.height() {
+            self.headers.push(chain.header_at(height).unwrap().clone());
+            self.chainwork
+                .push(chain.chainwork_at(height).unwrap().clone());
+        }
+    }
+
+    fn record(&self, height: usize) -> Vec<u8> {
+        let mut record = self.headers[height].serialize().to_vec();
+        record.extend_from_slice(&(height as u64).to_le_bytes());
+        let (_, chainwork) = self.chainwork[height].to_bytes_be();
+        record.extend(std::iter::repeat_n(0, 32 - chainwork.len()));
+        record.extend(chainwork);
+        record
+    }
+
+    // Drops everything after the valid records.
+    fn truncate_file(&self, path: &Path) -> Result<(), StoreError> {
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        file.write_all(&preamble(self.headers.len()))?;
+        file.set_len((PREAMBLE_SIZE + self.headers.len() * RECORD_SIZE) as u64)?;
+        Ok(())
+    }
+}
+
+fn preamble(count: usize) -> Vec<u8> {
+    let mut preamble = STORE_MAGIC.to_vec();
+    preamble.extend_from_slice(&STORE_VERSION.to_le_bytes());
+    preamble.extend_from_slice(&(count as u32).to_le_bytes());
+    preamble
+}
+
+#[cfg(test)]
+mod header_store_tests {
+    use super::*;
+    use crate::block::header_chain::header_chain_tests::{
+        extend, genesis, retargeting_params, EASY_BITS,
+    };
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{name}_{}.bin", std::process::id()))
+    }
+
+    fn chain_of(count: usize) -> HeaderChain {
+        let mut chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        extend(&mut chain, count, 600);
+        chain
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let path = temp_path("header_store_round_trip");
+        let chain = chain_of(10_000);
+        let store = HeaderStore::from_chain(&chain);
+        store.save(&path).unwrap();
+
+        let loaded = HeaderStore::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, store);
+        assert_eq!(loaded.height(), Some(10_000));
+        assert_eq!(loaded.chainwork().last(), Some(chain.chainwork()));
+    }
+
+    #[test]
+    fn test_truncated_file_loads_valid_prefix() {
+        let path = temp_path("header_store_truncated");
+        let store = HeaderStore::from_chain(&chain_of(20));
+        store.save(&path).unwrap();
+        // Cut the file in the middle of the header at height 15
+        let contents = fs::read(&path).unwrap();
+        fs::write(&path, &contents[..PREAMBLE_SIZE + 15 * RECORD_SIZE + 40]).unwrap();
+
+        let loaded = HeaderStore::load(&path).unwrap();
+        assert_eq!(loaded.height(), Some(14));
+        assert_eq!(loaded.headers(), &store.headers()[..15]);
+        // The torn record is gone from disk too
+        let contents = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(contents.len(), PREAMBLE_SIZE + 15 * RECORD_SIZE);
+        assert_eq!(contents[8..12], 15u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_corrupted_record_loads_valid_prefix() {
+        let path = temp_path("header_store_corrupted");
+        HeaderStore::from_chain(&chain_of(20)).save(&path).unwrap();
+        let mut contents = fs::read(&path).unwrap();
+        // Flip a bit of the header at height 8, breaking the link from height 9
+        contents[PREAMBLE_SIZE + 8 * RECORD_SIZE + 40] ^= 1;
+        fs::write(&path, contents).unwrap();
+
+        let loaded = HeaderStore::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.height(), Some(8));
+    }
+
+    #[test]
+    fn test_resume_sync_after_load() {
+        let path = temp_path("header_store_resume");
+        let mut chain = chain_of(50);
+        let mut store = HeaderStore::from_chain(&chain);
+        store.save(&path).unwrap();
+
+        let loaded = HeaderStore::load(&path).unwrap();
+        let mut resumed = HeaderChain::from_store(&loaded, retargeting_params()).unwrap();
+        assert_eq!(resumed.height(), 50);
+        assert_eq!(resumed.tip(), chain.tip());
+        assert_eq!(resumed.chainwork(), chain.chainwork());
+
+        extend(&mut resumed, 10, 600);
+        assert_eq!(resumed.height(), 60);
+        assert_eq!(store.append(&path, &resumed).unwrap(), 10);
+        let reloaded = HeaderStore::load(&path).unwrap();
+        assert_eq!(reloaded.height(), Some(60));
+        assert_eq!(reloaded, store);
+
+        // A chain that doesn't extend the stored one can't be appended
+        extend(&mut chain, 20, 300);
+        assert!(matches!(
+            store.append(&path, &chain),
+            Err(StoreError::Unlinked { height: 60 })
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_empty_store_cannot_be_resumed() {
+        let path = temp_path("header_store_empty");
+        fs::write(&path, preamble(0)).unwrap();
+
+        let loaded = HeaderStore::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.height(), None);
+        assert!(matches!(
+            HeaderChain::from_store(&loaded, retargeting_params()),
+            Err(StoreError::Empty)
+        ));
+    }
+
+    #[test]
+    fn test_reject_foreign_files() {
+        let path = temp_path("header_store_foreign");
+        fs::write(&path, b"not a header store").unwrap();
+        assert!(matches!(
+            HeaderStore::load(&path),
+            Err(StoreError::NotAHeaderStore)
+        ));
+
+        let mut contents = preamble(0);
+        contents[4] = 2;
+        fs::write(&path, contents).unwrap();
+        assert!(matches!(
+            HeaderStore::load(&path),
+            Err(StoreError::UnsupportedVersion { version: 2 })
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,279 @@
+use std::thread;
+
+use crate::hash::hash256;
+
+// This module implements the merkle tree that commits a block header to its transactions.
+//
+// Leaves are txids in internal byte order (the reverse of the hex shown by explorers) and
+// every parent is hash256 of its two children concatenated. A level with an odd number of
+// hashes duplicates its last one. The root comes out in internal byte order too, which is
+// how the header stores it; reverse it to compare with an explorer's merkle root.
+
+pub fn merkle_parent(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut concatenated = [0u8; 64];
+    concatenated[..32].copy_from_slice(left);
+    concatenated[32..].copy_from_slice(right);
+    hash256(&concatenated)
+}
+
+pub fn merkle_parent_level(hashes: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    hashes
+        .chunks(2)
+        .map(|pair| merkle_parent(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
+// None for an empty list, as a block always has at least its coinbase.
+pub fn merkle_root(hashes: &[[u8; 32]]) -> Option<[u8; 32]> {
+    let mut level = hashes.to_vec();
+    while level.len() > 1 {
+        level = merkle_parent_level(&level);
+    }
+    level.first().copied()
+}
+
+// Levels with fewer hashes than this are hashed on the calling thread, where spawning threads
+// would cost more than it saves.
+pub const PARALLEL_MERKLE_THRESHOLD: usize = 1024;
+
+// Same root as `merkle_root`, hashing large levels with up to `threads` threads.
+pub fn merkle_root_parallel(hashes: &[[u8; 32]], threads: usize) -> Option<[u8; 32]> {
+    merkle_root_parallel_with_threshold(hashes, threads, PARALLEL_MERKLE_THRESHOLD)
+}
+
+pub fn merkle_root_parallel_with_threshold(
+    hashes: &[[u8; 32]],
+    threads: usize,
+    threshold: usize,
+) -> Option<[u8; 32]> {
+    let mut level = hashes.to_vec();
+    while level.len() > 1 {
+        level = if threads > 1 && level.len() >= threshold {
+            parallel_parent_level(&level, threads)
+        } else {
+            merkle_parent_level(&level)
+        };
+    }
+    level.first().copied()
+}
+
+fn parallel_parent_level(hashes: &[[u8; 32]], threads: usize) -> Vec<[u8; 32]> {
+    // Chunks hold an even number of hashes so that no pair is split between two threads.
+    // Only the last chunk can be odd, and it duplicates its last hash like the whole level would
+    let chunk_size = hashes.len().div_ceil(threads).next_multiple_of(2);
+    thread::scope(|scope| {
+        let workers: Vec<_> = hashes
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || merkle_parent_level(chunk)))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    })
+}
+
+// The path proving that a transaction is in a block: the sibling of each node on the way from
+// its leaf to the root, and the leaf position, whose bits tell on which side each sibling is.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MerkleProof {
+    pub index: u32,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl MerkleProof {
+    // `txids` are the block's transactions in order. None if `txid` is not one of them.
+    pub fn generate(txids: &[[u8; 32]], txid: &[u8; 32]) -> Option<Self> {
+        let mut position = txids.iter().position(|candidate| candidate == txid)?;
+        let index = u32::try_from(position).ok()?;
+
+        let mut siblings = Vec::new();
+        let mut level = txids.to_vec();
+        while level.len() > 1 {
+            // The last node of an odd level is its own sibling
+            let sibling = level.get(position ^ 1).unwrap_or(&level[position]);
+            siblings.push(*sibling);
+            level = merkle_parent_level(&level);
+            position /= 2;
+        }
+        Some(MerkleProof { index, siblings })
+    }
+
+    pub fn verify(&self, root: &[u8; 32], txid: &[u8; 32]) -> bool {
+        let mut position = self.index;
+        let mut hash = *txid;
+        for sibling in &self.siblings {
+            hash = if position & 1 == 0 {
+                merkle_parent(&hash, sibling)
+            } else {
+                merkle_parent(sibling, &hash)
+            };
+            position >>= 1;
+        }
+        // Leftover bits would mean a leaf deeper than the path
+        position == 0 && hash == *root
+    }
+}
+
+#[cfg(test)]
+mod merkle_tests {
+    use super::*;
+    use crate::encoding::hex::{decode_hex, encode_hex};
+    use crate::hash::hash256;
+
+    fn hash(hex: &str) -> [u8; 32] {
+        decode_hex(hex).unwrap().try_into().unwrap()
+    }
+
+    fn hashes(hexes: &[&str]) -> Vec<[u8; 32]> {
+        hexes.iter().map(|hex| hash(hex)).collect()
+    }
+
+    const LEVEL_HASHES: [&str; 5] = [
+        "c117ea8ec828342f4dfb0ad6bd140e03a50720ece40169ee38bdc15d9eb64cf5",
+        "c131474164b412e3406696da1ee20ab0fc9bf41c8f05fa8ceea7a08d672d7cc5",
+        "f391da6ecfeed1814efae39e7fcb3838ae0b02c02ae7d0a5848a66947c0727b0",
+        "3d238a92a94532b946c90e19c49351c763696cff3db400485b813aecb8a13181",
+        "10092f2633be5f3ce349bf9ddbde36caa3dd10dfa0ec8106bce23acbff637dae",
+    ];
+
+    #[test]
+    fn test_merkle_parent() {
+        let parent = merkle_parent(&hash(LEVEL_HASHES[0]), &hash(LEVEL_HASHES[1]));
+        assert_eq!(
+            encode_hex(&parent),
+            "8b30c5ba100f6f2e5ad1e2a742e5020491240f8eb514fe97c713c31718ad7ecd"
+        );
+    }
+
+    #[test]
+    fn test_merkle_parent_level_duplicates_odd_last_hash() {
+        let level = merkle_parent_level(&hashes(&LEVEL_HASHES));
+        assert_eq!(
+            level,
+            hashes(&[
+                "8b30c5ba100f6f2e5ad1e2a742e5020491240f8eb514fe97c713c31718ad7ecd",
+                "7f4e6f9e224e20fda0ae4c44114237f97cd35aca38d83081c9bfd41feb907800",
+                "3ecf6115380c77e8aae56660f5634982ee897351ba906a6837d15ebc3a225df0",
+            ])
+        );
+        let last = hash(LEVEL_HASHES[4]);
+        assert_eq!(level[2], merkle_parent(&last, &last));
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let leaves = hashes(&[
+            "c117ea8ec828342f4dfb0ad6bd140e03a50720ece40169ee38bdc15d9eb64cf5",
+            "c131474164b412e3406696da1ee20ab0fc9bf41c8f05fa8ceea7a08d672d7cc5",
+            "f391da6ecfeed1814efae39e7fcb3838ae0b02c02ae7d0a5848a66947c0727b0",
+            "3d238a92a94532b946c90e19c49351c763696cff3db400485b813aecb8a13181",
+            "10092f2633be5f3ce349bf9ddbde36caa3dd10dfa0ec8106bce23acbff637dae",
+            "7d37b3d54fa6a64869084bfd2e831309118b9e833610e6228adacdbd1b4ba161",
+            "8118a77e542892fe15ae3fc771a4abfd2f5d5d5997544c3487ac36b5c85170fc",
+            "dff6879848c2c9b62fe652720b8df5272093acfaa45a43cdb3696fe2466a3877",
+            "b825c0745f46ac58f7d3759e6dc535a1fec7820377f24d4c2c6ad2cc55c0cb59",
+            "95513952a04bd8992721e9b7e2937f1c04ba31e0469fbe615a78197f68f52b7c",
+            "2e6d722e5e4dbdf2447ddecc9f7dabb8e299bae921c99ad5b0184cd9eb8e5908",
+            "b13a750047bc0bdceb2473e5fe488c2596d7a7124b4e716fdd29b046ef99bbf0",
+        ]);
+        assert_eq!(
+            encode_hex(&merkle_root(&leaves).unwrap()),
+            "acbcab8bcc1af95d8d563b77d24c3d19b18f1486383d75a5085c4e86c86beed6"
+        );
+    }
+
+    #[test]
+    fn test_merkle_root_of_block_170_from_display_txids() {
+        // Explorers show txids and the root reversed
+        let mut leaves = hashes(&[
+            "b1fea52486ce0c62bb442b530a3f0132b826c74e473d1f2c220bfa78111c5082",
+            "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16",
+        ]);
+        leaves.iter_mut().for_each(|leaf| leaf.reverse());
+        let mut root = merkle_root(&leaves).unwrap();
+        root.reverse();
+        assert_eq!(
+            encode_hex(&root),
+            "7dac2c5666815c17a3b36427de37bb9d2e2c5ccec3f8633eb91a4205cb4c10ff"
+        );
+    }
+
+    #[test]
+    fn test_single_leaf_root_is_the_leaf() {
+        let leaf = hash(LEVEL_HASHES[0]);
+        assert_eq!(merkle_root(&[leaf]), Some(leaf));
+        assert_eq!(merkle_root(&[]), None);
+    }
+
+    #[test]
+    fn test_merkle_proofs_in_a_13_transaction_block() {
+        // 13 leaves give odd levels of 13, 7 and 3 nodes
+        let txids: Vec<[u8; 32]> = (0u8..13).map(|i| hash256(&[i])).collect();
+        let root = merkle_root(&txids).unwrap();
+
+        for index in [0, 6, 12] {
+            let proof = MerkleProof::generate(&txids, &txids[index]).unwrap();
+            assert_eq!(proof.index, index as u32);
+            assert_eq!(proof.siblings.len(), 4);
+            assert!(proof.verify(&root, &txids[index]));
+            assert!(!proof.verify(&root, &txids[(index + 1) % 13]));
+        }
+
+        // The last leaf is paired with itself on the first level
+        let last = MerkleProof::generate(&txids, &txids[12]).unwrap();
+        assert_eq!(last.siblings[0], txids[12]);
+    }
+
+    #[test]
+    fn test_tampered_merkle_proofs_fail() {
+        let txids: Vec<[u8; 32]> = (0u8..13).map(|i| hash256(&[i])).collect();
+        let root = merkle_root(&txids).unwrap();
+        let proof = MerkleProof::generate(&txids, &txids[6]).unwrap();
+
+        let mut truncated = proof.clone();
+        truncated.siblings.pop();
+        assert!(!truncated.verify(&root, &txids[6]));
+
+        let mut moved = proof.clone();
+        moved.index = 7;
+        assert!(!moved.verify(&root, &txids[6]));
+
+        assert_eq!(MerkleProof::generate(&txids, &[0; 32]), None);
+    }
+
+    #[test]
+    fn test_single_transaction_proof_is_empty() {
+        let txid = hash256(b"coinbase");
+        let proof = MerkleProof::generate(&[txid], &txid).unwrap();
+        assert!(proof.siblings.is_empty());
+        assert!(proof.verify(&txid, &txid));
+    }
+
+    #[test]
+    fn test_parallel_root_matches_serial() {
+        let leaves: Vec<[u8; 32]> = (0u32..1000).map(|i| hash256(&i.to_le_bytes())).collect();
+        for count in 1..=1000 {
+            // A threshold of 2 sends every level through the threads
+            assert_eq!(
+                merkle_root_parallel_with_threshold(&leaves[..count], 3, 2),
+                merkle_root(&leaves[..count]),
+                "{count} leaves"
+            );
+        }
+        assert_eq!(merkle_root_parallel(&[], 4), None);
+    }
+
+    #[test]
+    fn test_parallel_root_of_a_large_block() {
+        // Pseudo-random leaves from a hash chain
+        let mut leaves = vec![hash256(b"seed")];
+        for _ in 1..10_007 {
+            leaves.push(hash256(leaves.last().unwrap()));
+        }
+        let root = merkle_root(&leaves);
+        for threads in [1, 2, 7, 16] {
+            assert_eq!(merkle_root_parallel(&leaves, threads), root);
+        }
+    }
+}
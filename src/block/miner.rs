@@ -0,0 +1,195 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use num_bigint::Sign;
+
+use crate::block::header::BlockHeader;
+use crate::block::pow::bits_to_target;
+use crate::hash::hash256;
+
+// This module implements an educational CPU miner, only practical at regtest difficulty.
+//
+// The 32-bit nonce space is split into one contiguous range per thread. When every nonce has
+// been tried for the current timestamp, the timestamp is bumped by one second ("time
+// rolling") and the search starts over, up to `max_time_rolls` times. All workers share a
+// stop flag: the first one to find a header raises it, and so can the caller to cancel.
+
+const NONCE_SPACE: u64 = 1 << 32;
+
+pub fn mine(template: BlockHeader, threads: usize, max_time_rolls: u32) -> Option<BlockHeader> {
+    mine_until(template, threads, max_time_rolls, &AtomicBool::new(false))
+}
+
+// Same as `mine`, giving up (with None) as soon as `stop` is set.
+pub fn mine_until(
+    template: BlockHeader,
+    threads: usize,
+    max_time_rolls: u32,
+    stop: &AtomicBool,
+) -> Option<BlockHeader> {
+    search(template, threads, max_time_rolls, NONCE_SPACE, stop)
+}
+
+// Tries the first `nonce_count` nonces for each timestamp.
+fn search(
+    template: BlockHeader,
+    threads: usize,
+    max_time_rolls: u32,
+    nonce_count: u64,
+    stop: &AtomicBool,
+) -> Option<BlockHeader> {
+    let target = target_le_bytes(template.bits)?;
+    let threads = threads.max(1) as u64;
+    let chunk = nonce_count.div_ceil(threads);
+
+    for roll in 0..=max_time_rolls {
+        let mut header = template.clone();
+        header.timestamp = template.timestamp.checked_add(roll)?;
+        let serialized = header.serialize();
+
+        let found = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|i| {
+                    let start = (i * chunk).min(nonce_count);
+                    let end = ((i + 1) * chunk).min(nonce_count);
+                    scope.spawn(move || grind(serialized, start..end, &target, stop))
+                })
+                .collect();
+            workers
+                .into_iter()
+                .filter_map(|worker| worker.join().unwrap())
+                .next()
+        });
+
+        if let Some(nonce) = found {
+            header.nonce = nonce;
+            return Some(header);
+        }
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+    }
+    None
+}
+
+fn grind(
+    mut serialized: [u8; 80],
+    nonces: std::ops::Range<u64>,
+    target: &[u8; 32],
+    stop: &AtomicBool,
+) -> Option<u32> {
+    for nonce in nonces {
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        let nonce = nonce as u32;
+        serialized[76..].copy_from_slice(&nonce.to_le_bytes());
+        if meets_target(&hash256(&serialized), target) {
+            stop.store(true, Ordering::Relaxed);
+            return Some(nonce);
+        }
+    }
+    None
+}
+
+// The target as 32 little-endian bytes, like the hashes it is compared against.
+fn target_le_bytes(bits: u32) -> Option<[u8; 32]> {
+    let target = bits_to_target(bits).ok()?;
+    let (_, bytes) = target.to_bytes_le();
+    if target.sign() == Sign::Minus || bytes.len() > 32 {
+        return None;
+    }
+    let mut target = [0u8; 32];
+    target[..bytes.len()].copy_from_slice(&bytes);
+    Some(target)
+}
+
+// hash <= target, both read as little-endian 256-bit integers.
+fn meets_target(hash: &[u8; 32], target: &[u8; 32]) -> bool {
+    hash.iter().rev().cmp(target.iter().rev()).is_le()
+}
+
+#[cfg(test)]
+mod miner_tests {
+    use super::*;
+    use crate::network::params::Network;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    fn template(bits: u32) -> BlockHeader {
+        let genesis = Network::Regtest.genesis_header();
+        BlockHeader {
+            version: 0x20000000,
+            prev_blockhash: genesis.hash(),
+            merkle_root: [0x42; 32],
+            timestamp: genesis.timestamp + 600,
+            bits,
+            nonce: 0,
+        }
+    }
+
+    #[test]
+    fn test_single_threaded_mining_finds_the_first_nonce() {
+        let mined = mine(template(0x207fffff), 1, 0).unwrap();
+        assert!(mined.check_pow());
+
+        let first = (0..)
+            .map(|nonce| BlockHeader {
+                nonce,
+                ..template(0x207fffff)
+            })
+            .find(BlockHeader::check_pow)
+            .unwrap();
+        assert_eq!(mined, first);
+    }
+
+    #[test]
+    fn test_multi_threaded_mining() {
+        // 256 times harder than regtest, so every thread has work to do
+        let mined = mine(template(0x2000ffff), 4, 0).unwrap();
+        assert!(mined.check_pow());
+        assert_eq!(mined.timestamp, template(0x2000ffff).timestamp);
+    }
+
+    #[test]
+    fn test_time_rolling_when_nonces_run_out() {
+        let template = template(0x2000ffff);
+        let stop = AtomicBool::new(false);
+        // Only 2 nonces per timestamp, so a solution needs the timestamp to move
+        let mined = search(template.clone(), 2, 10_000, 2, &stop).unwrap();
+
+        assert!(mined.check_pow());
+        assert!(mined.nonce < 2);
+        assert!(mined.timestamp > template.timestamp);
+        assert_eq!(search(template, 1, 0, 2, &AtomicBool::new(false)), None);
+    }
+
+    #[test]
+    fn test_external_cancellation() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let canceller = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                stop.store(true, Ordering::Relaxed);
+            })
+        };
+
+        // A target of 1 is never met
+        let start = Instant::now();
+        assert_eq!(mine_until(template(0x03000001), 2, u32::MAX, &stop), None);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        canceller.join().unwrap();
+    }
+
+    #[test]
+    fn test_meets_target_compares_as_little_endian() {
+        let target = target_le_bytes(0x207fffff).unwrap();
+        let mut hash = [0u8; 32];
+        hash[31] = 0x7f;
+        assert!(meets_target(&hash, &target));
+        hash[31] = 0x80;
+        assert!(!meets_target(&hash, &target));
+        assert_eq!(target_le_bytes(0x04923456), None);
+    }
+}
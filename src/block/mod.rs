@@ -0,0 +1,10 @@
+pub mod genesis;
+pub mod header;
+pub mod header_chain;
+pub mod header_store;
+pub mod merkle;
+pub mod miner;
+pub mod pow;
+pub mod subsidy;
+pub mod timestamp;
+pub mod versionbits;
@@ -0,0 +1,262 @@
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::types::errors::BlockError;
+
+// This module implements the proof-of-work helpers built around the compact "bits" encoding.
+//
+// A block header stores its target in 4 bytes: one exponent byte followed by a 3 byte
+// coefficient, meaning
+//
+//     target = coefficient * 256^(exponent - 3)
+//
+// The coefficient's top bit is a sign bit inherited from OpenSSL's MPI format, so a valid
+// target never has it set. A header satisfies proof-of-work when its hash, read as a
+// little-endian 256-bit integer, is lower than or equal to the target.
+
+// Bits of the lowest difficulty (highest target) allowed on mainnet.
+pub const MAX_TARGET_BITS: u32 = 0x1d00ffff;
+// The target is recomputed every 2016 blocks, aiming at one block every 10 minutes.
+pub const RETARGET_INTERVAL: u64 = 2016;
+pub const TWO_WEEKS: u64 = 60 * 60 * 24 * 14;
+
+pub fn bits_to_target(bits: u32) -> Result<BigInt, BlockError> {
+    let exponent = bits >> 24;
+    let coefficient = bits & 0x007fffff;
+
+    if coefficient != 0 && bits & 0x00800000 != 0 {
+        return Err(BlockError::NegativeTarget { bits });
+    }
+    if coefficient != 0
+        && (exponent > 34
+            || (coefficient > 0xff && exponent > 33)
+            || (coefficient > 0xffff && exponent > 32))
+    {
+        return Err(BlockError::TargetOverflow { bits });
+    }
+
+    let target = if exponent <= 3 {
+        BigInt::from(coefficient >> (8 * (3 - exponent)))
+    } else {
+        BigInt::from(coefficient) << (8 * (exponent - 3))
+    };
+    Ok(target)
+}
+
+// Inverse of `bits_to_target`. Precision below the 3 most significant bytes is truncated,
+// and the exponent is bumped whenever the coefficient would otherwise set the sign bit.
+pub fn target_to_bits(target: &BigInt) -> u32 {
+    let (_, bytes) = target.to_bytes_be();
+    let mut exponent = if target.is_zero() {
+        0
+    } else {
+        bytes.len() as u32
+    };
+
+    let mut coefficient: u32 = if exponent <= 3 {
+        target.to_u32().unwrap_or(0) << (8 * (3 - exponent))
+    } else {
+        bytes[..3]
+            .iter()
+            .fold(0u32, |acc, byte| (acc << 8) | u32::from(*byte))
+    };
+
+    if coefficient & 0x00800000 != 0 {
+        coefficient >>= 8;
+        exponent += 1;
+    }
+    coefficient | (exponent << 24)
+}
+
+pub fn max_target() -> BigInt {
+    bits_to_target(MAX_TARGET_BITS).unwrap()
+}
+
+// Difficulty as displayed by block explorers: how many times harder than the easiest target.
+pub fn difficulty(bits: u32) -> Result<f64, BlockError> {
+    let target = bits_to_target(bits)?;
+    if target.is_zero() {
+        return Ok(f64::INFINITY);
+    }
+    let max = max_target().to_f64().unwrap();
+    Ok(max / target.to_f64().unwrap())
+}
+
+// Retarget after a 2016 block period. `time_differential` is the timestamp of the last block
+// of the period minus the timestamp of the first one. It is clamped to [1/4, 4] times the
+// expected two weeks, and the resulting target never exceeds the maximum target.
+pub fn calculate_new_bits(previous_bits: u32, time_differential: u64) -> Result<u32, BlockError> {
+    calculate_new_bits_with_limit(previous_bits, time_differential, MAX_TARGET_BITS)
+}
+
+// Same as `calculate_new_bits` for networks whose easiest target is `max_target_bits`.
+pub fn calculate_new_bits_with_limit(
+    previous_bits: u32,
+    time_differential: u64,
+    max_target_bits: u32,
+) -> Result<u32, BlockError> {
+    let time_differential = time_differential.clamp(TWO_WEEKS / 4, TWO_WEEKS * 4);
+    let new_target = bits_to_target(previous_bits)? * time_differential / TWO_WEEKS;
+    Ok(target_to_bits(
+        &new_target.min(bits_to_target(max_target_bits)?),
+    ))
+}
+
+// Expected number of hashes needed to find a block at this target: 2^256 / (target + 1).
+pub fn work(bits: u32) -> Result<BigInt, BlockError> {
+    let target = bits_to_target(bits)?;
+    Ok((BigInt::one() << 256) / (target + 1))
+}
+
+// Total work of a chain of blocks, which is what nodes compare to pick the best tip
+// (not the number of blocks).
+pub fn chainwork<I: IntoIterator<Item = u32>>(bits: I) -> Result<BigInt, BlockError> {
+    bits.into_iter()
+        .try_fold(BigInt::zero(), |total, bits| Ok(total + work(bits)?))
+}
+
+// `hash` is in internal byte order (the reverse of the hex shown by explorers).
+pub fn check_pow(hash: &[u8; 32], bits: u32) -> bool {
+    let target = match bits_to_target(bits) {
+        Ok(target) if !target.is_zero() => target,
+        _ => return false,
+    };
+    BigInt::from_bytes_le(Sign::Plus, hash) <= target
+}
+
+#[cfg(test)]
+mod pow_tests {
+    use super::*;
+    use crate::encoding::hex::hash_from_display_hex;
+    use num_traits::Num;
+
+    #[test]
+    fn test_bits_to_target_programming_bitcoin_example() {
+        let target = bits_to_target(0x18013ce9).unwrap();
+        let expected = BigInt::from_str_radix(
+            "0000000000000000013ce9000000000000000000000000000000000000000000",
+            16,
+        )
+        .unwrap();
+
+        assert_eq!(target, expected);
+    }
+
+    #[test]
+    fn test_difficulty_programming_bitcoin_example() {
+        let difficulty = difficulty(0x18013ce9).unwrap();
+
+        assert!((difficulty - 888171856257.3206).abs() < 0.01);
+        assert_eq!(super::difficulty(MAX_TARGET_BITS).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_bits_target_round_trip() {
+        let historical_bits = [
+            0x1d00ffff, 0x1c0ae493, 0x1b0404cb, 0x1a05db8b, 0x18013ce9, 0x1715a35c, 0x17034219,
+            0x207fffff,
+        ];
+
+        for bits in historical_bits {
+            let target = bits_to_target(bits).unwrap();
+            assert_eq!(target_to_bits(&target), bits);
+        }
+    }
+
+    #[test]
+    fn test_target_to_bits_truncates_and_avoids_sign_bit() {
+        // 0x80 would set the sign bit, so the exponent grows by one.
+        assert_eq!(target_to_bits(&BigInt::from(0x80)), 0x02008000);
+        assert_eq!(target_to_bits(&BigInt::from(0x123456789_u64)), 0x05012345);
+        assert_eq!(target_to_bits(&BigInt::zero()), 0);
+    }
+
+    #[test]
+    fn test_bits_to_target_rejects_negative_and_overflow() {
+        assert_eq!(
+            bits_to_target(0x04923456),
+            Err(BlockError::NegativeTarget { bits: 0x04923456 })
+        );
+        assert_eq!(
+            bits_to_target(0xff123456),
+            Err(BlockError::TargetOverflow { bits: 0xff123456 })
+        );
+        assert_eq!(bits_to_target(0x01003456).unwrap(), BigInt::zero());
+    }
+
+    #[test]
+    fn test_calculate_new_bits_programming_bitcoin_example() {
+        // Blocks 471744 and 473759 from chapter 9
+        let first_timestamp = 0x5945b8dd;
+        let last_timestamp = 0x59584226;
+
+        let new_bits = calculate_new_bits(0x18018b7e, last_timestamp - first_timestamp).unwrap();
+
+        assert_eq!(new_bits, 0x18018d30);
+    }
+
+    #[test]
+    fn test_calculate_new_bits_clamps_time_differential() {
+        let bits = 0x18018b7e;
+
+        assert_eq!(
+            calculate_new_bits(bits, 0).unwrap(),
+            calculate_new_bits(bits, TWO_WEEKS / 4).unwrap()
+        );
+        assert_eq!(
+            calculate_new_bits(bits, TWO_WEEKS * 100).unwrap(),
+            calculate_new_bits(bits, TWO_WEEKS * 4).unwrap()
+        );
+        assert_eq!(calculate_new_bits(bits, TWO_WEEKS).unwrap(), bits);
+    }
+
+    #[test]
+    fn test_calculate_new_bits_never_exceeds_max_target() {
+        assert_eq!(
+            calculate_new_bits(MAX_TARGET_BITS, TWO_WEEKS * 4).unwrap(),
+            MAX_TARGET_BITS
+        );
+    }
+
+    #[test]
+    fn test_work_of_difficulty_one_block() {
+        assert_eq!(
+            work(MAX_TARGET_BITS).unwrap(),
+            BigInt::from(0x100010001_u64)
+        );
+    }
+
+    #[test]
+    fn test_chainwork_prefers_heavier_chain_over_longer_one() {
+        let longer_lighter = [MAX_TARGET_BITS; 10];
+        let shorter_heavier = [0x1b0404cb; 3];
+
+        let longer_work = chainwork(longer_lighter).unwrap();
+        let shorter_work = chainwork(shorter_heavier).unwrap();
+
+        assert_eq!(longer_work, BigInt::from(0x100010001_u64) * 10);
+        assert!(shorter_work > longer_work);
+    }
+
+    #[test]
+    fn test_check_pow_genesis() {
+        let genesis = hash_from_display_hex(
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+        )
+        .unwrap();
+
+        assert!(check_pow(&genesis, MAX_TARGET_BITS));
+        assert!(!check_pow(&genesis, 0x18013ce9));
+    }
+
+    #[test]
+    fn test_check_pow_fails_for_hash_above_target() {
+        let mut hash = [0u8; 32];
+        hash[28] = 0x01;
+
+        assert!(!check_pow(&hash, MAX_TARGET_BITS));
+        hash[28] = 0x00;
+        hash[27] = 0xff;
+        assert!(check_pow(&hash, MAX_TARGET_BITS));
+    }
+}
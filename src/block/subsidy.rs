@@ -0,0 +1,86 @@
+// This module implements the block subsidy schedule.
+//
+// The coinbase of the first block could claim 50 BTC. The subsidy is halved (right-shifted)
+// every 210,000 blocks, which makes it reach zero after 33 halvings and caps the total supply
+// just below 21 million BTC. Amounts are expressed in satoshis.
+
+pub const COIN: u64 = 100_000_000;
+pub const INITIAL_SUBSIDY: u64 = 50 * COIN;
+pub const HALVING_INTERVAL: u64 = 210_000;
+
+pub fn block_subsidy(height: u64) -> u64 {
+    let halvings = height / HALVING_INTERVAL;
+    // Shifting a u64 by 64 or more bits overflows, so it is special-cased.
+    if halvings >= 64 {
+        return 0;
+    }
+    INITIAL_SUBSIDY >> halvings
+}
+
+// Sum of all subsidies from the genesis block up to and including `height`.
+pub fn total_supply_at(height: u64) -> u64 {
+    let mut supply = 0;
+    let mut era_start = 0;
+
+    while era_start <= height {
+        let subsidy = block_subsidy(era_start);
+        if subsidy == 0 {
+            break;
+        }
+        let era_end = (era_start + HALVING_INTERVAL - 1).min(height);
+        supply += subsidy * (era_end - era_start + 1);
+        era_start += HALVING_INTERVAL;
+    }
+    supply
+}
+
+// A coinbase may claim at most the block subsidy plus the fees of the block's transactions.
+pub fn check_coinbase_value(coinbase_output_values: &[u64], height: u64, total_fees: u64) -> bool {
+    let claimed = coinbase_output_values
+        .iter()
+        .try_fold(0u64, |acc, value| acc.checked_add(*value));
+    // Fees large enough to overflow can't come from real transactions
+    let allowed = block_subsidy(height).checked_add(total_fees);
+
+    match (claimed, allowed) {
+        (Some(claimed), Some(allowed)) => claimed <= allowed,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod subsidy_tests {
+    use super::*;
+
+    #[test]
+    fn test_block_subsidy_halvings() {
+        assert_eq!(block_subsidy(0), 50 * COIN);
+        assert_eq!(block_subsidy(209_999), 50 * COIN);
+        assert_eq!(block_subsidy(210_000), 25 * COIN);
+        assert_eq!(block_subsidy(420_000), 1_250_000_000);
+        assert_eq!(block_subsidy(32 * HALVING_INTERVAL), 1);
+        assert_eq!(block_subsidy(33 * HALVING_INTERVAL), 0);
+        assert_eq!(block_subsidy(64 * HALVING_INTERVAL), 0);
+        assert_eq!(block_subsidy(u64::MAX), 0);
+    }
+
+    #[test]
+    fn test_total_supply() {
+        assert_eq!(total_supply_at(0), 50 * COIN);
+        assert_eq!(total_supply_at(209_999), 210_000 * 50 * COIN);
+        assert_eq!(total_supply_at(210_000), 210_000 * 50 * COIN + 25 * COIN);
+        assert_eq!(total_supply_at(u64::MAX), 2_099_999_997_690_000);
+    }
+
+    #[test]
+    fn test_check_coinbase_value() {
+        let height = 420_000;
+        let fees = 1_000;
+
+        assert!(check_coinbase_value(&[1_250_000_000, 1_000], height, fees));
+        assert!(check_coinbase_value(&[1_000_000_000], height, fees));
+        assert!(!check_coinbase_value(&[1_250_000_000, 1_001], height, fees));
+        assert!(!check_coinbase_value(&[u64::MAX, 1], height, fees));
+        assert!(!check_coinbase_value(&[1], height, u64::MAX));
+    }
+}
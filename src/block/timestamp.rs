@@ -0,0 +1,108 @@
+use crate::types::errors::BlockError;
+
+// This module implements the consensus rules on block timestamps.
+//
+// Block times are only loosely ordered, so instead of requiring them to increase, a new
+// block must be later than the median-time-past (MTP): the median of the timestamps of the
+// previous 11 blocks. It also can't be more than 2 hours ahead of the node's adjusted time.
+// MTP is also the clock that time-based locktimes are evaluated against (BIP113).
+
+pub const MEDIAN_TIME_SPAN: usize = 11;
+pub const MAX_FUTURE_BLOCK_TIME: u64 = 2 * 60 * 60;
+
+// `timestamps` are the times of the blocks up to and including the one whose MTP is wanted,
+// oldest first. Near genesis fewer than 11 blocks are available and all of them are used.
+pub fn median_time_past(timestamps: &[u64]) -> u64 {
+    let start = timestamps.len().saturating_sub(MEDIAN_TIME_SPAN);
+    let mut window = timestamps[start..].to_vec();
+    if window.is_empty() {
+        return 0;
+    }
+    window.sort_unstable();
+    window[window.len() / 2]
+}
+
+// Checks the timestamp of a new block given the timestamps of its ancestors (oldest first)
+// and the current adjusted network time.
+pub fn check_timestamp(
+    timestamp: u64,
+    previous_timestamps: &[u64],
+    adjusted_time: u64,
+) -> Result<(), BlockError> {
+    let median_time_past = median_time_past(previous_timestamps);
+    if timestamp <= median_time_past {
+        return Err(BlockError::TimestampTooOld {
+            timestamp,
+            median_time_past,
+        });
+    }
+    let max_allowed = adjusted_time.saturating_add(MAX_FUTURE_BLOCK_TIME);
+    if timestamp > max_allowed {
+        return Err(BlockError::TimestampTooNew {
+            timestamp,
+            max_allowed,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod timestamp_tests {
+    use super::*;
+    use crate::transaction::locktime::LockTime;
+
+    #[test]
+    fn test_median_time_past_unsorted_window() {
+        // The first timestamp falls outside the 11 block window
+        let timestamps = [1, 50, 10, 90, 20, 80, 30, 70, 40, 60, 100, 0];
+
+        assert_eq!(median_time_past(&timestamps), 50);
+    }
+
+    #[test]
+    fn test_median_time_past_near_genesis() {
+        assert_eq!(median_time_past(&[]), 0);
+        assert_eq!(median_time_past(&[7]), 7);
+        assert_eq!(median_time_past(&[30, 10, 20]), 20);
+        assert_eq!(median_time_past(&[10, 40, 20, 30]), 30);
+    }
+
+    #[test]
+    fn test_check_timestamp() {
+        let previous: Vec<u64> = (1..=11).map(|i| i * 600).collect();
+        let mtp = median_time_past(&previous);
+        let now = 10_000;
+
+        assert_eq!(
+            check_timestamp(mtp, &previous, now),
+            Err(BlockError::TimestampTooOld {
+                timestamp: mtp,
+                median_time_past: mtp
+            })
+        );
+        assert!(check_timestamp(mtp + 1, &previous, now).is_ok());
+        assert!(check_timestamp(now + MAX_FUTURE_BLOCK_TIME, &previous, now).is_ok());
+        assert_eq!(
+            check_timestamp(now + MAX_FUTURE_BLOCK_TIME + 1, &previous, now),
+            Err(BlockError::TimestampTooNew {
+                timestamp: now + MAX_FUTURE_BLOCK_TIME + 1,
+                max_allowed: now + MAX_FUTURE_BLOCK_TIME
+            })
+        );
+        // An adjusted time near the end of the range doesn't overflow
+        assert!(check_timestamp(u64::MAX, &previous, u64::MAX - 1).is_ok());
+    }
+
+    #[test]
+    fn test_time_locktime_becomes_final_when_mtp_crosses_it() {
+        let lock = LockTime::from_consensus(1_700_000_000);
+        let mut timestamps: Vec<u64> = (0..11).map(|i| 1_699_999_995 + i).collect();
+
+        // MTP is 1_700_000_000: not yet strictly past the lock
+        assert_eq!(median_time_past(&timestamps), 1_700_000_000);
+        assert!(!lock.is_satisfied_by(u64::MAX, median_time_past(&timestamps)));
+
+        timestamps.push(1_700_000_006);
+        assert!(lock.is_satisfied_by(u64::MAX, median_time_past(&timestamps)));
+    }
+}
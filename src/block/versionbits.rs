@@ -0,0 +1,361 @@
+// This module implements BIP9 version-bits deployment tracking.
+//
+// Soft forks are signaled by miners setting a bit in the block version, with the top 3 bits
+// of the version fixed to 001. Blocks are grouped into windows (2016 blocks on mainnet) and
+// every deployment walks the state machine below once per window:
+//
+//     DEFINED -> STARTED -> LOCKED_IN -> ACTIVE
+//        \          \
+//         `----------`--> FAILED
+//
+// All blocks of a window share the same state, computed from the window before it: the
+// median-time-past of its last block is compared against the deployment's start time and
+// timeout, and the number of signaling blocks in it against the threshold. Using MTP rather
+// than the raw block time keeps a single miner from skewing the transition.
+
+use crate::block::timestamp::{median_time_past, MEDIAN_TIME_SPAN};
+use crate::types::errors::BlockError;
+
+pub const VERSIONBITS_TOP_MASK: i32 = 0xe0000000_u32 as i32;
+pub const VERSIONBITS_TOP_BITS: i32 = 0x20000000;
+// Bits 0 to 28 can carry signals, the ones above are the top bits.
+pub const VERSIONBITS_NUM_BITS: u8 = 29;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ThresholdState {
+    Defined,
+    Started,
+    LockedIn,
+    Active,
+    Failed,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Deployment {
+    pub bit: u8,
+    pub start_time: u64,
+    pub timeout: u64,
+    pub threshold: u32,
+    pub period: u32,
+}
+
+impl Deployment {
+    pub fn validate(&self) -> Result<(), BlockError> {
+        let reason = if self.period == 0 {
+            "the period is zero"
+        } else if self.threshold == 0 || self.threshold > self.period {
+            "the threshold is not within the period"
+        } else if self.bit >= VERSIONBITS_NUM_BITS {
+            "the bit overlaps the top version bits"
+        } else if self.start_time > self.timeout {
+            "the timeout is before the start time"
+        } else {
+            return Ok(());
+        };
+        Err(BlockError::InvalidDeployment { reason })
+    }
+}
+
+// Whether a block with the given version signals for `bit`. Never true for the top bits,
+// which are set in every signaling version.
+pub fn signals(version: i32, bit: u8) -> bool {
+    bit < VERSIONBITS_NUM_BITS
+        && version & VERSIONBITS_TOP_MASK == VERSIONBITS_TOP_BITS
+        && (version >> bit) & 1 == 1
+}
+
+pub struct VersionBits {
+    deployment: Deployment,
+}
+
+impl VersionBits {
+    pub fn new(deployment: Deployment) -> Result<Self, BlockError> {
+        deployment.validate()?;
+        Ok(VersionBits { deployment })
+    }
+
+    pub fn deployment(&self) -> &Deployment {
+        &self.deployment
+    }
+
+    // State of the deployment for the block at `height`. `headers` holds the (time, version)
+    // pair of every block from genesis, indexed by height, and must cover all windows
+    // before the one containing `height`.
+    pub fn state_at(
+        &self,
+        headers: &[(u64, i32)],
+        height: u64,
+    ) -> Result<ThresholdState, BlockError> {
+        let period = self.deployment.period as u64;
+        let needed = height / period * period;
+        if (headers.len() as u64) < needed {
+            return Err(BlockError::MissingHeaders {
+                needed,
+                available: headers.len() as u64,
+            });
+        }
+
+        let mut state = ThresholdState::Defined;
+        for end in (period..=needed).step_by(period as usize) {
+            let end = end as usize;
+            let start = end - period as usize;
+            state = self.next_state(state, &headers[..end], &headers[start..end]);
+        }
+        Ok(state)
+    }
+
+    // `chain` holds every block up to the end of `previous_window`.
+    fn next_state(
+        &self,
+        state: ThresholdState,
+        chain: &[(u64, i32)],
+        previous_window: &[(u64, i32)],
+    ) -> ThresholdState {
+        let times: Vec<u64> = chain[chain.len().saturating_sub(MEDIAN_TIME_SPAN)..]
+            .iter()
+            .map(|(time, _)| *time)
+            .collect();
+        let time = median_time_past(&times);
+
+        match state {
+            ThresholdState::Defined if time >= self.deployment.timeout => ThresholdState::Failed,
+            ThresholdState::Defined if time >= self.deployment.start_time => {
+                ThresholdState::Started
+            }
+            ThresholdState::Started if time >= self.deployment.timeout => ThresholdState::Failed,
+            ThresholdState::Started => {
+                let count = previous_window
+                    .iter()
+                    .filter(|(_, version)| signals(*version, self.deployment.bit))
+                    .count();
+                if count >= self.deployment.threshold as usize {
+                    ThresholdState::LockedIn
+                } else {
+                    ThresholdState::Started
+                }
+            }
+            ThresholdState::LockedIn => ThresholdState::Active,
+            state => state,
+        }
+    }
+}
+
+#[cfg(test)]
+mod versionbits_tests {
+    use super::*;
+
+    const PERIOD: u32 = 2016;
+    const BIT: u8 = 1;
+    const SIGNALING: i32 = VERSIONBITS_TOP_BITS | (1 << BIT);
+    const NOT_SIGNALING: i32 = VERSIONBITS_TOP_BITS;
+
+    fn deployment() -> Deployment {
+        Deployment {
+            bit: BIT,
+            start_time: 1_000,
+            timeout: 10_000,
+            threshold: 1916,
+            period: PERIOD,
+        }
+    }
+
+    // One window per entry: (time of every block, number of signaling blocks)
+    fn build_headers(windows: &[(u64, u32)]) -> Vec<(u64, i32)> {
+        let mut headers = Vec::new();
+        for &(time, signaling) in windows {
+            for i in 0..PERIOD {
+                let version = if i < signaling {
+                    SIGNALING
+                } else {
+                    NOT_SIGNALING
+                };
+                headers.push((time, version));
+            }
+        }
+        headers
+    }
+
+    fn window_start(window: u64) -> u64 {
+        window * PERIOD as u64
+    }
+
+    #[test]
+    fn test_signals_requires_top_bits() {
+        assert!(signals(SIGNALING, BIT));
+        assert!(!signals(SIGNALING, BIT + 1));
+        assert!(!signals(NOT_SIGNALING, BIT));
+        // Same bit set but without the 001 prefix
+        assert!(!signals(1 << BIT, BIT));
+        assert!(!signals(0x60000000 | (1 << BIT), BIT));
+        // The top bits themselves are not deployments
+        assert!(!signals(NOT_SIGNALING, 29));
+        assert!(!signals(NOT_SIGNALING, 31));
+        assert!(!signals(-1, 40));
+    }
+
+    #[test]
+    fn test_deployment_locks_in_exactly_at_threshold() {
+        let headers = build_headers(&[(0, 0), (1_000, 0), (2_000, 1916), (3_000, 0), (4_000, 0)]);
+        let version_bits = VersionBits::new(deployment()).unwrap();
+
+        assert_eq!(
+            version_bits.state_at(&headers, 0).unwrap(),
+            ThresholdState::Defined
+        );
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(1)).unwrap(),
+            ThresholdState::Defined
+        );
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(2)).unwrap(),
+            ThresholdState::Started
+        );
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(3)).unwrap(),
+            ThresholdState::LockedIn
+        );
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(4)).unwrap(),
+            ThresholdState::Active
+        );
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(5)).unwrap(),
+            ThresholdState::Active
+        );
+    }
+
+    #[test]
+    fn test_deployment_one_short_of_threshold_stays_started() {
+        let headers = build_headers(&[(1_000, 0), (2_000, 1915), (3_000, 0)]);
+        let version_bits = VersionBits::new(deployment()).unwrap();
+
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(2)).unwrap(),
+            ThresholdState::Started
+        );
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(3)).unwrap(),
+            ThresholdState::Started
+        );
+    }
+
+    #[test]
+    fn test_deployment_fails_at_timeout() {
+        let headers = build_headers(&[(1_000, 0), (5_000, 100), (10_000, 2016), (20_000, 2016)]);
+        let version_bits = VersionBits::new(deployment()).unwrap();
+
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(2)).unwrap(),
+            ThresholdState::Started
+        );
+        // The window reaching the timeout fails even though every block signaled
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(3)).unwrap(),
+            ThresholdState::Failed
+        );
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(4)).unwrap(),
+            ThresholdState::Failed
+        );
+    }
+
+    #[test]
+    fn test_window_boundaries() {
+        let headers = build_headers(&[(1_000, 0), (2_000, 2016), (3_000, 0)]);
+        let version_bits = VersionBits::new(deployment()).unwrap();
+
+        // The last block of a window shares the state of its first block
+        assert_eq!(
+            version_bits
+                .state_at(&headers, window_start(1) - 1)
+                .unwrap(),
+            ThresholdState::Defined
+        );
+        assert_eq!(
+            version_bits
+                .state_at(&headers, window_start(2) - 1)
+                .unwrap(),
+            ThresholdState::Started
+        );
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(2)).unwrap(),
+            ThresholdState::LockedIn
+        );
+        assert_eq!(
+            version_bits
+                .state_at(&headers, window_start(3) - 1)
+                .unwrap(),
+            ThresholdState::LockedIn
+        );
+    }
+
+    #[test]
+    fn test_transitions_use_median_time_past() {
+        let mut headers = build_headers(&[(500, 0), (1_000, 0), (5_000, 2016)]);
+        // A miner stamping the last block of the first window past the start time doesn't
+        // start the deployment early, and one stamping past the timeout doesn't make it fail
+        headers[window_start(1) as usize - 1].0 = 5_000;
+        headers[window_start(3) as usize - 1].0 = 50_000;
+        let version_bits = VersionBits::new(deployment()).unwrap();
+
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(1)).unwrap(),
+            ThresholdState::Defined
+        );
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(2)).unwrap(),
+            ThresholdState::Started
+        );
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(3)).unwrap(),
+            ThresholdState::LockedIn
+        );
+    }
+
+    #[test]
+    fn test_missing_headers() {
+        let headers = build_headers(&[(1_000, 0), (2_000, 2016)]);
+        let version_bits = VersionBits::new(deployment()).unwrap();
+
+        assert!(version_bits.state_at(&headers, window_start(3) - 1).is_ok());
+        assert_eq!(
+            version_bits.state_at(&headers, window_start(3)),
+            Err(BlockError::MissingHeaders {
+                needed: window_start(3),
+                available: window_start(2),
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_deployments_are_rejected() {
+        let invalid = [
+            Deployment {
+                period: 0,
+                ..deployment()
+            },
+            Deployment {
+                threshold: PERIOD + 1,
+                ..deployment()
+            },
+            Deployment {
+                threshold: 0,
+                ..deployment()
+            },
+            Deployment {
+                bit: 29,
+                ..deployment()
+            },
+            Deployment {
+                start_time: 20_000,
+                ..deployment()
+            },
+        ];
+        for deployment in invalid {
+            assert!(matches!(
+                VersionBits::new(deployment),
+                Err(BlockError::InvalidDeployment { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,516 @@
+// This module implements elements of the binary field 𝔽_2^m.
+//
+// An element is a polynomial of degree < m with coefficients in 𝔽_2, stored as the bits of a
+// `BigUint` (bit i is the coefficient of xⁱ). Arithmetic is done modulo an irreducible
+// polynomial of degree m, stored the same way:
+//
+//     a + b = a XOR b                      (so subtraction is the same as addition)
+//     a * b = carry-less product mod f(x)
+//
+// For example, the AES field 𝔽_2^8 uses f(x) = x⁸ + x⁴ + x³ + x + 1, i.e. 0x11b.
+
+use num_bigint::{BigInt, BigUint};
+use num_traits::{One, Signed, Zero};
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::sync::Arc;
+
+use crate::elliptic_curve::traits;
+use crate::types::errors::FieldError;
+
+/// An element of the binary field 𝔽_2^m, stored together with its reduction polynomial.
+///
+/// Like `FieldElement`, the polynomial is reference counted and shared between elements
+/// derived from each other.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct BinaryFieldElement {
+    pub(crate) bits: BigUint,
+    pub(crate) modulus: Arc<BigUint>,
+}
+
+impl BinaryFieldElement {
+    // Reduces `bits` modulo `modulus`, which must have degree at least 1. Irreducibility is
+    // not checked.
+    pub fn new<M: Into<Arc<BigUint>>>(bits: BigUint, modulus: M) -> Self {
+        let modulus = modulus.into();
+        assert!(
+            modulus.bits() >= 2,
+            "The reduction polynomial must have degree at least 1"
+        );
+        let bits = poly_rem(bits, &modulus);
+        BinaryFieldElement { bits, modulus }
+    }
+
+    pub fn zero<M: Into<Arc<BigUint>>>(modulus: M) -> Self {
+        BinaryFieldElement::new(BigUint::zero(), modulus)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.bits.is_zero()
+    }
+
+    pub fn one<M: Into<Arc<BigUint>>>(modulus: M) -> Self {
+        BinaryFieldElement::new(BigUint::one(), modulus)
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.bits.is_one()
+    }
+
+    pub fn bits(&self) -> &BigUint {
+        &self.bits
+    }
+
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    // The m in 𝔽_2^m.
+    pub fn degree(&self) -> u64 {
+        self.modulus.bits() - 1
+    }
+
+    fn check_same_field(&self, elem: &BinaryFieldElement) -> Result<(), FieldError> {
+        if self.modulus != elem.modulus {
+            return Err(FieldError::DifferentFields {
+                left: BigInt::from((*self.modulus).clone()),
+                right: BigInt::from((*elem.modulus).clone()),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn try_add(&self, elem: &BinaryFieldElement) -> Result<BinaryFieldElement, FieldError> {
+        self.check_same_field(elem)?;
+        Ok(BinaryFieldElement {
+            bits: &self.bits ^ &elem.bits,
+            modulus: self.modulus.clone(),
+        })
+    }
+
+    // In characteristic 2 every element is its own negative.
+    pub fn try_sub(&self, elem: &BinaryFieldElement) -> Result<BinaryFieldElement, FieldError> {
+        self.try_add(elem)
+    }
+
+    pub fn try_mul(&self, elem: &BinaryFieldElement) -> Result<BinaryFieldElement, FieldError> {
+        self.check_same_field(elem)?;
+        let bits = poly_rem(poly_mul(&self.bits, &elem.bits), &self.modulus);
+        Ok(BinaryFieldElement {
+            bits,
+            modulus: self.modulus.clone(),
+        })
+    }
+
+    pub fn try_div(&self, elem: &BinaryFieldElement) -> Result<BinaryFieldElement, FieldError> {
+        self.check_same_field(elem)?;
+        self.try_mul(&elem.inverse()?)
+    }
+
+    // Multiplicative inverse with the extended Euclidean algorithm over 𝔽_2[x]. Since
+    // irreducibility isn't checked, an element sharing a factor with a reducible modulus
+    // has no inverse.
+    pub fn inverse(&self) -> Result<BinaryFieldElement, FieldError> {
+        if self.is_zero() {
+            return Err(FieldError::DivisionByZero);
+        }
+        // Invariant: old_s * bits ≡ old_r (mod f). The last non-zero remainder is gcd = 1.
+        let (mut old_r, mut r) = (self.bits.clone(), (*self.modulus).clone());
+        let (mut old_s, mut s) = (BigUint::one(), BigUint::zero());
+        while !r.is_zero() {
+            let (quotient, remainder) = poly_div_rem(old_r, &r);
+            old_r = std::mem::replace(&mut r, remainder);
+            let next_s = &old_s ^ poly_mul(&quotient, &s);
+            old_s = std::mem::replace(&mut s, next_s);
+        }
+        if !old_r.is_one() {
+            return Err(FieldError::NotInvertible {
+                num: BigInt::from(self.bits.clone()),
+                modulus: BigInt::from((*self.modulus).clone()),
+            });
+        }
+        Ok(BinaryFieldElement::new(old_s, self.modulus.clone()))
+    }
+
+    pub fn pow<E: Into<BigInt>>(&self, exp: E) -> BinaryFieldElement {
+        self.try_pow(exp).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    // A negative exponent raises the inverse, so it fails for zero.
+    pub fn try_pow<E: Into<BigInt>>(&self, exp: E) -> Result<BinaryFieldElement, FieldError> {
+        let exp = exp.into();
+        let base = if exp.is_negative() {
+            self.inverse()?
+        } else {
+            self.clone()
+        };
+        let exp = exp.magnitude();
+
+        let mut result = BinaryFieldElement::one(self.modulus.clone());
+        for i in (0..exp.bits()).rev() {
+            result *= result.clone();
+            if exp.bit(i) {
+                result *= &base;
+            }
+        }
+        Ok(result)
+    }
+}
+
+// Carry-less multiplication: the partial products are combined with XOR instead of addition.
+fn poly_mul(a: &BigUint, b: &BigUint) -> BigUint {
+    let mut result = BigUint::zero();
+    for i in 0..b.bits() {
+        if b.bit(i) {
+            result ^= a << i;
+        }
+    }
+    result
+}
+
+// Polynomial long division over 𝔽_2: cancel the leading term until the degree drops below
+// the divisor's.
+fn poly_div_rem(mut a: BigUint, b: &BigUint) -> (BigUint, BigUint) {
+    let mut quotient = BigUint::zero();
+    while a.bits() >= b.bits() {
+        let shift = a.bits() - b.bits();
+        quotient.set_bit(shift, true);
+        a ^= b << shift;
+    }
+    (quotient, a)
+}
+
+fn poly_rem(a: BigUint, b: &BigUint) -> BigUint {
+    poly_div_rem(a, b).1
+}
+
+// Printed with the polynomials in hex, e.g. `BinaryFieldElement_0x11b(0x57)`.
+impl fmt::Display for BinaryFieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BinaryFieldElement_{:#x}({:#x})",
+            *self.modulus, self.bits
+        )
+    }
+}
+
+// The operators panic when the operands belong to different fields (or on division by zero);
+// the `try_*` methods return the error instead.
+impl Add<BinaryFieldElement> for BinaryFieldElement {
+    type Output = Self;
+
+    fn add(self, elem: BinaryFieldElement) -> BinaryFieldElement {
+        self.try_add(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+impl Sub<BinaryFieldElement> for BinaryFieldElement {
+    type Output = Self;
+
+    fn sub(self, elem: BinaryFieldElement) -> BinaryFieldElement {
+        self.try_sub(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+impl Mul<BinaryFieldElement> for BinaryFieldElement {
+    type Output = Self;
+
+    fn mul(self, elem: BinaryFieldElement) -> BinaryFieldElement {
+        self.try_mul(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+impl Div<BinaryFieldElement> for BinaryFieldElement {
+    type Output = Self;
+
+    fn div(self, elem: BinaryFieldElement) -> BinaryFieldElement {
+        self.try_div(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+impl AddAssign<&BinaryFieldElement> for BinaryFieldElement {
+    fn add_assign(&mut self, elem: &BinaryFieldElement) {
+        *self = self.try_add(elem).unwrap_or_else(|error| panic!("{error}"));
+    }
+}
+
+impl AddAssign<BinaryFieldElement> for BinaryFieldElement {
+    fn add_assign(&mut self, elem: BinaryFieldElement) {
+        *self += &elem;
+    }
+}
+
+impl SubAssign<&BinaryFieldElement> for BinaryFieldElement {
+    fn sub_assign(&mut self, elem: &BinaryFieldElement) {
+        *self = self.try_sub(elem).unwrap_or_else(|error| panic!("{error}"));
+    }
+}
+
+impl SubAssign<BinaryFieldElement> for BinaryFieldElement {
+    fn sub_assign(&mut self, elem: BinaryFieldElement) {
+        *self -= &elem;
+    }
+}
+
+impl MulAssign<&BinaryFieldElement> for BinaryFieldElement {
+    fn mul_assign(&mut self, elem: &BinaryFieldElement) {
+        self.check_same_field(elem)
+            .unwrap_or_else(|error| panic!("{error}"));
+        self.bits = poly_rem(poly_mul(&self.bits, &elem.bits), &self.modulus);
+    }
+}
+
+impl MulAssign<BinaryFieldElement> for BinaryFieldElement {
+    fn mul_assign(&mut self, elem: BinaryFieldElement) {
+        *self *= &elem;
+    }
+}
+
+impl DivAssign<&BinaryFieldElement> for BinaryFieldElement {
+    fn div_assign(&mut self, elem: &BinaryFieldElement) {
+        *self = self.try_div(elem).unwrap_or_else(|error| panic!("{error}"));
+    }
+}
+
+impl DivAssign<BinaryFieldElement> for BinaryFieldElement {
+    fn div_assign(&mut self, elem: BinaryFieldElement) {
+        *self /= &elem;
+    }
+}
+
+// As for `FieldElement`, an empty iterator panics: there is no modulus to build the identity in.
+impl Sum for BinaryFieldElement {
+    fn sum<I: Iterator<Item = BinaryFieldElement>>(mut iter: I) -> BinaryFieldElement {
+        let mut total = iter
+            .next()
+            .expect("Cannot sum an empty iterator of field elements");
+        for elem in iter {
+            total += elem;
+        }
+        total
+    }
+}
+
+impl<'a> Sum<&'a BinaryFieldElement> for BinaryFieldElement {
+    fn sum<I: Iterator<Item = &'a BinaryFieldElement>>(iter: I) -> BinaryFieldElement {
+        iter.cloned().sum()
+    }
+}
+
+impl Product for BinaryFieldElement {
+    fn product<I: Iterator<Item = BinaryFieldElement>>(mut iter: I) -> BinaryFieldElement {
+        let mut total = iter
+            .next()
+            .expect("Cannot multiply an empty iterator of field elements");
+        for elem in iter {
+            total *= elem;
+        }
+        total
+    }
+}
+
+impl<'a> Product<&'a BinaryFieldElement> for BinaryFieldElement {
+    fn product<I: Iterator<Item = &'a BinaryFieldElement>>(iter: I) -> BinaryFieldElement {
+        iter.cloned().product()
+    }
+}
+
+impl Neg for &BinaryFieldElement {
+    type Output = BinaryFieldElement;
+
+    fn neg(self) -> BinaryFieldElement {
+        self.clone()
+    }
+}
+
+impl Neg for BinaryFieldElement {
+    type Output = BinaryFieldElement;
+
+    fn neg(self) -> BinaryFieldElement {
+        self
+    }
+}
+
+impl traits::Field for BinaryFieldElement {
+    fn zero_like(&self) -> Self {
+        BinaryFieldElement::zero(self.modulus.clone())
+    }
+
+    fn one_like(&self) -> Self {
+        BinaryFieldElement::one(self.modulus.clone())
+    }
+
+    fn is_zero(&self) -> bool {
+        BinaryFieldElement::is_zero(self)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        BinaryFieldElement::inverse(self).ok()
+    }
+
+    fn pow(&self, exp: u64) -> Self {
+        BinaryFieldElement::pow(self, exp)
+    }
+}
+
+#[cfg(test)]
+mod binary_field_tests {
+    use super::*;
+
+    const AES_MODULUS: u32 = 0x11b;
+
+    fn aes(bits: u32) -> BinaryFieldElement {
+        BinaryFieldElement::new(BigUint::from(bits), BigUint::from(AES_MODULUS))
+    }
+
+    // The byte-oriented multiplication from the AES specification, used as a reference.
+    fn aes_reference_mul(mut a: u8, mut b: u8) -> u8 {
+        let mut product = 0;
+        while b != 0 {
+            if b & 1 == 1 {
+                product ^= a;
+            }
+            let carry = a & 0x80 != 0;
+            a <<= 1;
+            if carry {
+                a ^= 0x1b;
+            }
+            b >>= 1;
+        }
+        product
+    }
+
+    #[test]
+    fn test_aes_field_known_values() {
+        // Examples from FIPS 197, section 4.2
+        assert_eq!(aes(0x57) + aes(0x83), aes(0xd4));
+        assert_eq!(aes(0x57) * aes(0x83), aes(0xc1));
+        assert_eq!(aes(0x57) * aes(0x13), aes(0xfe));
+        assert_eq!(aes(0x53).inverse().unwrap(), aes(0xca));
+        assert_eq!(aes(0x57) - aes(0x83), aes(0x57) + aes(0x83));
+        assert_eq!(-aes(0x57), aes(0x57));
+        assert_eq!(aes(0x1ff), aes(0xe4));
+    }
+
+    #[test]
+    fn test_aes_field_multiplication_table() {
+        let elements: Vec<BinaryFieldElement> = (0..256).map(aes).collect();
+        for a in 0..=255u8 {
+            for b in 0..=255u8 {
+                let product = elements[a as usize].clone() * elements[b as usize].clone();
+                assert_eq!(
+                    product.bits,
+                    BigUint::from(aes_reference_mul(a, b)),
+                    "{a:#x} * {b:#x}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_aes_field_inverse_and_pow() {
+        for a in 1..256 {
+            let a = aes(a);
+            assert!((a.inverse().unwrap() * a.clone()).is_one());
+            assert_eq!(a.pow(255), aes(1));
+            assert_eq!(a.pow(-1), a.inverse().unwrap());
+            assert_eq!(a.pow(254), a.inverse().unwrap());
+            assert_eq!(aes(1) / a.clone(), a.inverse().unwrap());
+        }
+        assert_eq!(aes(0).inverse(), Err(FieldError::DivisionByZero));
+        assert_eq!(aes(0).try_pow(-1), Err(FieldError::DivisionByZero));
+        assert!(aes(0).pow(0).is_one());
+    }
+
+    #[test]
+    fn test_gf2_163_reduction() {
+        // f(x) = x^163 + x^7 + x^6 + x^3 + 1, the NIST B-163 / K-163 polynomial
+        let modulus: Arc<BigUint> = Arc::new((BigUint::one() << 163) | BigUint::from(0xc9_u32));
+        let monomial =
+            |degree: u64| BinaryFieldElement::new(BigUint::one() << degree, modulus.clone());
+
+        // x^163 ≡ x^7 + x^6 + x^3 + 1
+        assert_eq!(monomial(162) * monomial(1), monomial(163));
+        assert_eq!(monomial(163).bits, BigUint::from(0xc9_u32));
+        // x^164 ≡ x^8 + x^7 + x^4 + x
+        assert_eq!((monomial(162) * monomial(2)).bits, BigUint::from(0x192_u32));
+
+        let a = BinaryFieldElement::new(BigUint::from(0x1234_5678_9abc_def0_u64) << 90, modulus);
+        assert_eq!(a.degree(), 163);
+        assert!((a.inverse().unwrap() * a.clone()).is_one());
+        // The multiplicative group has order 2^163 - 1
+        assert!(a.pow((BigInt::one() << 163) - 1).is_one());
+    }
+
+    #[test]
+    fn test_assign_and_iterator_operators() {
+        let mut x = aes(0x57);
+        x *= aes(0x83);
+        assert_eq!(x, aes(0xc1));
+        x /= &aes(0x83);
+        assert_eq!(x, aes(0x57));
+        x += aes(0x83);
+        x -= &aes(0x83);
+        assert_eq!(x, aes(0x57));
+
+        let elements = [aes(0x57), aes(0x83), aes(0x13)];
+        assert_eq!(
+            elements.iter().sum::<BinaryFieldElement>(),
+            aes(0x57 ^ 0x83 ^ 0x13)
+        );
+        assert_eq!(
+            elements.iter().product::<BinaryFieldElement>(),
+            aes(0x57) * aes(0x83) * aes(0x13)
+        );
+        assert_eq!(format!("{}", aes(0x57)), "BinaryFieldElement_0x11b(0x57)");
+    }
+
+    #[test]
+    fn test_field_trait() {
+        use crate::elliptic_curve::traits::traits_tests::field_identities;
+
+        field_identities(aes(0x57));
+    }
+
+    #[test]
+    fn test_different_binary_fields() {
+        let other = BinaryFieldElement::new(BigUint::from(0x57_u32), BigUint::from(0x11d_u32));
+        assert_eq!(
+            aes(0x57).try_mul(&other),
+            Err(FieldError::DifferentFields {
+                left: BigInt::from(0x11b),
+                right: BigInt::from(0x11d)
+            })
+        );
+    }
+
+    #[test]
+    fn test_reducible_modulus_has_non_invertible_elements() {
+        // x² + 1 = (x + 1)² over 𝔽_2
+        let element =
+            |bits: u32| BinaryFieldElement::new(BigUint::from(bits), BigUint::from(0b101_u32));
+
+        assert_eq!(
+            element(0b11).inverse(),
+            Err(FieldError::NotInvertible {
+                num: BigInt::from(0b11),
+                modulus: BigInt::from(0b101)
+            })
+        );
+        assert!(element(0b1).try_div(&element(0b11)).is_err());
+        // x * x = 1, so x is still its own inverse
+        assert_eq!(element(0b10).inverse(), Ok(element(0b10)));
+    }
+
+    #[test]
+    #[should_panic(expected = "The reduction polynomial must have degree at least 1")]
+    fn test_constant_modulus() {
+        BinaryFieldElement::new(BigUint::from(1_u32), BigUint::from(1_u32));
+    }
+}
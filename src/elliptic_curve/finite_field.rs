@@ -1,53 +1,498 @@
 // Create struct for a finite field element.
-use num_bigint::BigInt;
+use num_bigint::{BigInt, BigUint, Sign};
 use num_integer::Integer;
-use num_traits::Zero;
-use std::ops::{Add, Div, Mul, Sub};
+use num_traits::{Num, One, Signed, ToPrimitive, Zero};
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::mem;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+use std::sync::Arc;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub(crate) struct FieldElement {
+use crate::elliptic_curve::traits;
+use crate::types::errors::FieldError;
+
+/// An element of the prime field 𝔽_p, stored together with its prime.
+///
+/// `num_traits::Zero` and `One` are not implemented: their constructors take no arguments,
+/// so they can't know which field to build the identity in. Use `FieldElement::zero(prime)`
+/// and `FieldElement::one(prime)` instead, or `Field::zero_like` and `Field::one_like` in
+/// generic code.
+///
+/// The prime is reference counted: every element derived from another one shares its
+/// allocation. Constructors accept either a `BigInt` or an `Arc<BigInt>`, and passing the same
+/// `Arc` makes unrelated elements share it too.
+///
+/// `==` exits at the first differing limb, so its timing depends on the values. That's fine
+/// for public data; compare secrets (private keys, nonces) with `ct_eq` instead.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct FieldElement {
     pub(crate) num: BigInt,
-    pub(crate) prime: BigInt,
+    pub(crate) prime: Arc<BigInt>,
 }
 
 impl FieldElement {
-    pub fn new(num: BigInt, prime: BigInt) -> Self {
+    pub fn new<P: Into<Arc<BigInt>>>(num: BigInt, prime: P) -> Self {
+        FieldElement {
+            num,
+            prime: prime.into(),
+        }
+    }
+
+    // Like `new`, but only accepts canonical elements 0 <= num < prime. Primality itself is
+    // not checked.
+    pub fn try_new<P: Into<Arc<BigInt>>>(num: BigInt, prime: P) -> Result<Self, FieldError> {
+        let prime = prime.into();
+        if *prime < BigInt::from(2) {
+            return Err(FieldError::InvalidPrime {
+                prime: (*prime).clone(),
+            });
+        }
+        if num.is_negative() || num >= *prime {
+            return Err(FieldError::OutOfRange {
+                num,
+                prime: (*prime).clone(),
+            });
+        }
+        Ok(FieldElement { num, prime })
+    }
+
+    // Parses a big-endian hex number, with or without a `0x` prefix and in either case, as a
+    // canonical element of the field.
+    pub fn from_hex<P: Into<Arc<BigInt>>>(num_hex: &str, prime: P) -> Result<Self, FieldError> {
+        FieldElement::try_new(parse_hex(num_hex)?, prime)
+    }
+
+    // Accepts any integer and reduces it into [0, prime), so -1 becomes prime - 1.
+    pub fn new_reduced<P: Into<Arc<BigInt>>>(num: BigInt, prime: P) -> Self {
+        let prime = prime.into();
+        let num = num.mod_floor(&prime);
         FieldElement { num, prime }
     }
 
-    pub fn pow(&self, exp: &BigInt) -> FieldElement {
-        let prime_minus_one = &self.prime - 1;
-        let positive_exponent = exp.mod_floor(&prime_minus_one);
-        let num = self.num.modpow(&positive_exponent, &self.prime);
-        FieldElement::new(num, self.prime.clone())
+    // A negative exponent raises the inverse to |exp|, so x.pow(-1) == 1 / x. Panics when
+    // zero is raised to a negative power; `try_pow` returns the error instead.
+    pub fn pow<E: Into<BigInt>>(&self, exp: E) -> FieldElement {
+        self.try_pow(exp).unwrap_or_else(|error| panic!("{error}"))
     }
 
-    pub fn zero(prime: BigInt) -> FieldElement {
-        FieldElement {
-            num: BigInt::zero(),
-            prime,
-        }
+    pub fn try_pow<E: Into<BigInt>>(&self, exp: E) -> Result<FieldElement, FieldError> {
+        let exp = exp.into();
+        let base = if exp.is_negative() {
+            self.inverse()?.num
+        } else {
+            self.num.clone()
+        };
+        let num = base.modpow(&exp.abs(), self.prime());
+        Ok(FieldElement::new(num, self.prime.clone()))
+    }
+
+    pub fn zero<P: Into<Arc<BigInt>>>(prime: P) -> FieldElement {
+        FieldElement::new(BigInt::zero(), prime)
     }
 
     pub fn is_zero(&self) -> bool {
         self.num == BigInt::zero()
     }
 
-    pub fn prime(&self) -> BigInt {
-        self.prime.clone()
+    pub fn one<P: Into<Arc<BigInt>>>(prime: P) -> FieldElement {
+        FieldElement::new(BigInt::one(), prime)
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.num.is_one()
+    }
+
+    pub fn num(&self) -> &BigInt {
+        &self.num
+    }
+
+    pub fn prime(&self) -> &BigInt {
+        &self.prime
+    }
+
+    // The shared handle, to build other elements of the same field without a new allocation.
+    pub fn shared_prime(&self) -> &Arc<BigInt> {
+        &self.prime
+    }
+
+    /// Compares two elements in time that depends only on the size of their primes.
+    ///
+    /// Both numbers are first reduced into [0, prime), so unreduced or negative inputs compare
+    /// by their value in the field. The numbers and the primes are then read as a fixed count
+    /// of 64-bit limbs, the width of the larger prime, and every limb is compared without
+    /// exiting early.
+    pub fn ct_eq(&self, other: &FieldElement) -> bool {
+        let width = self.prime.bits().max(other.prime.bits()).div_ceil(64) as usize;
+        let left = self.num.mod_floor(&self.prime);
+        let right = other.num.mod_floor(&other.prime);
+
+        let mut difference = 0u64;
+        for (left, right) in [(&left, &right), (&*self.prime, &*other.prime)] {
+            let (mut left, mut right) = (left.iter_u64_digits(), right.iter_u64_digits());
+            for _ in 0..width {
+                difference |= left.next().unwrap_or(0) ^ right.next().unwrap_or(0);
+            }
+        }
+        difference == 0
+    }
+
+    // Two hex digits per byte of the prime, so every element of a field has the same width
+    // (64 digits for secp256k1).
+    fn hex_width(&self) -> usize {
+        self.prime.to_bytes_be().1.len() * 2
+    }
+
+    // Reduces a product modulo the prime, with a faster path for primes 2^256 - c such as
+    // secp256k1's.
+    fn reduce(&self, num: BigInt) -> BigInt {
+        match pseudo_mersenne_constant(&self.prime) {
+            Some(c) => reduce_pseudo_mersenne(num, &self.prime, c),
+            None => num.mod_floor(&self.prime),
+        }
+    }
+
+    pub(crate) fn check_same_field(&self, elem: &FieldElement) -> Result<(), FieldError> {
+        if !Arc::ptr_eq(&self.prime, &elem.prime) && self.prime != elem.prime {
+            return Err(FieldError::DifferentFields {
+                left: self.prime().clone(),
+                right: elem.prime().clone(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn try_add(&self, elem: &FieldElement) -> Result<FieldElement, FieldError> {
+        self.check_same_field(elem)?;
+        let num = (&self.num + &elem.num).mod_floor(self.prime());
+        Ok(FieldElement::new(num, self.prime.clone()))
+    }
+
+    pub fn try_sub(&self, elem: &FieldElement) -> Result<FieldElement, FieldError> {
+        self.check_same_field(elem)?;
+        let num = (&self.num - &elem.num).mod_floor(self.prime());
+        Ok(FieldElement::new(num, self.prime.clone()))
+    }
+
+    pub fn try_mul(&self, elem: &FieldElement) -> Result<FieldElement, FieldError> {
+        self.check_same_field(elem)?;
+        let num = self.reduce(&self.num * &elem.num);
+        Ok(FieldElement::new(num, self.prime.clone()))
+    }
+
+    pub fn try_div(&self, elem: &FieldElement) -> Result<FieldElement, FieldError> {
+        self.check_same_field(elem)?;
+        let num = (&self.num * elem.inverse()?.num).mod_floor(self.prime());
+        Ok(FieldElement::new(num, self.prime.clone()))
+    }
+
+    // Multiplicative inverse with the extended Euclidean algorithm, which needs far fewer
+    // BigInt operations than the Fermat exponentiation self^(p - 2).
+    pub fn inverse(&self) -> Result<FieldElement, FieldError> {
+        let num = self.num.mod_floor(self.prime());
+        if num.is_zero() {
+            return Err(FieldError::DivisionByZero);
+        }
+        // Invariant: old_s * num ≡ old_r (mod p). The last non-zero remainder is gcd = 1.
+        let (mut old_r, mut r) = (num, self.prime().clone());
+        let (mut old_s, mut s) = (BigInt::one(), BigInt::zero());
+        while !r.is_zero() {
+            let quotient = &old_r / &r;
+            let next_r = &old_r - &quotient * &r;
+            old_r = mem::replace(&mut r, next_r);
+            let next_s = &old_s - &quotient * &s;
+            old_s = mem::replace(&mut s, next_s);
+        }
+        Ok(FieldElement::new(
+            old_s.mod_floor(self.prime()),
+            self.prime.clone(),
+        ))
+    }
+
+    // Inverse by Fermat's little theorem, kept to cross-check `inverse`.
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn fermat_inverse(&self) -> FieldElement {
+        let num = self.num.modpow(&(self.prime() - 2_i32), self.prime());
+        FieldElement::new(num, self.prime.clone())
+    }
+
+    // Legendre symbol computed with Euler's criterion, self^((p - 1) / 2): 1 for non-zero
+    // squares, -1 for non-squares and 0 for zero.
+    pub fn legendre(&self) -> i8 {
+        let exponent = (self.prime() - 1_i32) / 2_i32;
+        let result = self.num.modpow(&exponent, self.prime());
+        if result.is_zero() {
+            0
+        } else if result.is_one() {
+            1
+        } else {
+            -1
+        }
+    }
+
+    pub fn is_quadratic_residue(&self) -> bool {
+        self.legendre() >= 0
+    }
+
+    // Square root of a quadratic residue, or `None` when self is not a square. The other root
+    // is its negation. For primes congruent to 3 mod 4 (such as secp256k1's) a root is
+    // self^((p + 1) / 4); other primes fall back to Tonelli-Shanks.
+    pub fn sqrt(&self) -> Option<FieldElement> {
+        if *self.prime() == BigInt::from(2) {
+            // Both elements of 𝔽_2 are their own root
+            return Some(self.clone());
+        }
+        if (self.prime() % 4_i32) != BigInt::from(3) {
+            return self.tonelli_shanks();
+        }
+        if !self.is_quadratic_residue() {
+            return None;
+        }
+        Some(FieldElement::new(
+            self.num
+                .modpow(&((self.prime() + 1_i32) / 4_i32), self.prime()),
+            self.prime.clone(),
+        ))
+    }
+
+    // Both square roots in any prime field, as the pair (r, -r).
+    pub fn sqrt_general(&self) -> Option<(FieldElement, FieldElement)> {
+        let root = self.sqrt()?;
+        let negated = -&root;
+        Some((root, negated))
+    }
+
+    fn tonelli_shanks(&self) -> Option<FieldElement> {
+        let prime = self.prime();
+        let one = BigInt::one();
+        let prime_minus_one = prime - 1_i32;
+        let euler_exponent = &prime_minus_one / 2_i32;
+
+        match self.legendre() {
+            0 => return Some(FieldElement::zero(self.prime.clone())),
+            -1 => return None,
+            _ => {}
+        }
+
+        // p - 1 = q * 2^s with q odd
+        let mut q = prime_minus_one.clone();
+        let mut s = 0u32;
+        while q.is_even() {
+            q >>= 1;
+            s += 1;
+        }
+
+        // Any non-residue z works as the generator of the 2-power part
+        let mut z = BigInt::from(2);
+        while z.modpow(&euler_exponent, prime) != prime_minus_one {
+            z += 1;
+        }
+
+        let mut m = s;
+        let mut c = z.modpow(&q, prime);
+        let mut t = self.num.modpow(&q, prime);
+        let mut r = self.num.modpow(&((&q + 1_i32) / 2_i32), prime);
+        while t != one {
+            // Least i such that t^(2^i) = 1
+            let mut i = 0;
+            let mut t_power = t.clone();
+            while t_power != one {
+                t_power = (&t_power * &t_power) % prime;
+                i += 1;
+            }
+            let b = c.modpow(&(BigInt::one() << (m - i - 1)), prime);
+            m = i;
+            c = (&b * &b) % prime;
+            t = (t * &c) % prime;
+            r = (r * &b) % prime;
+        }
+        Some(FieldElement::new(r, self.prime.clone()))
+    }
+}
+
+// The c of a prime p = 2^256 - c with c < 2^64, read from its limbs without allocating: the
+// three upper limbs are all ones and the lowest one is 2^64 - c. secp256k1's c is 2^32 + 977.
+fn pseudo_mersenne_constant(prime: &BigInt) -> Option<u64> {
+    let mut limbs = prime.iter_u64_digits();
+    if prime.sign() != Sign::Plus || limbs.len() != 4 {
+        return None;
+    }
+    let low = limbs.next()?;
+    if low == 0 || !limbs.all(|limb| limb == u64::MAX) {
+        return None;
+    }
+    Some(low.wrapping_neg())
+}
+
+// Since p = 2^256 - c, 2^256 ≡ c (mod p). A number n = high * 2^256 + low can therefore be
+// folded into low + high * c. Working on 64-bit limbs, one fold of a product of two reduced
+// elements leaves a small overflow above 2^256, which is folded the same way until nothing
+// is left. The result is then below 2^256 = p + c, so at most one
+// subtraction of p finishes the reduction.
+fn reduce_pseudo_mersenne(num: BigInt, prime: &BigInt, c: u64) -> BigInt {
+    let (sign, limbs) = num.to_u64_digits();
+    if sign == Sign::Minus || limbs.len() > 8 {
+        return num.mod_floor(prime);
+    }
+    let limb = |i: usize| limbs.get(i).copied().unwrap_or(0);
+    let prime_limbs = [c.wrapping_neg(), u64::MAX, u64::MAX, u64::MAX];
+
+    // low + high * c, as 4 limbs plus an overflow limb
+    let mut reduced = [0u64; 4];
+    let mut carry: u128 = 0;
+    for (i, limb_out) in reduced.iter_mut().enumerate() {
+        let acc = u128::from(limb(i)) + u128::from(limb(i + 4)) * u128::from(c) + carry;
+        *limb_out = acc as u64;
+        carry = acc >> 64;
+    }
+    // Fold the overflow limb until nothing is left above 2^256
+    while carry != 0 {
+        let mut fold = carry * u128::from(c);
+        carry = 0;
+        for limb_out in reduced.iter_mut() {
+            let acc = u128::from(*limb_out) + (fold & u128::from(u64::MAX)) + carry;
+            *limb_out = acc as u64;
+            carry = acc >> 64;
+            fold >>= 64;
+        }
+    }
+
+    if reduced.iter().rev().ge(prime_limbs.iter().rev()) {
+        let mut borrow = false;
+        for (limb_out, prime_limb) in reduced.iter_mut().zip(prime_limbs) {
+            let (difference, borrow_a) = limb_out.overflowing_sub(prime_limb);
+            let (difference, borrow_b) = difference.overflowing_sub(u64::from(borrow));
+            *limb_out = difference;
+            borrow = borrow_a || borrow_b;
+        }
+    }
+
+    let digits: Vec<u32> = reduced
+        .iter()
+        .flat_map(|limb| [*limb as u32, (*limb >> 32) as u32])
+        .collect();
+    BigInt::from_biguint(Sign::Plus, BigUint::new(digits))
+}
+
+fn parse_hex(hex: &str) -> Result<BigInt, FieldError> {
+    let digits = hex
+        .strip_prefix("0x")
+        .or_else(|| hex.strip_prefix("0X"))
+        .unwrap_or(hex);
+    let prefix_len = hex.len() - digits.len();
+    if digits.is_empty() {
+        return Err(FieldError::InvalidHex {
+            position: prefix_len,
+        });
     }
+    // `from_str_radix` would also accept a leading sign
+    if let Some(position) = digits.chars().position(|c| !c.is_ascii_hexdigit()) {
+        return Err(FieldError::InvalidHex {
+            position: prefix_len + position,
+        });
+    }
+    Ok(BigInt::from_str_radix(digits, 16).expect("digits were validated"))
 }
 
+// Parses the "num/prime" form, both in hex, e.g. "0xc0/0xdf". Hex error positions are
+// relative to the whole string.
+impl FromStr for FieldElement {
+    type Err = FieldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (num_hex, prime_hex) = s.split_once('/').ok_or(FieldError::MissingPrime)?;
+        let prime = parse_hex(prime_hex).map_err(|error| match error {
+            FieldError::InvalidHex { position } => FieldError::InvalidHex {
+                position: num_hex.len() + 1 + position,
+            },
+            error => error,
+        })?;
+        FieldElement::from_hex(num_hex, prime)
+    }
+}
+
+impl From<FieldElement> for BigInt {
+    fn from(elem: FieldElement) -> BigInt {
+        elem.num
+    }
+}
+
+impl TryFrom<&FieldElement> for u64 {
+    type Error = FieldError;
+
+    fn try_from(elem: &FieldElement) -> Result<u64, FieldError> {
+        elem.num
+            .to_u64()
+            .ok_or_else(|| FieldError::IntegerOverflow {
+                num: elem.num.clone(),
+                bits: u64::BITS,
+            })
+    }
+}
+
+impl TryFrom<&FieldElement> for u128 {
+    type Error = FieldError;
+
+    fn try_from(elem: &FieldElement) -> Result<u128, FieldError> {
+        elem.num
+            .to_u128()
+            .ok_or_else(|| FieldError::IntegerOverflow {
+                num: elem.num.clone(),
+                bits: u128::BITS,
+            })
+    }
+}
+
+// Elements are ordered by field first and then by value, so sorting a mixed collection
+// groups each field together.
+impl Ord for FieldElement {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.prime
+            .cmp(&other.prime)
+            .then_with(|| self.num.cmp(&other.num))
+    }
+}
+
+impl PartialOrd for FieldElement {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Printed like in Programming Bitcoin, e.g. `FieldElement_223(192)`.
+impl fmt::Display for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FieldElement_{}({})", self.prime, self.num)
+    }
+}
+
+impl fmt::LowerHex for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{:0width$x}", self.num, width = self.hex_width())
+    }
+}
+
+impl fmt::UpperHex for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{:0width$X}", self.num, width = self.hex_width())
+    }
+}
+
+// The operators panic when the operands belong to different fields (or on division by zero);
+// the `try_*` methods return the error instead.
 impl Add<FieldElement> for FieldElement {
     type Output = Self;
 
     fn add(self, elem: FieldElement) -> FieldElement {
-        assert!(
-            self.prime == elem.prime,
-            "Cannot add two numbers in different fields"
-        );
-        let num = (self.num + elem.num).mod_floor(&self.prime);
-        FieldElement::new(num, self.prime)
+        self.try_add(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
     }
 }
 
@@ -55,12 +500,8 @@ impl Sub<FieldElement> for FieldElement {
     type Output = Self;
 
     fn sub(self, elem: FieldElement) -> FieldElement {
-        assert!(
-            self.prime == elem.prime,
-            "Cannot subtract two numbers in different fields"
-        );
-        let num = (self.num - elem.num).mod_floor(&self.prime);
-        FieldElement::new(num, self.prime)
+        self.try_sub(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
     }
 }
 
@@ -68,12 +509,8 @@ impl Mul<FieldElement> for FieldElement {
     type Output = Self;
 
     fn mul(self, elem: FieldElement) -> FieldElement {
-        assert!(
-            self.prime == elem.prime,
-            "Cannot multiply two numbers in different fields"
-        );
-        let num = (self.num * elem.num).mod_floor(&self.prime);
-        FieldElement::new(num, self.prime)
+        self.try_mul(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
     }
 }
 
@@ -81,19 +518,191 @@ impl Div<FieldElement> for FieldElement {
     type Output = Self;
 
     fn div(self, elem: FieldElement) -> FieldElement {
-        assert!(
-            self.prime == elem.prime,
-            "Cannot divide two numbers in different fields"
-        );
-        let factor = elem.num.modpow(&(&self.prime - 2_i32), &self.prime);
-        let num = (self.num * factor) % &self.prime;
+        self.try_div(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+// Multiplication by integer scalars, which are reduced modulo the prime, so that constants
+// such as the 2 and 3 of the doubling formula don't need to be field elements.
+impl Mul<&BigInt> for FieldElement {
+    type Output = FieldElement;
+
+    fn mul(self, scalar: &BigInt) -> FieldElement {
+        let num = (self.num * scalar).mod_floor(&self.prime);
         FieldElement::new(num, self.prime)
     }
 }
 
+impl Mul<u64> for FieldElement {
+    type Output = FieldElement;
+
+    fn mul(self, scalar: u64) -> FieldElement {
+        self * &BigInt::from(scalar)
+    }
+}
+
+impl Mul<i64> for FieldElement {
+    type Output = FieldElement;
+
+    fn mul(self, scalar: i64) -> FieldElement {
+        self * &BigInt::from(scalar)
+    }
+}
+
+impl Mul<FieldElement> for u64 {
+    type Output = FieldElement;
+
+    fn mul(self, elem: FieldElement) -> FieldElement {
+        elem * self
+    }
+}
+
+impl Mul<FieldElement> for i64 {
+    type Output = FieldElement;
+
+    fn mul(self, elem: FieldElement) -> FieldElement {
+        elem * self
+    }
+}
+
+// The compound assignment operators update `num` in place.
+impl AddAssign<&FieldElement> for FieldElement {
+    fn add_assign(&mut self, elem: &FieldElement) {
+        self.check_same_field(elem)
+            .unwrap_or_else(|error| panic!("{error}"));
+        self.num = (&self.num + &elem.num).mod_floor(self.prime());
+    }
+}
+
+impl AddAssign<FieldElement> for FieldElement {
+    fn add_assign(&mut self, elem: FieldElement) {
+        *self += &elem;
+    }
+}
+
+impl SubAssign<&FieldElement> for FieldElement {
+    fn sub_assign(&mut self, elem: &FieldElement) {
+        self.check_same_field(elem)
+            .unwrap_or_else(|error| panic!("{error}"));
+        self.num = (&self.num - &elem.num).mod_floor(self.prime());
+    }
+}
+
+impl SubAssign<FieldElement> for FieldElement {
+    fn sub_assign(&mut self, elem: FieldElement) {
+        *self -= &elem;
+    }
+}
+
+impl MulAssign<&FieldElement> for FieldElement {
+    fn mul_assign(&mut self, elem: &FieldElement) {
+        self.check_same_field(elem)
+            .unwrap_or_else(|error| panic!("{error}"));
+        self.num = self.reduce(&self.num * &elem.num);
+    }
+}
+
+impl MulAssign<FieldElement> for FieldElement {
+    fn mul_assign(&mut self, elem: FieldElement) {
+        *self *= &elem;
+    }
+}
+
+impl DivAssign<&FieldElement> for FieldElement {
+    fn div_assign(&mut self, elem: &FieldElement) {
+        let quotient = self.try_div(elem).unwrap_or_else(|error| panic!("{error}"));
+        self.num = quotient.num;
+    }
+}
+
+impl DivAssign<FieldElement> for FieldElement {
+    fn div_assign(&mut self, elem: FieldElement) {
+        *self /= &elem;
+    }
+}
+
+// The field is taken from the first element, so summing or multiplying an empty iterator
+// panics: there is no prime to build the identity in.
+impl Sum for FieldElement {
+    fn sum<I: Iterator<Item = FieldElement>>(mut iter: I) -> FieldElement {
+        let mut total = iter
+            .next()
+            .expect("Cannot sum an empty iterator of field elements");
+        for elem in iter {
+            total += elem;
+        }
+        total
+    }
+}
+
+impl<'a> Sum<&'a FieldElement> for FieldElement {
+    fn sum<I: Iterator<Item = &'a FieldElement>>(iter: I) -> FieldElement {
+        iter.cloned().sum()
+    }
+}
+
+impl Product for FieldElement {
+    fn product<I: Iterator<Item = FieldElement>>(mut iter: I) -> FieldElement {
+        let mut total = iter
+            .next()
+            .expect("Cannot multiply an empty iterator of field elements");
+        for elem in iter {
+            total *= elem;
+        }
+        total
+    }
+}
+
+impl<'a> Product<&'a FieldElement> for FieldElement {
+    fn product<I: Iterator<Item = &'a FieldElement>>(iter: I) -> FieldElement {
+        iter.cloned().product()
+    }
+}
+
+impl Neg for &FieldElement {
+    type Output = FieldElement;
+
+    fn neg(self) -> FieldElement {
+        let num = (self.prime() - &self.num).mod_floor(self.prime());
+        FieldElement::new(num, self.prime.clone())
+    }
+}
+
+impl Neg for FieldElement {
+    type Output = FieldElement;
+
+    fn neg(self) -> FieldElement {
+        -&self
+    }
+}
+
+impl traits::Field for FieldElement {
+    fn zero_like(&self) -> Self {
+        FieldElement::zero(self.prime.clone())
+    }
+
+    fn one_like(&self) -> Self {
+        FieldElement::one(self.prime.clone())
+    }
+
+    fn is_zero(&self) -> bool {
+        FieldElement::is_zero(self)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        FieldElement::inverse(self).ok()
+    }
+
+    fn pow(&self, exp: u64) -> Self {
+        FieldElement::pow(self, exp)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::elliptic_curve::test_scalars;
     use num_bigint::ToBigInt;
 
     #[test]
@@ -104,7 +713,7 @@ mod test {
         let field_element = FieldElement::new(num, prime);
 
         assert_eq!(field_element.num.clone(), 4.to_bigint().unwrap());
-        assert_eq!(field_element.prime, 7.to_bigint().unwrap());
+        assert_eq!(*field_element.prime, 7.to_bigint().unwrap());
     }
 
     #[test]
@@ -136,7 +745,7 @@ mod test {
         let result = field_element1 + field_element2;
 
         assert_eq!(result.num, 6.to_bigint().unwrap());
-        assert_eq!(result.prime, 13.to_bigint().unwrap());
+        assert_eq!(*result.prime, 13.to_bigint().unwrap());
     }
 
     #[test]
@@ -146,7 +755,7 @@ mod test {
         let result = field_element1 - field_element2;
 
         assert_eq!(result.num, 8.to_bigint().unwrap());
-        assert_eq!(result.prime, 13.to_bigint().unwrap());
+        assert_eq!(*result.prime, 13.to_bigint().unwrap());
     }
 
     #[test]
@@ -156,21 +765,21 @@ mod test {
         let result = field_element1 * field_element2;
 
         assert_eq!(result.num, 10.to_bigint().unwrap());
-        assert_eq!(result.prime, 13.to_bigint().unwrap());
+        assert_eq!(*result.prime, 13.to_bigint().unwrap());
     }
 
     #[test]
     fn pow_field_elements() {
         let field_element1 = FieldElement::new(17.to_bigint().unwrap(), 31.to_bigint().unwrap());
         let exp = 3.to_bigint().unwrap();
-        let result = field_element1.pow(&exp);
+        let result = field_element1.pow(exp);
 
         assert_eq!(result.num, 15.to_bigint().unwrap());
-        assert_eq!(result.prime, 31.to_bigint().unwrap());
+        assert_eq!(*result.prime, 31.to_bigint().unwrap());
 
         let field_element2 = FieldElement::new(7.to_bigint().unwrap(), 13.to_bigint().unwrap());
         let exp = -3.to_bigint().unwrap();
-        let result = field_element2.pow(&exp);
+        let result = field_element2.pow(exp);
 
         assert_eq!(result.num, 8.to_bigint().unwrap());
     }
@@ -185,4 +794,700 @@ mod test {
         assert_eq!(result.num, 4.to_bigint().unwrap());
         assert_eq!(result.prime, felt1_prime);
     }
+
+    #[test]
+    fn neg_field_elements() {
+        let prime = 13.to_bigint().unwrap();
+        let zero = FieldElement::zero(prime.clone());
+        let two = FieldElement::new(2.to_bigint().unwrap(), prime.clone());
+        let twelve = FieldElement::new(12.to_bigint().unwrap(), prime.clone());
+
+        assert_eq!(-zero.clone(), zero);
+        assert_eq!(
+            -&two,
+            FieldElement::new(11.to_bigint().unwrap(), prime.clone())
+        );
+        // The value next to the prime negates to one
+        assert_eq!(-&twelve, FieldElement::new(1.to_bigint().unwrap(), prime));
+        assert_eq!(-(-twelve.clone()), twelve);
+        assert_eq!(two.clone() + -two, zero);
+    }
+
+    #[test]
+    fn assign_field_elements() {
+        let prime = 31.to_bigint().unwrap();
+        let element = |num: i32| FieldElement::new(num.to_bigint().unwrap(), prime.clone());
+
+        // Horner evaluation of 3x^2 + 2x + 5 at x = 7
+        let x = element(7);
+        let mut acc = element(3);
+        acc *= &x;
+        acc += element(2);
+        acc *= &x;
+        acc += &element(5);
+        assert_eq!(acc, element((3 * 49 + 2 * 7 + 5) % 31));
+        assert_eq!(*acc.prime, prime);
+
+        acc -= element(20);
+        assert_eq!(acc, element((166 - 20) % 31));
+        acc /= &element(24);
+        assert_eq!(acc * element(24), element((166 - 20) % 31));
+
+        let mut sum = FieldElement::zero(prime.clone());
+        for num in 1..=30 {
+            sum += element(num);
+        }
+        assert_eq!(sum, element(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot operate on elements of different fields (31 and 13)")]
+    fn add_assign_different_fields() {
+        let mut element = FieldElement::new(3.to_bigint().unwrap(), 31.to_bigint().unwrap());
+        element += FieldElement::new(3.to_bigint().unwrap(), 13.to_bigint().unwrap());
+    }
+
+    #[test]
+    fn sqrt_field_elements() {
+        let prime = 223.to_bigint().unwrap();
+        let element = |num: i32| FieldElement::new(num.to_bigint().unwrap(), prime.clone());
+
+        for num in 0..223 {
+            let square = element(num).pow(2);
+            let root = square.sqrt().unwrap();
+            assert!(root == element(num) || root == -element(num));
+        }
+        // 3, 5 and 6 are not squares modulo 223
+        for num in [3, 5, 6] {
+            assert_eq!(element(num).sqrt(), None);
+        }
+    }
+
+    #[test]
+    fn sqrt_secp256k1_field_elements() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_PRIME;
+        use num_traits::Num;
+
+        let element = |hex: &str| {
+            FieldElement::new(
+                BigInt::from_str_radix(hex, 16).unwrap(),
+                SECP256K1_PRIME.clone(),
+            )
+        };
+        let gx = element("79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798");
+        let gy = element("483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8");
+
+        // Recover the generator's y coordinate from y² = x³ + 7
+        let y_squared = gx.pow(3) + element("7");
+        let root = y_squared.sqrt().unwrap();
+        assert!(root == gy || root == -gy);
+
+        // -1 is never a square when p ≡ 3 mod 4
+        assert_eq!((-element("1")).sqrt(), None);
+    }
+
+    #[test]
+    fn sqrt_primes_congruent_to_one_mod_four() {
+        let element = |num: i32, prime: i32| {
+            FieldElement::new(num.to_bigint().unwrap(), prime.to_bigint().unwrap())
+        };
+
+        let root = element(4, 13).sqrt().unwrap();
+        assert!(root == element(2, 13) || root == element(11, 13));
+        // 2 is not a square modulo 13
+        assert_eq!(element(2, 13).sqrt(), None);
+        assert_eq!(element(0, 17).sqrt(), Some(element(0, 17)));
+        assert_eq!(element(1, 2).sqrt(), Some(element(1, 2)));
+        assert_eq!(element(0, 2).sqrt(), Some(element(0, 2)));
+    }
+
+    #[test]
+    fn sqrt_general_matches_brute_force() {
+        // 13, 17 and 41 are 1 mod 4 (17 and 41 with larger powers of two in p - 1)
+        for prime in [2, 7, 13, 17, 31, 41, 223] {
+            for num in 0..prime {
+                let element =
+                    FieldElement::new(num.to_bigint().unwrap(), prime.to_bigint().unwrap());
+                let squares_to_num: Vec<i32> =
+                    (0..prime).filter(|y| (y * y) % prime == num).collect();
+
+                match element.sqrt_general() {
+                    Some((root, negated)) => {
+                        let mut roots = vec![root.num.clone(), negated.num.clone()];
+                        roots.sort();
+                        roots.dedup();
+                        let expected: Vec<BigInt> = squares_to_num
+                            .iter()
+                            .map(|y| y.to_bigint().unwrap())
+                            .collect();
+                        assert_eq!(roots, expected, "sqrt({num}) mod {prime}");
+                    }
+                    None => assert!(squares_to_num.is_empty(), "sqrt({num}) mod {prime}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn legendre_matches_table_of_squares() {
+        let prime = 223;
+        let squares: Vec<i32> = (1..prime).map(|y| (y * y) % prime).collect();
+
+        for num in 0..prime {
+            let element = FieldElement::new(num.to_bigint().unwrap(), prime.to_bigint().unwrap());
+            let expected = if num == 0 {
+                0
+            } else if squares.contains(&num) {
+                1
+            } else {
+                -1
+            };
+
+            assert_eq!(element.legendre(), expected, "legendre({num})");
+            assert_eq!(element.is_quadratic_residue(), expected >= 0);
+        }
+    }
+
+    #[test]
+    fn inverse_field_elements() {
+        let prime = 31.to_bigint().unwrap();
+        for num in 1..31 {
+            let element = FieldElement::new(num.to_bigint().unwrap(), prime.clone());
+            let inverse = element.inverse().unwrap();
+
+            assert_eq!(inverse, element.fermat_inverse());
+            assert_eq!(
+                element * inverse,
+                FieldElement::new(1.to_bigint().unwrap(), prime.clone())
+            );
+        }
+        assert_eq!(
+            FieldElement::zero(prime.clone()).inverse(),
+            Err(FieldError::DivisionByZero)
+        );
+        // Unreduced multiples of the prime are zero as well
+        assert_eq!(
+            FieldElement::new(62.to_bigint().unwrap(), prime).inverse(),
+            Err(FieldError::DivisionByZero)
+        );
+    }
+
+    fn secp256k1_elements() -> Vec<FieldElement> {
+        use crate::elliptic_curve::secp256k1::SECP256K1_PRIME;
+
+        (1..200_u64)
+            .map(|i| {
+                let num = (BigInt::from(i) << 200) + BigInt::from(i * 7919);
+                FieldElement::new(num, SECP256K1_PRIME.clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn inverse_matches_fermat_on_secp256k1() {
+        for element in secp256k1_elements() {
+            assert_eq!(element.inverse().unwrap(), element.fermat_inverse());
+        }
+    }
+
+    // Wall-clock timing depends on the machine and build profile, so it only runs on demand:
+    // cargo test --release -- --ignored inverse_is_faster
+    #[test]
+    #[ignore]
+    fn inverse_is_faster_than_fermat_on_secp256k1() {
+        use std::time::Instant;
+
+        let elements = secp256k1_elements();
+        let start = Instant::now();
+        let euclid: Vec<FieldElement> = elements.iter().map(|e| e.inverse().unwrap()).collect();
+        let euclid_time = start.elapsed();
+
+        let start = Instant::now();
+        let fermat: Vec<FieldElement> = elements.iter().map(|e| e.fermat_inverse()).collect();
+        let fermat_time = start.elapsed();
+
+        assert_eq!(euclid, fermat);
+        assert!(
+            euclid_time < fermat_time,
+            "extended Euclid took {euclid_time:?}, Fermat took {fermat_time:?}"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot divide by zero")]
+    fn div_by_zero_field_element() {
+        let prime = 31.to_bigint().unwrap();
+        let _ =
+            FieldElement::new(3.to_bigint().unwrap(), prime.clone()) / FieldElement::zero(prime);
+    }
+
+    #[test]
+    fn try_new_field_elements() {
+        let prime = 31.to_bigint().unwrap();
+
+        assert_eq!(
+            FieldElement::try_new(30.to_bigint().unwrap(), prime.clone()),
+            Ok(FieldElement::new(30.to_bigint().unwrap(), prime.clone()))
+        );
+        assert_eq!(
+            FieldElement::try_new((-1).to_bigint().unwrap(), prime.clone()),
+            Err(FieldError::OutOfRange {
+                num: (-1).to_bigint().unwrap(),
+                prime: prime.clone()
+            })
+        );
+        assert_eq!(
+            FieldElement::try_new(prime.clone() + 5, prime.clone()),
+            Err(FieldError::OutOfRange {
+                num: prime.clone() + 5,
+                prime: prime.clone()
+            })
+        );
+        assert_eq!(
+            FieldElement::try_new(0.to_bigint().unwrap(), 1.to_bigint().unwrap()),
+            Err(FieldError::InvalidPrime {
+                prime: 1.to_bigint().unwrap()
+            })
+        );
+    }
+
+    #[test]
+    fn new_reduced_field_elements() {
+        let prime = 31.to_bigint().unwrap();
+
+        assert_eq!(
+            FieldElement::new_reduced((-1).to_bigint().unwrap(), prime.clone()),
+            FieldElement::new(30.to_bigint().unwrap(), prime.clone())
+        );
+        assert_eq!(
+            FieldElement::new_reduced(prime.clone() + 5, prime.clone()),
+            FieldElement::new(5.to_bigint().unwrap(), prime)
+        );
+    }
+
+    #[test]
+    fn try_operations_on_different_fields() {
+        let a = FieldElement::new(3.to_bigint().unwrap(), 31.to_bigint().unwrap());
+        let b = FieldElement::new(3.to_bigint().unwrap(), 13.to_bigint().unwrap());
+        let error = FieldError::DifferentFields {
+            left: 31.to_bigint().unwrap(),
+            right: 13.to_bigint().unwrap(),
+        };
+
+        assert_eq!(a.try_add(&b), Err(error.clone()));
+        assert_eq!(a.try_sub(&b), Err(error.clone()));
+        assert_eq!(a.try_mul(&b), Err(error.clone()));
+        assert_eq!(a.try_div(&b), Err(error));
+        assert_eq!(
+            a.try_div(&FieldElement::zero(31.to_bigint().unwrap())),
+            Err(FieldError::DivisionByZero)
+        );
+        assert_eq!(
+            a.try_div(&a),
+            Ok(FieldElement::new(
+                1.to_bigint().unwrap(),
+                31.to_bigint().unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot operate on elements of different fields (31 and 13)")]
+    fn mul_different_fields() {
+        let _ = FieldElement::new(3.to_bigint().unwrap(), 31.to_bigint().unwrap())
+            * FieldElement::new(3.to_bigint().unwrap(), 13.to_bigint().unwrap());
+    }
+
+    #[test]
+    fn field_element_from_hex() {
+        let prime = 223.to_bigint().unwrap();
+        let expected = FieldElement::new(0xc0.to_bigint().unwrap(), prime.clone());
+
+        assert_eq!(
+            FieldElement::from_hex("c0", prime.clone()),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            FieldElement::from_hex("C0", prime.clone()),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            FieldElement::from_hex("0xc0", prime.clone()),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            FieldElement::from_hex("0X00C0", prime.clone()),
+            Ok(expected)
+        );
+        assert_eq!(
+            FieldElement::from_hex("0xdf", prime.clone()),
+            Err(FieldError::OutOfRange {
+                num: 223.to_bigint().unwrap(),
+                prime: prime.clone()
+            })
+        );
+        assert_eq!(
+            FieldElement::from_hex("0xcg", prime.clone()),
+            Err(FieldError::InvalidHex { position: 3 })
+        );
+        assert_eq!(
+            FieldElement::from_hex("+c0", prime.clone()),
+            Err(FieldError::InvalidHex { position: 0 })
+        );
+        assert_eq!(
+            FieldElement::from_hex("0x", prime.clone()),
+            Err(FieldError::InvalidHex { position: 2 })
+        );
+    }
+
+    #[test]
+    fn field_element_from_str() {
+        assert_eq!(
+            "0xc0/0xdf".parse(),
+            Ok(FieldElement::new(
+                192.to_bigint().unwrap(),
+                223.to_bigint().unwrap()
+            ))
+        );
+        assert_eq!("c0".parse::<FieldElement>(), Err(FieldError::MissingPrime));
+        assert_eq!(
+            "0xc0/0xdz".parse::<FieldElement>(),
+            Err(FieldError::InvalidHex { position: 8 })
+        );
+        assert_eq!(
+            "0xc0/0x1".parse::<FieldElement>(),
+            Err(FieldError::InvalidPrime {
+                prime: 1.to_bigint().unwrap()
+            })
+        );
+    }
+
+    #[test]
+    fn format_field_elements() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_PRIME;
+
+        let element = FieldElement::new(9.to_bigint().unwrap(), 223.to_bigint().unwrap());
+        assert_eq!(element.to_string(), "FieldElement_223(9)");
+        assert_eq!(format!("{element:x}"), "09");
+        assert_eq!(format!("{element:#X}"), "0x09");
+
+        // Generator x coordinate of secp256k1 with its leading zero dropped
+        let x = FieldElement::from_hex(
+            "0x9BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+            SECP256K1_PRIME.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            format!("{x:x}"),
+            "09be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+        assert_eq!(
+            format!("{x:X}"),
+            "09BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798"
+        );
+    }
+
+    #[test]
+    fn identity_field_elements() {
+        let prime = 31.to_bigint().unwrap();
+        let one = FieldElement::one(prime.clone());
+        let zero = FieldElement::zero(prime.clone());
+        let x = FieldElement::new(17.to_bigint().unwrap(), prime.clone());
+
+        assert!(one.is_one() && !one.is_zero());
+        assert!(zero.is_zero() && !zero.is_one());
+        assert_eq!(x.clone() * one.clone(), x);
+        assert_eq!(x.clone() + zero, x);
+        assert!((x.clone() / x).is_one());
+        assert_eq!(*one.prime, prime);
+    }
+
+    #[test]
+    fn sum_and_product_field_elements() {
+        let prime = 223.to_bigint().unwrap();
+        let elements: Vec<FieldElement> = (1..=100)
+            .map(|num| FieldElement::new((num * 7).to_bigint().unwrap(), prime.clone()))
+            .collect();
+
+        let manual_sum = elements
+            .iter()
+            .fold(FieldElement::zero(prime.clone()), |acc, e| acc + e.clone());
+        let manual_product = elements
+            .iter()
+            .fold(FieldElement::one(prime.clone()), |acc, e| acc * e.clone());
+
+        assert_eq!(elements.iter().sum::<FieldElement>(), manual_sum);
+        assert_eq!(
+            elements.clone().into_iter().sum::<FieldElement>(),
+            manual_sum
+        );
+        assert_eq!(elements.iter().product::<FieldElement>(), manual_product);
+        assert_eq!(
+            elements.into_iter().product::<FieldElement>(),
+            manual_product
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot sum an empty iterator of field elements")]
+    fn sum_empty_iterator() {
+        let _: FieldElement = Vec::<FieldElement>::new().into_iter().sum();
+    }
+
+    #[test]
+    fn hash_field_elements() {
+        use std::collections::HashSet;
+
+        let element = |num: i32, prime: i32| {
+            FieldElement::new(num.to_bigint().unwrap(), prime.to_bigint().unwrap())
+        };
+        let set: HashSet<FieldElement> =
+            [element(3, 7), element(3, 7), element(3, 11), element(4, 7)]
+                .into_iter()
+                .collect();
+
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&element(3, 11)));
+    }
+
+    #[test]
+    fn sort_field_elements() {
+        let element = |num: i32, prime: i32| {
+            FieldElement::new(num.to_bigint().unwrap(), prime.to_bigint().unwrap())
+        };
+        let mut elements = vec![
+            element(5, 11),
+            element(6, 7),
+            element(0, 11),
+            element(2, 7),
+            element(10, 11),
+        ];
+        elements.sort();
+
+        assert_eq!(
+            elements,
+            vec![
+                element(2, 7),
+                element(6, 7),
+                element(0, 11),
+                element(5, 11),
+                element(10, 11),
+            ]
+        );
+        assert!(element(6, 7) < element(0, 11));
+    }
+
+    #[test]
+    fn mul_field_elements_by_integers() {
+        let prime = 13.to_bigint().unwrap();
+        let element = |num: i32| FieldElement::new(num.to_bigint().unwrap(), prime.clone());
+
+        assert_eq!(element(5) * 3u64, element(2));
+        assert_eq!(3u64 * element(5), element(2));
+        assert_eq!(element(5) * -3i64, element(11));
+        assert_eq!(-3i64 * element(5), element(11));
+        // Scalars larger than the prime are reduced first
+        assert_eq!(element(5) * 16u64, element(2));
+        assert_eq!(element(5) * &(prime.clone() * 1000 + 3), element(2));
+        assert_eq!(element(5) * &(-prime.clone() * 1000 - 3), element(11));
+    }
+
+    #[test]
+    fn pow_field_elements_edge_cases() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_PRIME;
+
+        let prime = 31.to_bigint().unwrap();
+        let x = FieldElement::new(17.to_bigint().unwrap(), prime.clone());
+        let zero = FieldElement::zero(prime.clone());
+
+        assert_eq!(x.pow(-1), FieldElement::one(prime.clone()) / x.clone());
+        assert_eq!(x.pow(-5), x.pow(5).inverse().unwrap());
+        assert!(x.pow(0).is_one());
+        assert!(zero.pow(0).is_one());
+        assert!(zero.pow(30).is_zero());
+        assert_eq!(zero.try_pow(-1), Err(FieldError::DivisionByZero));
+
+        let huge_exponent = BigInt::from(3).pow(200_u32);
+        let x = FieldElement::new(BigInt::from(0x1234567_u64), SECP256K1_PRIME.clone());
+        assert_eq!(
+            x.pow(huge_exponent.clone()).num,
+            x.num.modpow(&huge_exponent, &SECP256K1_PRIME)
+        );
+    }
+
+    #[test]
+    fn ct_eq_agrees_with_eq() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_PRIME;
+
+        // Small values make equal pairs common enough to exercise both outcomes
+        let prime = BigInt::from(223);
+        for pair in test_scalars(0x9e3779b97f4a7c15, 4000).chunks(2) {
+            let a = FieldElement::new(&pair[0] % 8, prime.clone());
+            let b = FieldElement::new(&pair[1] % 8, prime.clone());
+            assert_eq!(a.ct_eq(&b), a == b);
+        }
+        for pair in test_scalars(0x3c6ef372fe94f82b, 400).chunks(2) {
+            let a = FieldElement::new_reduced(pair[0].clone(), SECP256K1_PRIME.clone());
+            let b = FieldElement::new_reduced(&pair[0] + &pair[1] % 2, SECP256K1_PRIME.clone());
+            assert_eq!(a.ct_eq(&b), a == b);
+        }
+    }
+
+    #[test]
+    fn ct_eq_different_primes_and_widths() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_PRIME;
+
+        // Same number, different primes of the same width
+        let a = FieldElement::new(BigInt::from(5), BigInt::from(223));
+        let b = FieldElement::new(BigInt::from(5), BigInt::from(227));
+        assert!(!a.ct_eq(&b));
+
+        // Same number, primes of different widths
+        let c = FieldElement::new(BigInt::from(5), SECP256K1_PRIME.clone());
+        assert!(!a.ct_eq(&c));
+        assert!(!c.ct_eq(&a));
+
+        let d = FieldElement::new(BigInt::from(5), SECP256K1_PRIME.clone());
+        assert!(c.ct_eq(&d));
+        assert!(FieldElement::zero(BigInt::from(7)).ct_eq(&FieldElement::zero(BigInt::from(7))));
+    }
+
+    #[test]
+    fn ct_eq_reduces_negative_and_unreduced_inputs() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_PRIME;
+
+        let element = |num: i64| FieldElement::new(BigInt::from(num), BigInt::from(7));
+        assert!(element(-1).ct_eq(&element(6)));
+        assert!(element(13).ct_eq(&element(6)));
+        assert!(element(-8).ct_eq(&element(13)));
+        assert!(!element(-1).ct_eq(&element(5)));
+
+        // Wider than the prime before reduction
+        let prime = SECP256K1_PRIME.clone();
+        let huge = FieldElement::new((prime.clone() << 300) + 3, prime.clone());
+        let three = FieldElement::new(BigInt::from(3), prime.clone());
+        assert!(huge.ct_eq(&three));
+        assert!(three.ct_eq(&huge));
+        assert!(!huge.ct_eq(&FieldElement::new(BigInt::from(-3), prime)));
+    }
+
+    #[test]
+    fn negative_intermediate_values_are_reduced() {
+        let prime = BigInt::from(13);
+        let element = |num: i64| FieldElement::new(BigInt::from(num), prime.clone());
+
+        // 7 - 12 = -5 before reduction
+        assert_eq!(element(7) - element(12), element(8));
+        assert_eq!(element(0) - element(1), element(12));
+        assert_eq!(element(12) + -element(12), element(0));
+        assert_eq!(-element(3) * element(5), element(11));
+        assert_eq!(element(2).pow(-1), element(7));
+        assert_eq!(element(2).pow(-13), element(7));
+        // -3 / 2 = -3 * 7 = -21, which `%` would leave negative
+        let mut quotient = element(-3);
+        quotient /= element(2);
+        assert_eq!(quotient, element(5));
+
+        assert_eq!(
+            FieldElement::try_new(BigInt::from(0), BigInt::from(1)),
+            Err(FieldError::InvalidPrime {
+                prime: BigInt::from(1)
+            })
+        );
+    }
+
+    #[test]
+    fn field_elements_into_integers() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_PRIME;
+
+        // A private handle, so tests running in parallel don't change its count
+        let prime = Arc::new(SECP256K1_PRIME.clone());
+        let small = FieldElement::new(BigInt::from(u64::MAX), prime.clone());
+        let medium = FieldElement::new(BigInt::from(u64::MAX) + 1, prime.clone());
+        let large = FieldElement::new(BigInt::from(1) << 128, prime.clone());
+
+        assert_eq!(u64::try_from(&small), Ok(u64::MAX));
+        assert_eq!(
+            u64::try_from(&medium),
+            Err(FieldError::IntegerOverflow {
+                num: BigInt::from(u64::MAX) + 1,
+                bits: 64
+            })
+        );
+        assert_eq!(u128::try_from(&medium), Ok(u64::MAX as u128 + 1));
+        assert_eq!(
+            u128::try_from(&large),
+            Err(FieldError::IntegerOverflow {
+                num: BigInt::from(1) << 128,
+                bits: 128
+            })
+        );
+        assert_eq!(BigInt::from(large.clone()), BigInt::from(1) << 128);
+
+        // The accessors borrow the element, so no new handle to the prime is created
+        let handles = Arc::strong_count(&prime);
+        assert_eq!(*large.num(), BigInt::from(1) << 128);
+        let _ = u64::try_from(&small);
+        assert_eq!(Arc::strong_count(&prime), handles);
+    }
+
+    #[test]
+    fn field_trait_for_field_elements() {
+        use crate::elliptic_curve::traits::traits_tests::field_identities;
+
+        field_identities(FieldElement::new(BigInt::from(47), BigInt::from(223)));
+    }
+
+    #[test]
+    fn pseudo_mersenne_primes_are_detected() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_PRIME;
+
+        assert_eq!(
+            pseudo_mersenne_constant(&SECP256K1_PRIME),
+            Some((1 << 32) + 977)
+        );
+        assert_eq!(pseudo_mersenne_constant(&BigInt::from(223)), None);
+        assert_eq!(pseudo_mersenne_constant(&(BigInt::from(1) << 256)), None);
+        // The secp256k1 group order has a 129-bit c
+        let order = crate::elliptic_curve::secp256k1::SECP256K1_ORDER.clone();
+        assert_eq!(pseudo_mersenne_constant(&order), None);
+    }
+
+    #[test]
+    fn reduce_pseudo_mersenne_matches_mod_floor() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_PRIME;
+
+        let prime_minus_one = SECP256K1_PRIME.clone() - 1;
+        let mut inputs = vec![
+            BigInt::from(0),
+            SECP256K1_PRIME.clone(),
+            &prime_minus_one * &prime_minus_one,
+            BigInt::from(1) << 256,
+            (BigInt::from(1) << 512) - 1,
+            BigInt::from(1) << 600,
+            BigInt::from(-5),
+        ];
+        let factors: Vec<BigInt> = test_scalars(0x2545f4914f6cdd1d, 10_000)
+            .into_iter()
+            .map(|scalar| scalar.mod_floor(&SECP256K1_PRIME))
+            .collect();
+        for pair in factors.chunks(2) {
+            inputs.push(&pair[0] * &pair[1]);
+        }
+
+        let c = pseudo_mersenne_constant(&SECP256K1_PRIME).unwrap();
+        for num in inputs {
+            assert_eq!(
+                reduce_pseudo_mersenne(num.clone(), &SECP256K1_PRIME, c),
+                num.mod_floor(&SECP256K1_PRIME),
+                "{num}"
+            );
+        }
+
+        // The largest 256-bit prime, 2^256 - 189, takes the same path
+        let prime: BigInt = (BigInt::from(1) << 256) - 189;
+        let element = FieldElement::new(prime.clone() - 2, prime.clone());
+        assert_eq!(pseudo_mersenne_constant(&prime), Some(189));
+        assert_eq!((element.clone() * element).num, BigInt::from(4));
+    }
 }
@@ -0,0 +1,392 @@
+// This module implements the quadratic extension 𝔽_p² = 𝔽_p[i] / (i² - β).
+//
+// β is a quadratic non-residue modulo p, so i² = β has no solution in 𝔽_p and every element
+// of the extension is written uniquely as a + b·i with a, b in 𝔽_p:
+//
+//     (a + b·i) + (c + d·i) = (a + c) + (b + d)·i
+//     (a + b·i) * (c + d·i) = (ac + β·bd) + (ad + bc)·i
+//
+// The conjugate of a + b·i is a - b·i, and their product is the norm a² - β·b², which lies in
+// 𝔽_p and is zero only for zero. Dividing the conjugate by the norm gives the inverse.
+
+use num_bigint::BigInt;
+use num_traits::Signed;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::elliptic_curve::finite_field::FieldElement;
+use crate::elliptic_curve::traits;
+use crate::types::errors::FieldError;
+
+/// An element a + b·i of the quadratic extension of a prime field, where i² = `non_residue`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Fp2Element {
+    pub(crate) a: FieldElement,
+    pub(crate) b: FieldElement,
+    pub(crate) non_residue: FieldElement,
+}
+
+impl Fp2Element {
+    // Fails unless a, b and the non-residue share a prime and the non-residue really is one.
+    pub fn new(
+        a: FieldElement,
+        b: FieldElement,
+        non_residue: FieldElement,
+    ) -> Result<Self, FieldError> {
+        a.check_same_field(&b)?;
+        a.check_same_field(&non_residue)?;
+        if non_residue.legendre() != -1 {
+            return Err(FieldError::NotANonResidue {
+                num: non_residue.num().clone(),
+                prime: non_residue.prime().clone(),
+            });
+        }
+        Ok(Fp2Element { a, b, non_residue })
+    }
+
+    // Builds an element of the same extension as `self`.
+    fn with_parts(&self, a: FieldElement, b: FieldElement) -> Fp2Element {
+        Fp2Element {
+            a,
+            b,
+            non_residue: self.non_residue.clone(),
+        }
+    }
+
+    pub fn zero(non_residue: &FieldElement) -> Result<Self, FieldError> {
+        let zero = FieldElement::zero(non_residue.shared_prime().clone());
+        Fp2Element::new(zero.clone(), zero, non_residue.clone())
+    }
+
+    pub fn one(non_residue: &FieldElement) -> Result<Self, FieldError> {
+        let prime = non_residue.shared_prime();
+        Fp2Element::new(
+            FieldElement::one(prime.clone()),
+            FieldElement::zero(prime.clone()),
+            non_residue.clone(),
+        )
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.a.is_zero() && self.b.is_zero()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.a.is_one() && self.b.is_zero()
+    }
+
+    pub fn real(&self) -> &FieldElement {
+        &self.a
+    }
+
+    pub fn imaginary(&self) -> &FieldElement {
+        &self.b
+    }
+
+    pub fn non_residue(&self) -> &FieldElement {
+        &self.non_residue
+    }
+
+    fn check_same_field(&self, elem: &Fp2Element) -> Result<(), FieldError> {
+        self.a.check_same_field(&elem.a)?;
+        if self.non_residue != elem.non_residue {
+            return Err(FieldError::DifferentNonResidues {
+                left: self.non_residue.num().clone(),
+                right: elem.non_residue.num().clone(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn conjugate(&self) -> Fp2Element {
+        self.with_parts(self.a.clone(), -&self.b)
+    }
+
+    // a² - β·b², the product of the element and its conjugate.
+    pub fn norm(&self) -> FieldElement {
+        self.a.pow(2) - self.non_residue.clone() * self.b.pow(2)
+    }
+
+    pub fn try_add(&self, elem: &Fp2Element) -> Result<Fp2Element, FieldError> {
+        self.check_same_field(elem)?;
+        Ok(self.with_parts(
+            self.a.clone() + elem.a.clone(),
+            self.b.clone() + elem.b.clone(),
+        ))
+    }
+
+    pub fn try_sub(&self, elem: &Fp2Element) -> Result<Fp2Element, FieldError> {
+        self.check_same_field(elem)?;
+        Ok(self.with_parts(
+            self.a.clone() - elem.a.clone(),
+            self.b.clone() - elem.b.clone(),
+        ))
+    }
+
+    pub fn try_mul(&self, elem: &Fp2Element) -> Result<Fp2Element, FieldError> {
+        self.check_same_field(elem)?;
+        let (a, b) = (&self.a, &self.b);
+        let (c, d) = (&elem.a, &elem.b);
+        let real = a.clone() * c.clone() + self.non_residue.clone() * b.clone() * d.clone();
+        let imaginary = a.clone() * d.clone() + b.clone() * c.clone();
+        Ok(self.with_parts(real, imaginary))
+    }
+
+    pub fn try_div(&self, elem: &Fp2Element) -> Result<Fp2Element, FieldError> {
+        self.check_same_field(elem)?;
+        self.try_mul(&elem.inverse()?)
+    }
+
+    pub fn inverse(&self) -> Result<Fp2Element, FieldError> {
+        let norm_inverse = self.norm().inverse()?;
+        let conjugate = self.conjugate();
+        Ok(self.with_parts(
+            conjugate.a * norm_inverse.clone(),
+            conjugate.b * norm_inverse,
+        ))
+    }
+
+    // A negative exponent raises the inverse, like `FieldElement::pow`.
+    pub fn pow<E: Into<BigInt>>(&self, exp: E) -> Fp2Element {
+        self.try_pow(exp).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_pow<E: Into<BigInt>>(&self, exp: E) -> Result<Fp2Element, FieldError> {
+        let exp = exp.into();
+        let base = if exp.is_negative() {
+            self.inverse()?
+        } else {
+            self.clone()
+        };
+        let exp = exp.magnitude();
+
+        let prime = self.a.shared_prime();
+        let mut result = self.with_parts(
+            FieldElement::one(prime.clone()),
+            FieldElement::zero(prime.clone()),
+        );
+        for i in (0..exp.bits()).rev() {
+            result = result.try_mul(&result)?;
+            if exp.bit(i) {
+                result = result.try_mul(&base)?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+// Printed like `FieldElement`, e.g. `Fp2Element_223(3 + 5i)`.
+impl fmt::Display for Fp2Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Fp2Element_{}({} + {}i)",
+            self.a.prime(),
+            self.a.num(),
+            self.b.num()
+        )
+    }
+}
+
+// The operators panic when the operands belong to different extensions (or on division by
+// zero); the `try_*` methods return the error instead.
+impl Add<Fp2Element> for Fp2Element {
+    type Output = Self;
+
+    fn add(self, elem: Fp2Element) -> Fp2Element {
+        self.try_add(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+impl Sub<Fp2Element> for Fp2Element {
+    type Output = Self;
+
+    fn sub(self, elem: Fp2Element) -> Fp2Element {
+        self.try_sub(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+impl Mul<Fp2Element> for Fp2Element {
+    type Output = Self;
+
+    fn mul(self, elem: Fp2Element) -> Fp2Element {
+        self.try_mul(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+impl Div<Fp2Element> for Fp2Element {
+    type Output = Self;
+
+    fn div(self, elem: Fp2Element) -> Fp2Element {
+        self.try_div(&elem)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+impl Neg for &Fp2Element {
+    type Output = Fp2Element;
+
+    fn neg(self) -> Fp2Element {
+        self.with_parts(-&self.a, -&self.b)
+    }
+}
+
+impl Neg for Fp2Element {
+    type Output = Fp2Element;
+
+    fn neg(self) -> Fp2Element {
+        -&self
+    }
+}
+
+impl traits::Field for Fp2Element {
+    fn zero_like(&self) -> Self {
+        let zero = FieldElement::zero(self.a.shared_prime().clone());
+        self.with_parts(zero.clone(), zero)
+    }
+
+    fn one_like(&self) -> Self {
+        let prime = self.a.shared_prime();
+        self.with_parts(
+            FieldElement::one(prime.clone()),
+            FieldElement::zero(prime.clone()),
+        )
+    }
+
+    fn is_zero(&self) -> bool {
+        Fp2Element::is_zero(self)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        Fp2Element::inverse(self).ok()
+    }
+
+    fn pow(&self, exp: u64) -> Self {
+        Fp2Element::pow(self, exp)
+    }
+}
+
+#[cfg(test)]
+mod fp2_tests {
+    use super::*;
+
+    // 223 ≡ 3 mod 4, so -1 is a non-residue and 𝔽_223² looks like the complex numbers.
+    const PRIME: i64 = 223;
+
+    fn fe(num: i64) -> FieldElement {
+        FieldElement::new_reduced(BigInt::from(num), BigInt::from(PRIME))
+    }
+
+    fn fp2(a: i64, b: i64) -> Fp2Element {
+        Fp2Element::new(fe(a), fe(b), fe(-1)).unwrap()
+    }
+
+    #[test]
+    fn test_non_residue_is_checked() {
+        assert!(Fp2Element::new(fe(1), fe(2), fe(-1)).is_ok());
+        // 4 = 2² is a residue
+        assert_eq!(
+            Fp2Element::new(fe(1), fe(2), fe(4)),
+            Err(FieldError::NotANonResidue {
+                num: BigInt::from(4),
+                prime: BigInt::from(PRIME)
+            })
+        );
+        assert!(matches!(
+            Fp2Element::new(fe(0), fe(0), fe(0)),
+            Err(FieldError::NotANonResidue { .. })
+        ));
+        let other_field = FieldElement::new(BigInt::from(1), BigInt::from(227));
+        assert!(matches!(
+            Fp2Element::new(fe(1), other_field, fe(-1)),
+            Err(FieldError::DifferentFields { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fp2_arithmetic() {
+        // Same as over the complex numbers while nothing wraps around
+        assert_eq!(fp2(1, 2) + fp2(3, 4), fp2(4, 6));
+        assert_eq!(fp2(1, 2) - fp2(3, 4), fp2(-2, -2));
+        assert_eq!(fp2(1, 2) * fp2(3, 4), fp2(-5, 10));
+        assert_eq!(fp2(0, 1) * fp2(0, 1), fp2(-1, 0));
+        assert_eq!(-fp2(1, 2), fp2(-1, -2));
+        assert_eq!(fp2(1, 2).pow(2), fp2(-3, 4));
+        assert_eq!(fp2(0, 1).pow(4), fp2(1, 0));
+        assert_eq!(format!("{}", fp2(3, 5)), "Fp2Element_223(3 + 5i)");
+    }
+
+    #[test]
+    fn test_conjugate_and_norm() {
+        for (a, b) in [(0, 1), (3, 5), (100, 222), (17, 0)] {
+            let x = fp2(a, b);
+            // (a + b·i)(a - b·i) = a² - β·b²
+            let expected = fe(a).pow(2) - fe(-1) * fe(b).pow(2);
+            assert_eq!(x.norm(), expected);
+            let expected = Fp2Element::new(expected, fe(0), fe(-1)).unwrap();
+            assert_eq!(x.clone() * x.conjugate(), expected);
+        }
+
+        // With β = 3 (a non-residue mod 7), the norm is a² - 3b²
+        let seven = |num: i64| FieldElement::new_reduced(BigInt::from(num), BigInt::from(7));
+        let x = Fp2Element::new(seven(2), seven(5), seven(3)).unwrap();
+        assert_eq!(x.norm(), seven(4 - 3 * 25));
+        assert_eq!(x.conjugate().conjugate(), x);
+    }
+
+    #[test]
+    fn test_inverse_round_trips() {
+        for a in 0..PRIME {
+            for b in [0, 1, 7, 222] {
+                let x = fp2(a, b);
+                if x.is_zero() {
+                    assert_eq!(x.inverse(), Err(FieldError::DivisionByZero));
+                    continue;
+                }
+                assert!((x.inverse().unwrap() * x.clone()).is_one());
+                assert_eq!(x.pow(-1), x.inverse().unwrap());
+                assert_eq!(fp2(1, 0) / x.clone() * x, fp2(1, 0));
+            }
+        }
+
+        // The multiplicative group of 𝔽_p² has order p² - 1
+        assert!(fp2(3, 5).pow(PRIME * PRIME - 1).is_one());
+    }
+
+    #[test]
+    fn test_field_trait() {
+        use crate::elliptic_curve::traits::traits_tests::field_identities;
+
+        field_identities(fp2(3, 5));
+    }
+
+    #[test]
+    fn test_different_extensions() {
+        let beta = FieldElement::new(BigInt::from(5), BigInt::from(PRIME));
+        assert_eq!(beta.legendre(), -1);
+        let x = Fp2Element::new(fe(1), fe(2), beta).unwrap();
+        assert_eq!(
+            fp2(1, 2).try_mul(&x),
+            Err(FieldError::DifferentNonResidues {
+                left: BigInt::from(PRIME - 1),
+                right: BigInt::from(5)
+            })
+        );
+        // A different base field is still reported by its primes
+        let other_prime =
+            |num: i64| FieldElement::new_reduced(BigInt::from(num), BigInt::from(227));
+        let y = Fp2Element::new(other_prime(1), other_prime(2), other_prime(-1)).unwrap();
+        assert_eq!(
+            fp2(1, 2).try_add(&y),
+            Err(FieldError::DifferentFields {
+                left: BigInt::from(PRIME),
+                right: BigInt::from(227)
+            })
+        );
+        assert!(Fp2Element::zero(&fe(-1)).unwrap().is_zero());
+        assert!(Fp2Element::one(&fe(-1)).unwrap().is_one());
+    }
+}
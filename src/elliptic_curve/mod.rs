@@ -1,5 +1,27 @@
+pub mod binary_field;
 pub mod finite_field;
+pub mod fp2;
+pub mod montgomery;
 pub mod secp256k1;
 pub mod traits;
 pub mod weierstrass_field_point;
 pub mod weierstrass_integer_point;
+
+// Random-looking but reproducible 256-bit scalars for tests, drawn from a xorshift64 stream
+// started at `seed` (which must be non-zero).
+#[cfg(test)]
+pub(crate) fn test_scalars(seed: u64, count: usize) -> Vec<num_bigint::BigInt> {
+    let mut state = seed;
+    let mut next_limb = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..count)
+        .map(|_| {
+            let bytes: Vec<u8> = (0..4).flat_map(|_| next_limb().to_be_bytes()).collect();
+            num_bigint::BigInt::from_bytes_be(num_bigint::Sign::Plus, &bytes)
+        })
+        .collect()
+}
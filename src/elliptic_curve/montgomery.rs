@@ -0,0 +1,110 @@
+// This module implements Montgomery multiplication for a fixed odd modulus n.
+//
+// Numbers are kept in "Montgomery form" x̄ = x·R mod n, where R = 2^k is the smallest power of
+// 2^64 above n. The product of two such numbers is reduced with REDC, which replaces the
+// division by n with a mask and a shift:
+//
+//     REDC(T) = (T + m·n) / R    with m = (T mod R)·n′ mod R and n′ = -n⁻¹ mod R
+//
+// so that REDC(x̄·ȳ) is the Montgomery form of x·y. Sums and differences of numbers in
+// Montgomery form work as usual modulo n, and so does multiplying them by a plain integer.
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::One;
+
+/// Precomputed constants to multiply numbers modulo an odd `modulus` in Montgomery form.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MontgomeryField {
+    modulus: BigInt,
+    r_bits: u64,
+    r_mask: BigInt,
+    r_squared: BigInt,
+    n_prime: BigInt,
+}
+
+impl MontgomeryField {
+    pub fn new(modulus: &BigInt) -> Self {
+        assert!(
+            modulus.is_odd() && *modulus > BigInt::one(),
+            "Montgomery multiplication requires an odd modulus"
+        );
+        let r_bits = modulus.bits().div_ceil(64) * 64;
+        let r = BigInt::one() << r_bits;
+        let inverse = modulus.extended_gcd(&r).x;
+
+        MontgomeryField {
+            modulus: modulus.clone(),
+            r_bits,
+            r_mask: &r - 1,
+            r_squared: (&r * &r).mod_floor(modulus),
+            n_prime: (-inverse).mod_floor(&r),
+        }
+    }
+
+    pub fn modulus(&self) -> &BigInt {
+        &self.modulus
+    }
+
+    pub fn to_mont(&self, x: &BigInt) -> BigInt {
+        self.redc(&(x.mod_floor(&self.modulus) * &self.r_squared))
+    }
+
+    pub fn from_mont(&self, x: &BigInt) -> BigInt {
+        self.redc(x)
+    }
+
+    pub fn mont_mul(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        self.redc(&(a * b))
+    }
+
+    // Requires 0 <= t < n·R, which holds for products of two reduced numbers.
+    fn redc(&self, t: &BigInt) -> BigInt {
+        let m = ((t & &self.r_mask) * &self.n_prime) & &self.r_mask;
+        let u = (t + m * &self.modulus) >> self.r_bits;
+        if u >= self.modulus {
+            u - &self.modulus
+        } else {
+            u
+        }
+    }
+}
+
+#[cfg(test)]
+mod montgomery_tests {
+    use super::*;
+    use crate::elliptic_curve::secp256k1::SECP256K1_PRIME;
+
+    #[test]
+    fn test_montgomery_round_trip_and_mul_223() {
+        let modulus = BigInt::from(223);
+        let field = MontgomeryField::new(&modulus);
+
+        for a in 0..223 {
+            let a = BigInt::from(a);
+            let a_mont = field.to_mont(&a);
+            assert_eq!(field.from_mont(&a_mont), a);
+
+            let b = BigInt::from(101);
+            let product = field.from_mont(&field.mont_mul(&a_mont, &field.to_mont(&b)));
+            assert_eq!(product, (&a * &b) % &modulus);
+        }
+    }
+
+    #[test]
+    fn test_montgomery_mul_secp256k1() {
+        let field = MontgomeryField::new(&SECP256K1_PRIME);
+        let a = SECP256K1_PRIME.clone() - 2;
+        let b = BigInt::from(0x1234567890abcdef_u64) << 190;
+
+        let product = field.from_mont(&field.mont_mul(&field.to_mont(&a), &field.to_mont(&b)));
+
+        assert_eq!(product, (&a * &b).mod_floor(&*SECP256K1_PRIME));
+    }
+
+    #[test]
+    #[should_panic(expected = "Montgomery multiplication requires an odd modulus")]
+    fn test_montgomery_rejects_even_modulus() {
+        MontgomeryField::new(&BigInt::from(224));
+    }
+}
@@ -1,29 +1,40 @@
-#![allow(dead_code)]
 use crate::elliptic_curve::{
-    finite_field::FieldElement, traits::Point, weierstrass_field_point::WeierstrassCurve,
+    finite_field::FieldElement,
+    traits::Point,
+    weierstrass_field_point::{JacobianPoint, WeierstrassCurve},
 };
-use num_bigint::BigInt;
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
 use num_traits::Num;
 use once_cell::sync::Lazy;
+use std::sync::Arc;
 
 // Bitcoin secp256k1 prime = 2**256 - 2**32 - 977
 const SECP256K1_PRIME_HEX: &str =
     "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F";
+/// The secp256k1 field prime, 2²⁵⁶ - 2³² - 977.
 // TODO: Implement BigInt as an array. This would let it be known at compile time, thus removing the need of lazy
-static SECP256K1_PRIME: Lazy<BigInt> =
+pub static SECP256K1_PRIME: Lazy<BigInt> =
     Lazy::new(|| BigInt::from_str_radix(SECP256K1_PRIME_HEX, 16).unwrap());
+/// The secp256k1 prime behind a shared handle, used by all the constants of this module so
+/// that elements derived from them share a single allocation.
+pub static SECP256K1_SHARED_PRIME: Lazy<Arc<BigInt>> =
+    Lazy::new(|| Arc::new(SECP256K1_PRIME.clone()));
 
 // SECP256K1 (Bitcoin) Curve has y² = x³ + ax + b with a = 0 and b = 7
 // y² = x³ + 7
 static SECP256K1_A: Lazy<FieldElement> =
-    Lazy::new(|| FieldElement::new(0.into(), SECP256K1_PRIME.clone()));
+    Lazy::new(|| FieldElement::from_hex("0x00", SECP256K1_SHARED_PRIME.clone()).unwrap());
 static SECP256K1_B: Lazy<FieldElement> =
-    Lazy::new(|| FieldElement::new(7.into(), SECP256K1_PRIME.clone()));
+    Lazy::new(|| FieldElement::from_hex("0x07", SECP256K1_SHARED_PRIME.clone()).unwrap());
 
-// SECP256K1 (Bitcoin) Curve
-pub static SECP256K1_CURVE: Lazy<WeierstrassCurve> = Lazy::new(|| WeierstrassCurve {
-    a: SECP256K1_A.clone(),
-    b: SECP256K1_B.clone(),
+/// The secp256k1 curve, y² = x³ + 7, behind a handle shared by every point built from it.
+pub static SECP256K1_CURVE: Lazy<Arc<WeierstrassCurve>> = Lazy::new(|| {
+    Arc::new(
+        WeierstrassCurve::new(SECP256K1_A.clone(), SECP256K1_B.clone())
+            .unwrap()
+            .with_order(SECP256K1_ORDER.clone()),
+    )
 });
 
 const SECP256K1_X_GENERATOR_HEX: &str =
@@ -34,36 +45,108 @@ const SECP256K1_ORDER_HEX: &str =
     "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141";
 
 static SECP256K1_GX: Lazy<FieldElement> = Lazy::new(|| {
-    FieldElement::new(
-        BigInt::from_str_radix(SECP256K1_X_GENERATOR_HEX, 16).unwrap(),
-        SECP256K1_PRIME.clone(),
-    )
+    FieldElement::from_hex(SECP256K1_X_GENERATOR_HEX, SECP256K1_SHARED_PRIME.clone()).unwrap()
 });
 static SECP256K1_GY: Lazy<FieldElement> = Lazy::new(|| {
-    FieldElement::new(
-        BigInt::from_str_radix(SECP256K1_Y_GENERATOR_HEX, 16).unwrap(),
-        SECP256K1_PRIME.clone(),
-    )
+    FieldElement::from_hex(SECP256K1_Y_GENERATOR_HEX, SECP256K1_SHARED_PRIME.clone()).unwrap()
 });
-static SECP256K1_ORDER: Lazy<BigInt> =
+/// The order of the secp256k1 generator.
+pub static SECP256K1_ORDER: Lazy<BigInt> =
     Lazy::new(|| BigInt::from_str_radix(SECP256K1_ORDER_HEX, 16).unwrap());
 
-// SECP256K1 (Bitcoin) Curve Generator
+/// The secp256k1 generator point G.
 pub static SECP256K1_GENERATOR: Lazy<Point<WeierstrassCurve>> = Lazy::new(|| {
-    Point::<WeierstrassCurve>::new_point(&SECP256K1_CURVE, &SECP256K1_GX, &SECP256K1_GY).unwrap()
+    Point::<WeierstrassCurve>::new_point(&*SECP256K1_CURVE, &SECP256K1_GX, &SECP256K1_GY).unwrap()
 });
 
+const TABLE_WINDOW_BITS: u64 = 4;
+
+/// Precomputed multiples of a fixed point for fast multiplication by arbitrary scalars.
+///
+/// A scalar is split into 4-bit windows, k = Σ d_j·16^j, and the table stores d·16^j·P for
+/// every window j and digit d, so k·P is the sum of one entry per non-zero window: no
+/// doublings and at most 64 additions for a 256-bit scalar.
+#[derive(Debug)]
+pub struct GeneratorTable {
+    point: Point<WeierstrassCurve>,
+    windows: Vec<Vec<JacobianPoint>>,
+}
+
+impl GeneratorTable {
+    // Covers scalars up to `bits` bits, which must include the largest reduced scalar.
+    pub fn new(point: &Point<WeierstrassCurve>, bits: u64) -> Self {
+        let curve = &point.curve;
+        let mut windows = Vec::new();
+        if let Some(mut base) = JacobianPoint::from_affine(point) {
+            for _ in 0..bits.div_ceil(TABLE_WINDOW_BITS) {
+                // base, 2 * base, ..., 15 * base
+                let mut multiples = vec![base.clone()];
+                for d in 1..(1 << TABLE_WINDOW_BITS) - 1 {
+                    let next = multiples[d - 1].add(&base, curve);
+                    multiples.push(next);
+                }
+                base = multiples[multiples.len() - 1].add(&base, curve);
+                windows.push(multiples);
+            }
+        }
+        GeneratorTable {
+            point: point.clone(),
+            windows,
+        }
+    }
+
+    // Scalars are reduced modulo the curve order when it is known. A (reduced) scalar with
+    // more bits than the table covers goes through `Point::scalar_mul` instead.
+    pub fn mul(&self, k: &BigInt) -> Point<WeierstrassCurve> {
+        let curve = &self.point.curve;
+        // A table of the point at infinity has no windows
+        if self.windows.is_empty() {
+            return Point::new_infinity(curve);
+        }
+        let k = match &curve.order {
+            Some(order) => k.mod_floor(order),
+            None => k.clone(),
+        };
+        let (sign, digits) = k.to_radix_le(1 << TABLE_WINDOW_BITS);
+        if digits.len() > self.windows.len() {
+            return self.point.scalar_mul(&k);
+        }
+
+        let mut result = JacobianPoint::infinity(curve);
+        for (multiples, digit) in self.windows.iter().zip(digits) {
+            if digit != 0 {
+                result = result.add(&multiples[digit as usize - 1], curve);
+            }
+        }
+        if sign == Sign::Minus {
+            result = result.neg();
+        }
+        result.to_affine(curve)
+    }
+}
+
+/// The table for `SECP256K1_GENERATOR`, built on first use.
+pub static SECP256K1_GENERATOR_TABLE: Lazy<GeneratorTable> =
+    Lazy::new(|| GeneratorTable::new(&SECP256K1_GENERATOR, SECP256K1_ORDER.bits()));
+
+/// k·G for the secp256k1 generator G, using `SECP256K1_GENERATOR_TABLE`.
+pub fn mul_generator(k: &BigInt) -> Point<WeierstrassCurve> {
+    SECP256K1_GENERATOR_TABLE.mul(k)
+}
+
 #[cfg(test)]
 mod elliptic_curve_tests {
     use num_bigint::ToBigInt;
 
     use super::*;
+    use crate::elliptic_curve::test_scalars;
+    use crate::elliptic_curve::traits::Coords;
 
     #[test]
     fn test_multiply_secp256k1_curve_generator_by_order_returns_infinity() {
         assert_eq!(
-            Point::<WeierstrassCurve>::new_infinity(&SECP256K1_CURVE),
-            SECP256K1_GENERATOR.clone() * SECP256K1_ORDER.clone()
+            Point::<WeierstrassCurve>::new_infinity(&*SECP256K1_CURVE),
+            &*SECP256K1_GENERATOR * &SECP256K1_ORDER
         )
     }
 
@@ -71,7 +154,64 @@ mod elliptic_curve_tests {
     fn test_multiply_secp256k1_curve_generator_by_order_plus1_returns_circles_back() {
         assert_eq!(
             SECP256K1_GENERATOR.clone(),
-            SECP256K1_GENERATOR.clone() * (SECP256K1_ORDER.clone() + 1.to_bigint().unwrap())
+            SECP256K1_GENERATOR.scalar_mul(&(SECP256K1_ORDER.clone() + 1.to_bigint().unwrap()))
         )
     }
+
+    #[test]
+    fn test_mul_generator_matches_generic_multiplication() {
+        let mut scalars = vec![
+            BigInt::from(0),
+            BigInt::from(1),
+            BigInt::from(2),
+            BigInt::from(15),
+            BigInt::from(16),
+            SECP256K1_ORDER.clone() - 1,
+            SECP256K1_ORDER.clone(),
+            SECP256K1_ORDER.clone() * 3 + 7,
+            BigInt::from(-5),
+        ];
+        scalars.extend(test_scalars(0xda3e39cb94b95bdb, 4));
+
+        for k in scalars {
+            assert_eq!(mul_generator(&k), &*SECP256K1_GENERATOR * &k, "{k} * G");
+        }
+    }
+
+    // Without an order the table has to cover the scalar itself
+    fn unordered_generator() -> Point<WeierstrassCurve> {
+        let curve = WeierstrassCurve::new(SECP256K1_A.clone(), SECP256K1_B.clone()).unwrap();
+        Point::<WeierstrassCurve>::new_point(&curve, &SECP256K1_GX, &SECP256K1_GY).unwrap()
+    }
+
+    #[test]
+    fn test_generator_table_without_order() {
+        let g = unordered_generator();
+        let table = GeneratorTable::new(&g, 16);
+
+        assert_eq!(table.mul(&BigInt::from(0xbeef)), g.clone() * 0xbeef);
+        assert_eq!(table.mul(&BigInt::from(-0xbeef)), g * -0xbeef);
+    }
+
+    #[test]
+    fn test_generator_table_falls_back_on_large_scalars() {
+        let generator = unordered_generator();
+        let table = GeneratorTable::new(&generator, 16);
+        for k in [BigInt::from(1 << 16), BigInt::from(-(1 << 20) - 3)] {
+            assert_eq!(table.mul(&k), generator.scalar_mul(&k));
+        }
+    }
+
+    #[test]
+    fn test_scalar_multiplication_shares_the_prime() {
+        let point = SECP256K1_GENERATOR.scalar_mul(&BigInt::from(12345));
+
+        match point.coords {
+            Coords::Point(x, y) => {
+                assert!(Arc::ptr_eq(x.shared_prime(), &SECP256K1_SHARED_PRIME));
+                assert!(Arc::ptr_eq(y.shared_prime(), &SECP256K1_SHARED_PRIME));
+            }
+            Coords::Infinity => panic!("12345 * G is not the point at infinity"),
+        }
+    }
 }
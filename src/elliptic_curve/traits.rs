@@ -11,9 +11,54 @@
 // Implementing this trait for a field allows the use of elliptic curve operations
 // such as point addition, doubling, and scalar multiplication.
 
-pub(crate) trait EllipticCurve
+use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub};
+use std::sync::Arc;
+
+use crate::types::errors::CurveError;
+
+/// The arithmetic an elliptic curve needs from its coordinates.
+///
+/// The operators may panic when mixing elements of different fields, as `FieldElement`'s do.
+/// Point addition only divides by non-zero elements and relies on the division being exact,
+/// `(a / b) * b == a`.
+/// Elements such as `FieldElement` carry their field with them, so the identities are built
+/// from an existing element rather than from nothing.
+pub trait Field:
+    Sized
+    + Clone
+    + PartialEq
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    /// The additive identity of the field `self` belongs to.
+    fn zero_like(&self) -> Self;
+    /// The multiplicative identity of the field `self` belongs to.
+    fn one_like(&self) -> Self;
+    fn is_zero(&self) -> bool;
+    /// `None` when `self` has no multiplicative inverse (zero, in a field).
+    fn inverse(&self) -> Option<Self>;
+
+    fn pow(&self, exp: u64) -> Self {
+        let mut result = self.one_like();
+        for i in (0..u64::BITS - exp.leading_zeros()).rev() {
+            result = result.clone() * result;
+            if exp >> i & 1 == 1 {
+                result = result * self.clone();
+            }
+        }
+        result
+    }
+}
+
+/// An elliptic curve y² = x³ + ax + b over the field `Self::Field`.
+pub trait EllipticCurve
 where
-    Self::Field: std::fmt::Debug + Clone,
+    Self::Field: Field + Clone + PartialEq,
 {
     type Field;
 
@@ -21,16 +66,313 @@ where
     fn b(&self) -> Self::Field;
 
     fn defining_equation(&self, x: &Self::Field, y: &Self::Field) -> Self::Field;
+
+    /// Whether (x, y) satisfies the curve equation.
+    fn contains(&self, x: &Self::Field, y: &Self::Field) -> bool {
+        self.defining_equation(x, y).is_zero()
+    }
+
+    /// The sum of two points through the line joining them, the tangent when `p1 == p2`.
+    /// `None` when that line is vertical. Curves can override this with faster arithmetic;
+    /// the default is `chord`.
+    fn chord(
+        &self,
+        p1: (&Self::Field, &Self::Field),
+        p2: (&Self::Field, &Self::Field),
+    ) -> Option<(Self::Field, Self::Field)> {
+        chord(self, p1, p2)
+    }
+}
+
+// slope = (3x1² + a) / 2y1 for a tangent, (y2 - y1) / (x2 - x1) otherwise, then
+// x3 = slope² - x1 - x2 and y3 = slope(x1 - x3) - y1. The division is only done once the
+// denominator is known to be non-zero.
+pub(crate) fn chord<E: EllipticCurve + ?Sized>(
+    curve: &E,
+    (x1, y1): (&E::Field, &E::Field),
+    (x2, y2): (&E::Field, &E::Field),
+) -> Option<(E::Field, E::Field)> {
+    let (numerator, denominator) = if x1 == x2 {
+        let x1_squared = x1.clone() * x1.clone();
+        (
+            x1_squared.clone() + x1_squared.clone() + x1_squared + curve.a(),
+            y1.clone() + y1.clone(),
+        )
+    } else {
+        (y2.clone() - y1.clone(), x2.clone() - x1.clone())
+    };
+    if denominator.is_zero() {
+        return None;
+    }
+    let slope = numerator / denominator;
+    let x3 = slope.clone() * slope.clone() - x1.clone() - x2.clone();
+    let y3 = slope * (x1.clone() - x3.clone()) - y1.clone();
+    Some((x3, y3))
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct Point<'a, E: EllipticCurve> {
+/// A point on the curve `E`, either affine coordinates or the point at infinity.
+///
+/// The point keeps a shared handle to its curve, so it can outlive the value it was built
+/// from and be returned or sent to other threads freely.
+#[derive(Debug)]
+pub struct Point<E: EllipticCurve> {
     pub(crate) coords: Coords<E>,
-    pub(crate) curve: &'a E,
+    pub(crate) curve: Arc<E>,
+}
+
+/// What a point can be built from: a shared `Arc` handle to the curve, or a plain reference
+/// that gets cloned into a new handle.
+pub trait CurveHandle<E> {
+    fn into_shared(self) -> Arc<E>;
+}
+
+impl<E: Clone> CurveHandle<E> for &E {
+    fn into_shared(self) -> Arc<E> {
+        Arc::new(self.clone())
+    }
 }
 
-#[derive(Debug, Clone)]
+impl<E> CurveHandle<E> for Arc<E> {
+    fn into_shared(self) -> Arc<E> {
+        self
+    }
+}
+
+impl<E> CurveHandle<E> for &Arc<E> {
+    fn into_shared(self) -> Arc<E> {
+        Arc::clone(self)
+    }
+}
+
+#[derive(Debug)]
 pub(crate) enum Coords<E: EllipticCurve> {
     Point(E::Field, E::Field),
     Infinity,
 }
+
+// Written out rather than derived: the derive would require `E: Clone`, but only the
+// coordinates are cloned, and the curve handle is shared.
+impl<E: EllipticCurve> Clone for Coords<E> {
+    fn clone(&self) -> Self {
+        match self {
+            Coords::Point(x, y) => Coords::Point(x.clone(), y.clone()),
+            Coords::Infinity => Coords::Infinity,
+        }
+    }
+}
+
+impl<E: EllipticCurve> Clone for Point<E> {
+    fn clone(&self) -> Self {
+        Point {
+            coords: self.coords.clone(),
+            curve: Arc::clone(&self.curve),
+        }
+    }
+}
+
+impl<E: EllipticCurve> Point<E> {
+    /// The curve the point is on.
+    pub fn curve(&self) -> &Arc<E> {
+        &self.curve
+    }
+
+    /// The x coordinate, or `None` for the point at infinity.
+    pub fn x(&self) -> Option<&E::Field> {
+        match &self.coords {
+            Coords::Point(x, _) => Some(x),
+            Coords::Infinity => None,
+        }
+    }
+
+    /// The y coordinate, or `None` for the point at infinity.
+    pub fn y(&self) -> Option<&E::Field> {
+        match &self.coords {
+            Coords::Point(_, y) => Some(y),
+            Coords::Infinity => None,
+        }
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        matches!(self.coords, Coords::Infinity)
+    }
+
+    /// Checks again that the coordinates are on the curve, for points that were not built
+    /// through a validating constructor.
+    pub fn validate(&self) -> Result<(), CurveError> {
+        match &self.coords {
+            Coords::Point(x, y) if !self.curve.contains(x, y) => Err(CurveError::InvalidPoint),
+            _ => Ok(()),
+        }
+    }
+}
+
+// The group law, shared by every curve type. Points on different curves can't be added.
+impl<E: EllipticCurve + PartialEq> Point<E> {
+    pub fn checked_add(&self, other: &Self) -> Result<Self, CurveError> {
+        if self.curve != other.curve {
+            return Err(CurveError::DifferentCurves);
+        }
+        Ok(self.add_point(other))
+    }
+
+    /// P + P, through the tangent at P. Points with y = 0 have a vertical tangent and double
+    /// to the point at infinity.
+    pub fn double(&self) -> Self {
+        match &self.coords {
+            Coords::Point(x, y) if !y.is_zero() => self.through_chord((x, y), (x, y)),
+            _ => self.infinity(),
+        }
+    }
+
+    // Both points must be on the same curve, which `checked_add` makes sure of.
+    pub(crate) fn add_point(&self, other: &Self) -> Self {
+        let (Coords::Point(x1, y1), Coords::Point(x2, y2)) = (&self.coords, &other.coords) else {
+            // The point at infinity is the identity
+            return match self.coords {
+                Coords::Infinity => other.clone(),
+                Coords::Point(..) => self.clone(),
+            };
+        };
+        if x1 == x2 {
+            // P + (-P) is on a vertical line
+            return if y1 == y2 {
+                self.double()
+            } else {
+                self.infinity()
+            };
+        }
+        self.through_chord((x1, y1), (x2, y2))
+    }
+
+    fn through_chord(&self, p1: (&E::Field, &E::Field), p2: (&E::Field, &E::Field)) -> Self {
+        match self.curve.chord(p1, p2) {
+            Some((x3, y3)) => Point {
+                coords: Coords::Point(x3, y3),
+                curve: Arc::clone(&self.curve),
+            },
+            None => self.infinity(),
+        }
+    }
+
+    fn infinity(&self) -> Self {
+        Point {
+            coords: Coords::Infinity,
+            curve: Arc::clone(&self.curve),
+        }
+    }
+}
+
+impl<E: EllipticCurve + PartialEq> Add for Point<E> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.checked_add(&other)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+impl<E: EllipticCurve + PartialEq> AddAssign<&Self> for Point<E> {
+    fn add_assign(&mut self, other: &Self) {
+        *self = self
+            .checked_add(other)
+            .unwrap_or_else(|error| panic!("{error}"));
+    }
+}
+
+impl<E: EllipticCurve + PartialEq> AddAssign for Point<E> {
+    fn add_assign(&mut self, other: Self) {
+        *self += &other;
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod traits_tests {
+    use super::*;
+    use num_bigint::BigInt;
+    use num_traits::{One, Zero};
+
+    // The integers are not a field: division truncates and only ±1 have an inverse, so point
+    // sums are only right when the slope is an integer. Enough for test curves whose points are
+    // all integral.
+    impl Field for BigInt {
+        fn zero_like(&self) -> Self {
+            BigInt::zero()
+        }
+
+        fn one_like(&self) -> Self {
+            BigInt::one()
+        }
+
+        fn is_zero(&self) -> bool {
+            Zero::is_zero(self)
+        }
+
+        fn inverse(&self) -> Option<Self> {
+            (self.magnitude().is_one()).then(|| self.clone())
+        }
+    }
+
+    // Only uses the `Field` contract, like a generic curve implementation would.
+    pub(crate) fn field_identities<F: Field>(x: F) {
+        let zero = x.zero_like();
+        let one = x.one_like();
+        assert!(zero.is_zero());
+        assert_eq!(x.clone() + zero.clone(), x);
+        assert_eq!(x.clone() * one.clone(), x);
+        assert_eq!(x.clone() - x.clone(), zero);
+        assert_eq!(x.clone() + -x.clone(), zero);
+        assert_eq!(x.clone() / x.clone(), one);
+        assert_eq!(x.inverse().unwrap() * x.clone(), one);
+        assert_eq!(x.pow(3), x.clone() * x.clone() * x.clone());
+        assert_eq!(x.pow(0), one);
+        assert_eq!(zero.inverse(), None);
+    }
+
+    #[test]
+    fn test_field_trait_for_integers() {
+        // The integers only provide the ring operations, so only ±1 are invertible
+        let integer = BigInt::from(7);
+        assert_eq!(Field::pow(&integer, 3), BigInt::from(343));
+        assert_eq!(Field::inverse(&integer), None);
+        assert_eq!(Field::inverse(&BigInt::from(-1)), Some(BigInt::from(-1)));
+        assert_eq!(integer.zero_like(), BigInt::from(0));
+        assert_eq!(integer.one_like(), BigInt::from(1));
+    }
+
+    // Deliberately not Clone
+    #[derive(Debug)]
+    struct IntegerCurve;
+
+    impl EllipticCurve for IntegerCurve {
+        type Field = BigInt;
+
+        fn a(&self) -> BigInt {
+            BigInt::from(0)
+        }
+
+        fn b(&self) -> BigInt {
+            BigInt::from(8)
+        }
+
+        fn defining_equation(&self, x: &BigInt, y: &BigInt) -> BigInt {
+            y.pow(2) - x.pow(3) - self.b()
+        }
+    }
+
+    fn dup<E: EllipticCurve>(p: &Point<E>) -> Point<E> {
+        p.clone()
+    }
+
+    #[test]
+    fn test_clone_without_cloning_the_curve() {
+        let p = Point {
+            coords: Coords::Point(BigInt::from(1), BigInt::from(3)),
+            curve: Arc::new(IntegerCurve),
+        };
+        let copy = dup(&p);
+        assert_eq!(copy.x(), Some(&BigInt::from(1)));
+        assert_eq!(copy.y(), Some(&BigInt::from(3)));
+        assert!(Arc::ptr_eq(copy.curve(), p.curve()));
+        assert_eq!(copy.validate(), Ok(()));
+    }
+}
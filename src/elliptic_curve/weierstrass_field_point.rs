@@ -1,18 +1,87 @@
-use std::ops::{Add, Mul};
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Mul, Neg, Sub};
+use std::sync::Arc;
 
-use num_bigint::BigInt;
-use num_traits::Zero;
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_traits::{Signed, ToPrimitive, Zero};
 
 use crate::elliptic_curve::{
     finite_field::FieldElement,
-    traits::{Coords, EllipticCurve, Point},
+    montgomery::MontgomeryField,
+    traits::{self, Coords, CurveHandle, EllipticCurve, Point},
 };
-use crate::types::errors::Errors;
-
-#[derive(Debug, PartialEq, Clone)]
+use crate::types::errors::CurveError;
+
+/// A curve in short Weierstrass form over a prime field.
+///
+/// Built with `WeierstrassCurve::new`, which rejects singular curves.
+///
+/// `with_montgomery` makes point addition multiply in Montgomery form. `with_order` makes
+/// scalar multiplication first reduce the scalar modulo the order, which is only done when
+/// the order is known. Results are the same either way, so both are ignored when comparing
+/// curves.
+#[derive(Debug, Clone)]
 pub struct WeierstrassCurve {
-    pub a: FieldElement,
-    pub b: FieldElement,
+    pub(crate) a: FieldElement,
+    pub(crate) b: FieldElement,
+    pub(crate) montgomery: Option<MontgomeryField>,
+    // The number of points on the curve, or any multiple of the order of every point.
+    pub(crate) order: Option<BigInt>,
+}
+
+impl WeierstrassCurve {
+    // Fails when a and b belong to different fields, or when the discriminant 4a³ + 27b² is
+    // zero: the curve then has a cusp or a self-intersection and its points don't form a
+    // group.
+    pub fn new(a: FieldElement, b: FieldElement) -> Result<Self, CurveError> {
+        a.check_same_field(&b)?;
+        let discriminant = a.pow(3) * 4u64 + b.pow(2) * 27u64;
+        if discriminant.is_zero() {
+            return Err(CurveError::SingularCurve);
+        }
+        Ok(WeierstrassCurve {
+            a,
+            b,
+            montgomery: None,
+            order: None,
+        })
+    }
+
+    pub fn with_montgomery(self) -> Self {
+        WeierstrassCurve {
+            montgomery: Some(MontgomeryField::new(self.a.prime())),
+            ..self
+        }
+    }
+
+    pub fn order(&self) -> Option<&BigInt> {
+        self.order.as_ref()
+    }
+
+    pub fn with_order(self, order: BigInt) -> Self {
+        WeierstrassCurve {
+            order: Some(order),
+            ..self
+        }
+    }
+}
+
+impl PartialEq for WeierstrassCurve {
+    fn eq(&self, other: &Self) -> bool {
+        self.a == other.a && self.b == other.b
+    }
+}
+
+impl Eq for WeierstrassCurve {}
+
+impl Hash for WeierstrassCurve {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.a.hash(state);
+        self.b.hash(state);
+    }
 }
 
 impl EllipticCurve for WeierstrassCurve {
@@ -26,19 +95,41 @@ impl EllipticCurve for WeierstrassCurve {
         self.b.clone()
     }
     fn defining_equation(&self, x: &Self::Field, y: &Self::Field) -> Self::Field {
-        y.clone().pow(&2.into()) - x.clone().pow(&3.into()) - self.a() * x.clone() - self.b()
+        y.clone().pow(2) - x.clone().pow(3) - self.a() * x.clone() - self.b()
+    }
+
+    // In Montgomery form when the curve was built `with_montgomery`.
+    fn chord(
+        &self,
+        p1: (&FieldElement, &FieldElement),
+        p2: (&FieldElement, &FieldElement),
+    ) -> Option<(FieldElement, FieldElement)> {
+        match &self.montgomery {
+            Some(montgomery) => montgomery_chord(montgomery, self, p1, p2),
+            None => traits::chord(self, p1, p2),
+        }
+    }
+
+    // Coordinates from another field are never on the curve.
+    fn contains(&self, x: &Self::Field, y: &Self::Field) -> bool {
+        self.a.check_same_field(x).is_ok()
+            && self.a.check_same_field(y).is_ok()
+            && self.defining_equation(x, y).is_zero()
     }
 }
 
-#[allow(dead_code)]
-impl<'a> Point<'a, WeierstrassCurve> {
+impl Point<WeierstrassCurve> {
+    // `curve` is either a reference, which gets cloned, or an `Arc` shared with the point.
     pub fn new_point(
-        curve: &'a WeierstrassCurve,
+        curve: impl CurveHandle<WeierstrassCurve>,
         x: &FieldElement,
         y: &FieldElement,
-    ) -> Result<Self, Errors> {
-        if curve.defining_equation(x, y) != FieldElement::zero(x.prime()) {
-            return Err(Errors::InvalidPoint);
+    ) -> Result<Self, CurveError> {
+        let curve = curve.into_shared();
+        curve.a.check_same_field(x)?;
+        curve.a.check_same_field(y)?;
+        if !curve.contains(x, y) {
+            return Err(CurveError::InvalidPoint);
         }
 
         Ok(Self {
@@ -47,24 +138,46 @@ impl<'a> Point<'a, WeierstrassCurve> {
         })
     }
 
-    pub fn new_infinity(curve: &'a WeierstrassCurve) -> Self {
-        Self {
-            coords: Coords::Infinity,
+    // The point with x coordinate `x` whose y has the given parity (as an integer in [0, p)),
+    // as needed to decompress public keys. Fails when x³ + ax + b is not a square, or when its
+    // only root is 0 and `odd` is set.
+    pub fn from_x(
+        curve: impl CurveHandle<WeierstrassCurve>,
+        x: &FieldElement,
+        odd: bool,
+    ) -> Result<Self, CurveError> {
+        let curve = curve.into_shared();
+        curve.a.check_same_field(x)?;
+        let y_squared = x.pow(3) + curve.a.clone() * x.clone() + curve.b.clone();
+        let (root, negated) = y_squared.sqrt_general().ok_or(CurveError::InvalidPoint)?;
+        let y = if root.num().is_odd() == odd {
+            root
+        } else if negated.num().is_odd() == odd {
+            negated
+        } else {
+            return Err(CurveError::InvalidPoint);
+        };
+
+        Ok(Self {
+            coords: Coords::Point(x.clone(), y),
             curve,
-        }
+        })
     }
 
-    fn x(&self) -> FieldElement {
-        match self.coords.clone() {
-            Coords::Point(x, _) => x,
-            // TODO: Consider returning a Option<FieldElement>
-            Coords::Infinity => FieldElement::zero(self.curve.a().prime()),
+    pub fn new_infinity(curve: impl CurveHandle<WeierstrassCurve>) -> Self {
+        Self {
+            coords: Coords::Infinity,
+            curve: curve.into_shared(),
         }
     }
 }
 
-impl PartialEq for Point<'_, WeierstrassCurve> {
+// Points on different curves are never equal.
+impl PartialEq for Point<WeierstrassCurve> {
     fn eq(&self, other: &Self) -> bool {
+        if self.curve != other.curve {
+            return false;
+        }
         match (&self.coords, &other.coords) {
             (Coords::Point(x1, y1), Coords::Point(x2, y2)) => x1 == x2 && y1 == y2,
             (Coords::Infinity, Coords::Infinity) => true,
@@ -73,90 +186,467 @@ impl PartialEq for Point<'_, WeierstrassCurve> {
     }
 }
 
-// TODO: this needs to create new BigInts instances for every sum, although they are fixed
-// We could define them outside as constants and use referecnes to them
-// Similar, the prime number do not need to be cloned all the way around.
-// TODO: Implement aritmethics for &FieldElements to no need to clone all over the place
-impl Add for Point<'_, WeierstrassCurve> {
-    type Output = Self;
+impl Eq for Point<WeierstrassCurve> {}
+
+// The curve is hashed along with the coordinates, as it takes part in equality: the same
+// coordinates on two curves are two different points.
+impl Hash for Point<WeierstrassCurve> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.curve.hash(state);
+        match &self.coords {
+            Coords::Point(x, y) => {
+                state.write_u8(1);
+                x.hash(state);
+                y.hash(state);
+            }
+            Coords::Infinity => state.write_u8(0),
+        }
+    }
+}
+
+// Formats as in Programming Bitcoin, `Point(x, y)_a_b FieldElement(p)`, or `Infinity`. Numbers
+// are decimal for fields of up to 64 bits and hex above, where the coordinates are padded to
+// the size of the prime.
+impl fmt::Display for Point<WeierstrassCurve> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Coords::Point(x, y) = &self.coords else {
+            return write!(f, "Infinity");
+        };
+        let (a, b, prime) = (self.curve.a.num(), self.curve.b.num(), x.prime());
+        if prime.bits() <= DISPLAY_DECIMAL_BITS {
+            write!(
+                f,
+                "Point({}, {})_{a}_{b} FieldElement({prime})",
+                x.num(),
+                y.num()
+            )
+        } else {
+            write!(
+                f,
+                "Point({x:#x}, {y:#x})_{a:#x}_{b:#x} FieldElement({prime:#x})"
+            )
+        }
+    }
+}
+
+const DISPLAY_DECIMAL_BITS: u64 = 64;
+
+impl Point<WeierstrassCurve> {
+    pub fn double_in_place(&mut self) {
+        *self = self.double();
+    }
+
+    // k * P, with -P used for negative k. When the curve knows its order n, k is reduced to
+    // [0, n) first, since n * P is the point at infinity. Scalars above `WNAF_THRESHOLD_BITS`
+    // go through `mul_wnaf`, smaller ones through plain double-and-add.
+    pub fn scalar_mul(&self, k: &BigInt) -> Self {
+        self.mul_unreduced(&self.reduce_scalar(k))
+    }
+
+    // The smallest n <= max with n * P the point at infinity, found by adding P to itself.
+    // Only practical for small orders, such as those of the points of the book's small curves.
+    pub fn order(&self, max: u64) -> Option<BigInt> {
+        let mut multiple = self.clone();
+        for n in 1..=max {
+            if multiple.is_infinity() {
+                return Some(BigInt::from(n));
+            }
+            multiple = multiple.add_point(self);
+        }
+        None
+    }
+
+    // Whether n * P is the point at infinity and, for every prime q in `prime_factors`,
+    // (n / q) * P is not. With all the prime factors of n this means P has order exactly n;
+    // with none, only that its order divides n. The scalars are not reduced modulo the curve
+    // order, which would make the check trivial.
+    pub fn has_order(&self, n: &BigInt, prime_factors: &[BigInt]) -> bool {
+        self.mul_unreduced(n).is_infinity()
+            && prime_factors
+                .iter()
+                .all(|q| !self.mul_unreduced(&(n / q)).is_infinity())
+    }
+
+    // k * P using the width-`width` non-adjacent form of k (see `wnaf_digits`), which needs
+    // about bits(k) / (width + 1) additions instead of bits(k) / 2. Panics unless
+    // 2 <= width <= 8.
+    pub fn mul_wnaf(&self, k: &BigInt, width: u8) -> Self {
+        self.wnaf(&self.reduce_scalar(k), width)
+    }
+
+    // k * P with a Montgomery ladder, for secret scalars. Every step does one addition and
+    // one doubling whatever the bit, and the number of steps is fixed by the curve order
+    // (256 for secp256k1), or by the bit length of k when the order is unknown. The bit only
+    // picks which of the two running points is doubled.
+    //
+    // This hides the scalar from the sequence of operations, but the BigInt arithmetic
+    // underneath is not constant-time, so it is not a complete defence against timing attacks.
+    pub fn mul_ct(&self, k: &BigInt) -> Self {
+        let k = self.reduce_scalar(k);
+        let Some(point) = JacobianPoint::from_affine(self) else {
+            return self.clone();
+        };
+        let point = if k.is_negative() { point.neg() } else { point };
+        let magnitude = k.magnitude();
+        let bits = match &self.curve.order {
+            Some(order) => order.bits(),
+            None => magnitude.bits(),
+        };
+
+        // Invariant: ladder[1] - ladder[0] = P
+        let mut ladder = [JacobianPoint::infinity(&self.curve), point];
+        for i in (0..bits).rev() {
+            let bit = magnitude.bit(i) as usize;
+            ladder[1 - bit] = ladder[0].add(&ladder[1], &self.curve);
+            ladder[bit] = ladder[bit].double(&self.curve);
+        }
+        let [result, _] = ladder;
+        result.to_affine(&self.curve)
+    }
 
-    fn add(self, other: Self) -> Self::Output {
-        let curve = self.curve; // Ensure curve is accessible
-        let curve_other = other.curve;
-        // TODO: Handle this case gracefully
+    fn mul_unreduced(&self, k: &BigInt) -> Self {
+        if k.bits() > WNAF_THRESHOLD_BITS {
+            self.wnaf(k, WNAF_DEFAULT_WIDTH)
+        } else {
+            self.double_and_add(k)
+        }
+    }
+
+    fn reduce_scalar<'k>(&self, k: &'k BigInt) -> Cow<'k, BigInt> {
+        match &self.curve.order {
+            Some(order) if k.is_negative() || k >= order => Cow::Owned(k.mod_floor(order)),
+            _ => Cow::Borrowed(k),
+        }
+    }
+
+    fn wnaf(&self, k: &BigInt, width: u8) -> Self {
         assert!(
-            !(curve != curve_other),
-            "Cannot add points on different curves"
+            (2..=8).contains(&width),
+            "The wNAF window width must be between 2 and 8"
         );
+        let Some(point) = JacobianPoint::from_affine(self) else {
+            return self.clone();
+        };
+        let point = if k.is_negative() { point.neg() } else { point };
 
-        match (&self.coords, &other.coords) {
-            // If either operand is the identity (point at infinity), return the other.
-            (Coords::Infinity, _) => other,
-            (_, Coords::Infinity) => self,
-
-            (Coords::Point(x1, y1), Coords::Point(x2, y2)) => {
-                if x1 == x2 {
-                    if y1 == y2 {
-                        // ---- Doubling case (P1 == P2) ----
-                        if y1.is_zero() {
-                            // Tangent line to the curve is vertical if y = 0, which results in infinity
-                            Self::new_infinity(curve)
-                        } else {
-                            // slope = (3*x1^2 + A) / (2*y1)
-                            let numerator = FieldElement::new(3.into(), curve.a().prime())
-                                * x1.pow(&2.into())
-                                + curve.a();
-                            let denominator =
-                                FieldElement::new(2.into(), curve.a().prime()) * y1.clone();
-                            let slope = numerator / denominator;
-                            // x3 = slope^2 - 2x1
-                            // y3 = slope(x1 - x3) - y1
-                            let x3 = slope.pow(&2.into())
-                                - FieldElement::new(2.into(), curve.a().prime()) * x1.clone();
-                            let y3 = slope * (x1.clone() - x3.clone()) - y1.clone();
-                            Self::new_point(curve, &x3, &y3).unwrap()
-                        }
-                    } else {
-                        // ---- P1 = -P2 => vertical line => infinity. ----
-                        Self::new_infinity(curve)
-                    }
-                } else {
-                    // ---- Addition case (x1 != x2) ----
-                    let slope = (y2.clone() - y1.clone()) / (x2.clone() - x1.clone());
-                    let x3 = slope.pow(&2.into()) - x1.clone() - x2.clone();
-                    let y3 = slope * (x1.clone() - x3.clone()) - y1.clone();
-                    Self::new_point(curve, &x3, &y3).unwrap()
+        let odd_multiples = point.odd_multiples(width, &self.curve);
+
+        let mut result = JacobianPoint::infinity(&self.curve);
+        for digit in wnaf_digits(k.magnitude(), width).into_iter().rev() {
+            result = result.double(&self.curve);
+            result = result.add_wnaf_digit(&odd_multiples, digit, &self.curve);
+        }
+        result.to_affine(&self.curve)
+    }
+
+    // Σ k_i * P_i with Strauss' interleaving: the wNAF expansions of all the scalars are walked
+    // together, so the doublings are shared and only the additions depend on the number of
+    // terms. u * G + v * P costs about as much as a single multiplication plus half.
+    //
+    // Fails when `pairs` is empty, since there is then no curve for the result, or when the
+    // points are on different curves.
+    pub fn multi_scalar_mul(pairs: &[(&BigInt, &Self)]) -> Result<Self, CurveError> {
+        let Some((_, first)) = pairs.first() else {
+            return Err(CurveError::NoTerms);
+        };
+        let curve = &first.curve;
+        if pairs.iter().any(|(_, point)| point.curve != *curve) {
+            return Err(CurveError::DifferentCurves);
+        }
+
+        let terms: Vec<_> = pairs
+            .iter()
+            .filter_map(|(k, point)| {
+                let k = point.reduce_scalar(k);
+                let point = JacobianPoint::from_affine(point)?;
+                let point = if k.is_negative() { point.neg() } else { point };
+                let digits = wnaf_digits(k.magnitude(), WNAF_DEFAULT_WIDTH);
+                Some((digits, point.odd_multiples(WNAF_DEFAULT_WIDTH, curve)))
+            })
+            .collect();
+        let length = terms.iter().map(|(digits, _)| digits.len()).max();
+
+        let mut result = JacobianPoint::infinity(curve);
+        for i in (0..length.unwrap_or(0)).rev() {
+            result = result.double(curve);
+            for (digits, odd_multiples) in &terms {
+                if let Some(&digit) = digits.get(i) {
+                    result = result.add_wnaf_digit(odd_multiples, digit, curve);
                 }
             }
         }
+        Ok(result.to_affine(curve))
+    }
+
+    // Double-and-add over the bits of |k|, starting from -P when k is negative. Runs in
+    // Jacobian coordinates, so the only inversion is the one in `to_affine`.
+    fn double_and_add(&self, k: &BigInt) -> Self {
+        let Some(point) = JacobianPoint::from_affine(self) else {
+            return self.clone();
+        };
+        let mut result = JacobianPoint::infinity(&self.curve);
+        let mut current = if k.is_negative() { point.neg() } else { point };
+        let magnitude = k.magnitude();
+
+        for i in 0..magnitude.bits() {
+            if magnitude.bit(i) {
+                result = result.add(&current, &self.curve);
+            }
+            if i + 1 < magnitude.bits() {
+                current = current.double(&self.curve);
+            }
+        }
+        result.to_affine(&self.curve)
+    }
+}
+
+const WNAF_THRESHOLD_BITS: u64 = 64;
+const WNAF_DEFAULT_WIDTH: u8 = 5;
+
+// Recodes k as digits d_i (least significant first) with k = Σ d_i * 2^i, where every non-zero
+// digit is odd, |d_i| < 2^(width-1), and any `width` consecutive digits contain at most one
+// non-zero digit.
+fn wnaf_digits(k: &BigUint, width: u8) -> Vec<i16> {
+    let window = 1i16 << width;
+    let mut k = k.clone();
+    let mut digits = Vec::with_capacity(k.bits() as usize + 1);
+    while !k.is_zero() {
+        let digit = if k.bit(0) {
+            let low = (&k & BigUint::from((window - 1) as u16)).to_i16().unwrap();
+            let digit = if low >= window / 2 { low - window } else { low };
+            if digit > 0 {
+                k -= BigUint::from(digit as u16);
+            } else {
+                k += BigUint::from(digit.unsigned_abs());
+            }
+            digit
+        } else {
+            0
+        };
+        digits.push(digit);
+        k >>= 1;
+    }
+    digits
+}
+
+// A point in Jacobian coordinates: (X, Y, Z) stands for the affine point (X/Z², Y/Z³), and
+// Z = 0 for the point at infinity. Adding and doubling need no inversion this way; the
+// formulas are add-1998-cmo-2 and dbl-2007-bl from the Explicit-Formulas Database.
+#[derive(Debug, Clone)]
+pub(crate) struct JacobianPoint {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+}
+
+impl JacobianPoint {
+    pub(crate) fn infinity(curve: &WeierstrassCurve) -> Self {
+        let prime = curve.a.shared_prime();
+        JacobianPoint {
+            x: FieldElement::one(prime.clone()),
+            y: FieldElement::one(prime.clone()),
+            z: FieldElement::zero(prime.clone()),
+        }
+    }
+
+    // `None` for the point at infinity, which is handled by the caller.
+    pub(crate) fn from_affine(point: &Point<WeierstrassCurve>) -> Option<Self> {
+        match &point.coords {
+            Coords::Point(x, y) => Some(JacobianPoint {
+                x: x.clone(),
+                y: y.clone(),
+                z: FieldElement::one(x.shared_prime().clone()),
+            }),
+            Coords::Infinity => None,
+        }
+    }
+
+    pub(crate) fn to_affine(&self, curve: &Arc<WeierstrassCurve>) -> Point<WeierstrassCurve> {
+        let Ok(z_inverse) = self.z.inverse() else {
+            return Point::new_infinity(curve);
+        };
+        let z_inverse_squared = z_inverse.pow(2);
+        let x = self.x.clone() * z_inverse_squared.clone();
+        let y = self.y.clone() * z_inverse_squared * z_inverse;
+        Point {
+            coords: Coords::Point(x, y),
+            curve: Arc::clone(curve),
+        }
+    }
+
+    fn is_infinity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    pub(crate) fn neg(self) -> Self {
+        JacobianPoint { y: -self.y, ..self }
+    }
+
+    // P, 3P, ..., (2^(width-1) - 1)P, the multiples a wNAF digit of that width can select.
+    fn odd_multiples(self, width: u8, curve: &WeierstrassCurve) -> Vec<Self> {
+        let doubled = self.double(curve);
+        let mut odd_multiples = vec![self];
+        for i in 1..1 << (width - 2) {
+            let next = odd_multiples[i - 1].add(&doubled, curve);
+            odd_multiples.push(next);
+        }
+        odd_multiples
+    }
+
+    // self + digit * P, with `odd_multiples` from `P.odd_multiples`.
+    fn add_wnaf_digit(self, odd_multiples: &[Self], digit: i16, curve: &WeierstrassCurve) -> Self {
+        let multiple = &odd_multiples[(digit.unsigned_abs() / 2) as usize];
+        match digit.signum() {
+            1 => self.add(multiple, curve),
+            -1 => self.add(&multiple.clone().neg(), curve),
+            _ => self,
+        }
+    }
+
+    pub(crate) fn double(&self, curve: &WeierstrassCurve) -> Self {
+        if self.is_infinity() || self.y.is_zero() {
+            return JacobianPoint::infinity(curve);
+        }
+        let (x, y, z) = (&self.x, &self.y, &self.z);
+        let yy = y.pow(2);
+        // S = 4*X*Y^2, M = 3*X^2 + a*Z^4
+        let s = x.clone() * yy.clone() * 4u64;
+        let mut m = x.pow(2) * 3u64;
+        if !curve.a.is_zero() {
+            m += curve.a.clone() * z.pow(4);
+        }
+        // X3 = M^2 - 2*S, Y3 = M*(S - X3) - 8*Y^4, Z3 = 2*Y*Z
+        let x3 = m.pow(2) - s.clone() * 2u64;
+        let y3 = m * (s - x3.clone()) - yy.pow(2) * 8u64;
+        let z3 = y.clone() * z.clone() * 2u64;
+        JacobianPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    pub(crate) fn add(&self, other: &Self, curve: &WeierstrassCurve) -> Self {
+        if self.is_infinity() {
+            return other.clone();
+        }
+        if other.is_infinity() {
+            return self.clone();
+        }
+        let z1z1 = self.z.pow(2);
+        let z2z2 = other.z.pow(2);
+        // Both points brought to the same denominators: U = X*Z'^2, S = Y*Z'^3
+        let u1 = self.x.clone() * z2z2.clone();
+        let u2 = other.x.clone() * z1z1.clone();
+        let s1 = self.y.clone() * other.z.clone() * z2z2;
+        let s2 = other.y.clone() * self.z.clone() * z1z1;
+        if u1 == u2 {
+            return if s1 == s2 {
+                self.double(curve)
+            } else {
+                JacobianPoint::infinity(curve)
+            };
+        }
+
+        // X3 = r^2 - H^3 - 2*U1*H^2, Y3 = r*(U1*H^2 - X3) - S1*H^3, Z3 = Z1*Z2*H
+        let h = u2 - u1.clone();
+        let r = s2 - s1.clone();
+        let hh = h.pow(2);
+        let hhh = h.clone() * hh.clone();
+        let v = u1 * hh;
+        let x3 = r.pow(2) - hhh.clone() - v.clone() * 2u64;
+        let y3 = r * (v - x3.clone()) - s1 * hhh;
+        let z3 = self.z.clone() * other.z.clone() * h;
+        JacobianPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
     }
 }
 
-impl<T> Mul<T> for Point<'_, WeierstrassCurve>
+// Same formulas as in `traits::chord` (with p2 == p1 for doubling), computed on the Montgomery
+// forms of the coordinates. Only the inverse of the slope's denominator leaves Montgomery form.
+// Returns `None` when that denominator is zero, i.e. the line is vertical.
+fn montgomery_chord(
+    montgomery: &MontgomeryField,
+    curve: &WeierstrassCurve,
+    (x1, y1): (&FieldElement, &FieldElement),
+    (x2, y2): (&FieldElement, &FieldElement),
+) -> Option<(FieldElement, FieldElement)> {
+    let prime = montgomery.modulus();
+    let [x1, y1, x2, y2, a] = [x1, y1, x2, y2, &curve.a].map(|e| montgomery.to_mont(&e.num));
+
+    let (numerator, denominator) = if x1 == x2 {
+        (3 * montgomery.mont_mul(&x1, &x1) + a, 2 * &y1)
+    } else {
+        (&y2 - &y1, &x2 - &x1)
+    };
+    let denominator = FieldElement::new(
+        montgomery.from_mont(&denominator.mod_floor(prime)),
+        prime.clone(),
+    );
+    let inverse = montgomery.to_mont(&denominator.inverse().ok()?.num);
+    let slope = montgomery.mont_mul(&numerator.mod_floor(prime), &inverse);
+
+    let x3 = (montgomery.mont_mul(&slope, &slope) - &x1 - &x2).mod_floor(prime);
+    let y3 = (montgomery.mont_mul(&slope, &(&x1 - &x3).mod_floor(prime)) - &y1).mod_floor(prime);
+
+    let shared_prime = curve.a.shared_prime();
+    Some((
+        FieldElement::new(montgomery.from_mont(&x3), shared_prime.clone()),
+        FieldElement::new(montgomery.from_mont(&y3), shared_prime.clone()),
+    ))
+}
+
+// -(x, y) = (x, -y), the other point on the vertical line through P.
+impl Neg for Point<WeierstrassCurve> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        match self.coords {
+            Coords::Point(x, y) => Self {
+                coords: Coords::Point(x, -y),
+                curve: self.curve,
+            },
+            Coords::Infinity => self,
+        }
+    }
+}
+
+impl Sub for Point<WeierstrassCurve> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+// (-k) * P = k * (-P), so any integer works as a coefficient.
+impl<T> Mul<T> for Point<WeierstrassCurve>
 where
     T: Into<BigInt>,
 {
     type Output = Self;
 
     fn mul(self, coefficient: T) -> Self {
-        let mut coeff = coefficient.into();
-        let mut current = self.clone();
-        let mut result = Self::new_infinity(self.curve);
+        self.scalar_mul(&coefficient.into())
+    }
+}
 
-        while coeff != BigInt::zero() {
-            if coeff.clone() & BigInt::from(1) != BigInt::zero() {
-                result = result + current.clone();
-            }
-            coeff >>= 1;
-            current = current.clone() + current;
-        }
-        result
+impl Mul<&BigInt> for &Point<WeierstrassCurve> {
+    type Output = Point<WeierstrassCurve>;
+
+    fn mul(self, coefficient: &BigInt) -> Point<WeierstrassCurve> {
+        self.scalar_mul(coefficient)
     }
 }
 
 #[cfg(test)]
 mod weierstrass_field_point_tests {
     use super::*;
+    use crate::elliptic_curve::test_scalars;
+    use crate::types::errors::FieldError;
     use num_bigint::BigInt;
     use num_bigint::ToBigInt;
 
@@ -165,10 +655,7 @@ mod weierstrass_field_point_tests {
         let prime = 223.to_bigint().unwrap();
         let a = FieldElement::new(0.to_bigint().unwrap(), prime.clone());
         let b = FieldElement::new(7.to_bigint().unwrap(), prime.clone());
-        let curve = WeierstrassCurve {
-            a: a.clone(),
-            b: b.clone(),
-        };
+        let curve = WeierstrassCurve::new(a.clone(), b.clone()).unwrap();
 
         let valid_points: [(BigInt, BigInt); 3] = [
             (192.to_bigint().unwrap(), 105.to_bigint().unwrap()),
@@ -183,14 +670,47 @@ mod weierstrass_field_point_tests {
         for (x, y) in valid_points.iter() {
             let x = FieldElement::new(x.clone(), prime.clone());
             let y = FieldElement::new(y.clone(), prime.clone());
+            assert!(curve.contains(&x, &y));
             assert!(Point::new_point(&curve, &x, &y).is_ok());
         }
 
         for (x, y) in invalid_points.iter() {
             let x = FieldElement::new(x.clone(), prime.clone());
             let y = FieldElement::new(y.clone(), prime.clone());
-            assert_eq!(Point::new_point(&curve, &x, &y), Err(Errors::InvalidPoint));
+            assert!(!curve.contains(&x, &y));
+            assert_eq!(
+                Point::new_point(&curve, &x, &y),
+                Err(CurveError::InvalidPoint)
+            );
+            // Points assembled without going through `new_point` are caught by `validate`
+            let point = Point {
+                coords: Coords::Point(x, y),
+                curve: Arc::new(curve.clone()),
+            };
+            assert_eq!(point.validate(), Err(CurveError::InvalidPoint));
         }
+        assert_eq!(Point::new_infinity(&curve).validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_create_ec_field_point_from_other_field() {
+        let prime = BigInt::from(223);
+        let curve = WeierstrassCurve::new(
+            FieldElement::new(BigInt::from(0), prime.clone()),
+            FieldElement::new(BigInt::from(7), prime.clone()),
+        )
+        .unwrap();
+        let x = FieldElement::new(BigInt::from(192), prime);
+        let y = FieldElement::new(BigInt::from(105), BigInt::from(227));
+
+        assert_eq!(
+            Point::new_point(&curve, &x, &y),
+            Err(CurveError::Field(FieldError::DifferentFields {
+                left: BigInt::from(223),
+                right: BigInt::from(227)
+            }))
+        );
+        assert!(!curve.contains(&x, &y));
     }
 
     #[test]
@@ -198,10 +718,7 @@ mod weierstrass_field_point_tests {
         let prime = BigInt::from(223);
         let a = FieldElement::new(BigInt::from(0), prime.clone());
         let b = FieldElement::new(BigInt::from(7), prime.clone());
-        let curve = WeierstrassCurve {
-            a: a.clone(),
-            b: b.clone(),
-        };
+        let curve = WeierstrassCurve::new(a.clone(), b.clone()).unwrap();
 
         let x1 = FieldElement::new(BigInt::from(192), prime.clone());
         let y1 = FieldElement::new(BigInt::from(105), prime.clone());
@@ -257,10 +774,7 @@ mod weierstrass_field_point_tests {
         let prime = BigInt::from(223);
         let a = FieldElement::new(BigInt::from(0), prime.clone());
         let b = FieldElement::new(BigInt::from(7), prime.clone());
-        let curve = WeierstrassCurve {
-            a: a.clone(),
-            b: b.clone(),
-        };
+        let curve = WeierstrassCurve::new(a.clone(), b.clone()).unwrap();
 
         let x1 = FieldElement::new(BigInt::from(192), prime.clone());
         let y1 = FieldElement::new(BigInt::from(105), prime.clone());
@@ -276,13 +790,10 @@ mod weierstrass_field_point_tests {
         let prime = BigInt::from(223);
         let a = FieldElement::new(BigInt::from(5), prime.clone());
         let b = FieldElement::new(BigInt::from(7), prime.clone());
-        let curve = WeierstrassCurve {
-            a: a.clone(),
-            b: b.clone(),
-        };
+        let curve = WeierstrassCurve::new(a.clone(), b.clone()).unwrap();
 
         let one = FieldElement::new(BigInt::from(1), prime.clone());
-        let one_minus = FieldElement::new(BigInt::from(-1), prime.clone());
+        let one_minus = FieldElement::new_reduced(BigInt::from(-1), prime.clone());
 
         let p1 = Point::new_point(&curve, &one_minus, &one).unwrap();
         let p2 = Point::new_point(&curve, &one_minus, &one_minus).unwrap();
@@ -295,10 +806,7 @@ mod weierstrass_field_point_tests {
         let prime = BigInt::from(223);
         let a = FieldElement::new(BigInt::from(0), prime.clone());
         let b = FieldElement::new(BigInt::from(7), prime.clone());
-        let curve = WeierstrassCurve {
-            a: a.clone(),
-            b: b.clone(),
-        };
+        let curve = WeierstrassCurve::new(a.clone(), b.clone()).unwrap();
 
         let x1 = FieldElement::new(BigInt::from(192), prime.clone());
         let y1 = FieldElement::new(BigInt::from(105), prime.clone());
@@ -410,10 +918,7 @@ mod weierstrass_field_point_tests {
         let prime = BigInt::from(223);
         let a = FieldElement::new(BigInt::from(0), prime.clone());
         let b = FieldElement::new(BigInt::from(7), prime.clone());
-        let curve = WeierstrassCurve {
-            a: a.clone(),
-            b: b.clone(),
-        };
+        let curve = WeierstrassCurve::new(a.clone(), b.clone()).unwrap();
 
         let x = FieldElement::new(BigInt::from(15), prime.clone());
         let y = FieldElement::new(BigInt::from(86), prime.clone());
@@ -430,10 +935,7 @@ mod weierstrass_field_point_tests {
         let prime = BigInt::from(223);
         let a = FieldElement::new(BigInt::from(0), prime.clone());
         let b = FieldElement::new(BigInt::from(7), prime.clone());
-        let curve = WeierstrassCurve {
-            a: a.clone(),
-            b: b.clone(),
-        };
+        let curve = WeierstrassCurve::new(a.clone(), b.clone()).unwrap();
 
         let x = FieldElement::new(BigInt::from(47), prime.clone());
         let y = FieldElement::new(BigInt::from(71), prime.clone());
@@ -465,20 +967,614 @@ mod weierstrass_field_point_tests {
 
         for (s, &(expected_x, expected_y)) in (1..=20).zip(expected_results.iter()) {
             let result = p.clone() * s;
-            if let Coords::Point(rx, ry) = result.coords {
-                assert_eq!(rx.num, BigInt::from(expected_x));
-                assert_eq!(ry.num, BigInt::from(expected_y));
-            } else {
-                panic!("Result is not a point");
-            }
+            assert!(!result.is_infinity());
+            assert_eq!(*result.x().unwrap().num(), BigInt::from(expected_x));
+            assert_eq!(*result.y().unwrap().num(), BigInt::from(expected_y));
         }
 
         // Test for multiplying by 21, expecting the point at infinity
         let result = p.clone() * 21;
         assert_eq!(result, Point::new_infinity(&curve));
+        assert!(result.is_infinity());
+        assert_eq!(result.x(), None);
+        assert_eq!(result.y(), None);
 
         // Group "starts again"
         let result = p.clone() * 22;
         assert_eq!(result, p);
     }
+
+    #[test]
+    fn test_montgomery_curve_matches_plain_arithmetic() {
+        use crate::elliptic_curve::secp256k1::{SECP256K1_CURVE, SECP256K1_GENERATOR};
+
+        let prime = BigInt::from(223);
+        let a = FieldElement::new(BigInt::from(0), prime.clone());
+        let b = FieldElement::new(BigInt::from(7), prime.clone());
+        let plain = WeierstrassCurve::new(a.clone(), b.clone()).unwrap();
+        let montgomery = plain.clone().with_montgomery();
+        assert_eq!(plain, montgomery);
+
+        // Scalar multiplication runs in Jacobian coordinates, so the affine additions are
+        // compared directly
+        let x = FieldElement::new(BigInt::from(47), prime.clone());
+        let y = FieldElement::new(BigInt::from(71), prime);
+        let p_plain = Point::new_point(&plain, &x, &y).unwrap();
+        let p_montgomery = Point::new_point(&montgomery, &x, &y).unwrap();
+        let mut sum_plain = Point::new_infinity(&plain);
+        let mut sum_montgomery = Point::new_infinity(&montgomery);
+        for _ in 1..=21 {
+            sum_plain += &p_plain;
+            sum_montgomery += &p_montgomery;
+            assert_eq!(sum_plain, sum_montgomery);
+        }
+
+        let secp256k1 = (**SECP256K1_CURVE).clone().with_montgomery();
+        let g = &*SECP256K1_GENERATOR;
+        let g_montgomery = Point::new_point(&secp256k1, g.x().unwrap(), g.y().unwrap()).unwrap();
+        let (mut plain, mut montgomery) = (g.clone(), g_montgomery.clone());
+        for _ in 0..20 {
+            plain.double_in_place();
+            plain += g;
+            montgomery.double_in_place();
+            montgomery += &g_montgomery;
+            assert_eq!(plain, montgomery);
+        }
+    }
+
+    #[test]
+    fn test_zero_slope_denominator_gives_infinity() {
+        // Over 𝔽_2 the doubling denominator 2*y1 is zero even though y1 isn't
+        let prime = BigInt::from(2);
+        let a = FieldElement::new(BigInt::from(0), prime.clone());
+        let b = FieldElement::new(BigInt::from(1), prime.clone());
+        let zero = FieldElement::zero(prime.clone());
+        let one = FieldElement::one(prime);
+        assert_eq!(
+            (one.clone() * 2u64).try_div(&zero),
+            Err(FieldError::DivisionByZero)
+        );
+
+        let curve = WeierstrassCurve::new(a, b).unwrap();
+        let p = Point::new_point(&curve, &zero, &one).unwrap();
+        assert_eq!(p.clone() + p, Point::new_infinity(&curve));
+    }
+
+    #[test]
+    fn test_neg_and_sub_points() {
+        let prime = BigInt::from(223);
+        let fe = |num: i64| FieldElement::new(BigInt::from(num), prime.clone());
+        let curve = WeierstrassCurve::new(fe(0), fe(7)).unwrap();
+        let p = Point::new_point(&curve, &fe(192), &fe(105)).unwrap();
+        let q = Point::new_point(&curve, &fe(17), &fe(56)).unwrap();
+        let infinity = Point::new_infinity(&curve);
+
+        assert_eq!(
+            -p.clone(),
+            Point::new_point(&curve, &fe(192), &fe(118)).unwrap()
+        );
+        assert_eq!(p.clone() - p.clone(), infinity);
+        assert_eq!((p.clone() + q.clone()) - q.clone(), p);
+        assert_eq!(p.clone() - q.clone(), p.clone() + -q);
+        assert_eq!(-infinity.clone(), infinity);
+        assert_eq!(p.clone() - infinity.clone(), p);
+        assert_eq!(infinity - p.clone(), -p);
+    }
+
+    #[test]
+    fn test_add_assign_and_double_in_place() {
+        let prime = BigInt::from(223);
+        let fe = |num: i64| FieldElement::new(BigInt::from(num), prime.clone());
+        let curve = WeierstrassCurve::new(fe(0), fe(7)).unwrap();
+        let p = Point::new_point(&curve, &fe(47), &fe(71)).unwrap();
+        let q = Point::new_point(&curve, &fe(17), &fe(56)).unwrap();
+
+        let mut sum = p.clone();
+        sum += &q;
+        assert_eq!(sum, p.clone() + q.clone());
+        sum += Point::new_infinity(&curve);
+        assert_eq!(sum, p.clone() + q);
+
+        let mut doubled = p.clone();
+        doubled.double_in_place();
+        assert_eq!(doubled, p.clone() + p.clone());
+        assert_eq!(
+            doubled,
+            Point::new_point(&curve, &fe(36), &fe(111)).unwrap()
+        );
+
+        // The order of (47, 71) is 21
+        let mut point = p.clone() * 20;
+        point += &p;
+        assert_eq!(point, Point::new_infinity(&curve));
+        point.double_in_place();
+        assert_eq!(point, Point::new_infinity(&curve));
+    }
+
+    #[test]
+    fn test_borrowing_scalar_multiplication() {
+        let prime = BigInt::from(223);
+        let fe = |num: i64| FieldElement::new(BigInt::from(num), prime.clone());
+        let curve = WeierstrassCurve::new(fe(0), fe(7)).unwrap();
+        let p = Point::new_point(&curve, &fe(47), &fe(71)).unwrap();
+
+        assert_eq!(p.scalar_mul(&BigInt::from(0)), Point::new_infinity(&curve));
+        assert_eq!(p.scalar_mul(&BigInt::from(1)), p);
+        assert_eq!(&p * &BigInt::from(5), p.clone() * 5);
+        // Negative scalars multiply -P
+        assert_eq!(p.scalar_mul(&BigInt::from(-1)), -p.clone());
+        assert_eq!(&p * &BigInt::from(-5), -(p.clone() * 5));
+        assert_eq!(p.clone() * -5, p.clone() * 16);
+        assert_eq!(
+            Point::new_infinity(&curve).scalar_mul(&BigInt::from(-7)),
+            Point::new_infinity(&curve)
+        );
+    }
+
+    #[test]
+    fn test_scalar_is_reduced_modulo_the_order() {
+        use crate::elliptic_curve::secp256k1::{SECP256K1_GENERATOR, SECP256K1_ORDER};
+
+        let g = &*SECP256K1_GENERATOR;
+        let order = &*SECP256K1_ORDER;
+        assert_eq!(g * &(order * 1000 + 5), g * &BigInt::from(5));
+        assert_eq!(g * &(order + 1), g.clone());
+        assert_eq!(g * &BigInt::from(-5), -(g * &BigInt::from(5)));
+        let huge: BigInt = BigInt::from(7) << 100_000;
+        assert_eq!(g * &huge, g * &huge.mod_floor(order));
+
+        // (47, 71) generates the whole group of 21 points of y² = x³ + 7 over 𝔽_223
+        let prime = BigInt::from(223);
+        let fe = |num: i64| FieldElement::new(BigInt::from(num), prime.clone());
+        let curve = WeierstrassCurve::new(fe(0), fe(7)).unwrap();
+        let with_order = curve.clone().with_order(BigInt::from(21));
+        assert_eq!(curve, with_order);
+        let p = Point::new_point(&curve, &fe(47), &fe(71)).unwrap();
+        let q = Point::new_point(&with_order, &fe(47), &fe(71)).unwrap();
+        for k in -30..60 {
+            assert_eq!(p.clone() * k, q.clone() * k);
+        }
+    }
+
+    #[test]
+    fn test_jacobian_matches_affine_arithmetic() {
+        let prime = BigInt::from(223);
+        let fe = |num: i64| FieldElement::new(BigInt::from(num), prime.clone());
+        let curve = WeierstrassCurve::new(fe(0), fe(7)).unwrap();
+        let p = Point::new_point(&curve, &fe(170), &fe(142)).unwrap();
+
+        let mut affine = Point::new_infinity(&curve);
+        for k in 1..=100 {
+            affine += &p;
+            assert_eq!(p.clone() * k, affine, "{k} * P");
+        }
+
+        // A curve with a != 0: y² = x³ - 3x + 5 over 𝔽_223
+        let curve = WeierstrassCurve::new(fe(-3 + 223), fe(5)).unwrap();
+        let (x, y) = (1..223)
+            .flat_map(|x| (1..223).map(move |y| (x, y)))
+            .find(|&(x, y)| Point::new_point(&curve, &fe(x), &fe(y)).is_ok())
+            .unwrap();
+        let p = Point::new_point(&curve, &fe(x), &fe(y)).unwrap();
+        let mut affine = Point::new_infinity(&curve);
+        for k in 1..=100 {
+            affine += &p;
+            assert_eq!(p.clone() * k, affine, "{k} * P");
+        }
+    }
+
+    #[test]
+    fn test_wnaf_digits() {
+        for width in 2..=8 {
+            for k in 0..2000u32 {
+                let digits = wnaf_digits(&BigUint::from(k), width);
+                let value: i64 = digits
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &digit)| (digit as i64) << i)
+                    .sum();
+                assert_eq!(value, k as i64);
+                for window in digits.windows(width as usize) {
+                    assert!(window.iter().filter(|&&digit| digit != 0).count() <= 1);
+                }
+                for &digit in &digits {
+                    assert!(digit == 0 || (digit % 2 != 0 && digit.abs() < 1 << (width - 1)));
+                }
+            }
+        }
+        // 7 = 8 - 1
+        assert_eq!(wnaf_digits(&BigUint::from(7u32), 2), vec![-1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_wnaf_matches_double_and_add() {
+        use crate::elliptic_curve::secp256k1::{SECP256K1_GENERATOR, SECP256K1_ORDER};
+
+        let prime = BigInt::from(223);
+        let fe = |num: i64| FieldElement::new(BigInt::from(num), prime.clone());
+        let curve = WeierstrassCurve::new(fe(0), fe(7)).unwrap();
+        let p = Point::new_point(&curve, &fe(15), &fe(86)).unwrap();
+        for width in 2..=8 {
+            for k in -25..=25 {
+                let k = BigInt::from(k);
+                assert_eq!(p.mul_wnaf(&k, width), p.double_and_add(&k), "{k} * P");
+            }
+        }
+        assert_eq!(
+            Point::new_infinity(&curve).mul_wnaf(&BigInt::from(5), 4),
+            Point::new_infinity(&curve)
+        );
+
+        let g = &*SECP256K1_GENERATOR;
+        let mut scalars = vec![
+            BigInt::from(1),
+            SECP256K1_ORDER.clone() - 1,
+            SECP256K1_ORDER.clone(),
+        ];
+        scalars.extend(test_scalars(0x853c49e6748fea9b, 4));
+        for k in scalars {
+            let expected = g.double_and_add(&g.reduce_scalar(&k));
+            assert_eq!(g * &k, expected);
+            assert_eq!(g.mul_wnaf(&k, 4), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The wNAF window width must be between 2 and 8")]
+    fn test_wnaf_rejects_width_one() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_GENERATOR;
+
+        SECP256K1_GENERATOR.mul_wnaf(&BigInt::from(5), 1);
+    }
+
+    #[test]
+    fn test_mul_ct_matches_variable_time_multiplication() {
+        use crate::elliptic_curve::secp256k1::{SECP256K1_GENERATOR, SECP256K1_ORDER};
+
+        let prime = BigInt::from(223);
+        let fe = |num: i64| FieldElement::new(BigInt::from(num), prime.clone());
+        let curve = WeierstrassCurve::new(fe(0), fe(7)).unwrap();
+        let p = Point::new_point(&curve, &fe(47), &fe(71)).unwrap();
+        for k in -25..=25 {
+            assert_eq!(p.mul_ct(&BigInt::from(k)), p.clone() * k, "{k} * P");
+        }
+        assert_eq!(
+            Point::new_infinity(&curve).mul_ct(&BigInt::from(3)),
+            Point::new_infinity(&curve)
+        );
+
+        let g = &*SECP256K1_GENERATOR;
+        let mut scalars = vec![
+            BigInt::from(0),
+            BigInt::from(1),
+            SECP256K1_ORDER.clone() - 1,
+            SECP256K1_ORDER.clone(),
+            BigInt::from(-1),
+        ];
+        scalars.extend(test_scalars(0x6a09e667f3bcc908, 3));
+        for k in scalars {
+            assert_eq!(g.mul_ct(&k), g * &k, "{k} * G");
+        }
+    }
+
+    #[test]
+    fn test_new_curve_validation() {
+        let prime = BigInt::from(223);
+        let fe = |num: i64| FieldElement::new_reduced(BigInt::from(num), prime.clone());
+
+        assert!(WeierstrassCurve::new(fe(0), fe(7)).is_ok());
+        // y² = x³ has a cusp at the origin
+        assert_eq!(
+            WeierstrassCurve::new(fe(0), fe(0)).unwrap_err(),
+            CurveError::SingularCurve
+        );
+        // y² = x³ - 3x + 2 = (x - 1)²(x + 2) crosses itself at (1, 0)
+        assert_eq!(
+            WeierstrassCurve::new(fe(-3), fe(2)).unwrap_err(),
+            CurveError::SingularCurve
+        );
+        assert_eq!(
+            WeierstrassCurve::new(fe(0), FieldElement::new(BigInt::from(7), BigInt::from(227)))
+                .unwrap_err(),
+            CurveError::Field(FieldError::DifferentFields {
+                left: BigInt::from(223),
+                right: BigInt::from(227)
+            })
+        );
+
+        let curve = WeierstrassCurve::new(fe(0), fe(7)).unwrap();
+        assert_eq!(curve.order(), None);
+        assert_eq!(
+            curve.with_order(BigInt::from(21)).order(),
+            Some(&BigInt::from(21))
+        );
+    }
+
+    #[test]
+    fn test_points_on_different_curves() {
+        let curve_223 = WeierstrassCurve::new(
+            FieldElement::new(BigInt::from(0), BigInt::from(223)),
+            FieldElement::new(BigInt::from(7), BigInt::from(223)),
+        )
+        .unwrap();
+        let curve_227 = WeierstrassCurve::new(
+            FieldElement::new(BigInt::from(0), BigInt::from(227)),
+            FieldElement::new(BigInt::from(7), BigInt::from(227)),
+        )
+        .unwrap();
+        let p = Point::new_point(
+            &curve_223,
+            &FieldElement::new(BigInt::from(47), BigInt::from(223)),
+            &FieldElement::new(BigInt::from(71), BigInt::from(223)),
+        )
+        .unwrap();
+        let infinity_223 = Point::new_infinity(&curve_223);
+        let infinity_227 = Point::new_infinity(&curve_227);
+
+        assert_eq!(
+            p.checked_add(&infinity_227),
+            Err(CurveError::DifferentCurves)
+        );
+        assert_eq!(p.checked_add(&infinity_223), Ok(p.clone()));
+        assert_ne!(infinity_223, infinity_227);
+        assert_ne!(p, infinity_227);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot operate on points of different curves")]
+    fn test_add_points_on_different_curves_panics() {
+        let curve = |prime: i64| {
+            WeierstrassCurve::new(
+                FieldElement::new(BigInt::from(0), BigInt::from(prime)),
+                FieldElement::new(BigInt::from(7), BigInt::from(prime)),
+            )
+            .unwrap()
+        };
+        let (curve_223, curve_227) = (curve(223), curve(227));
+        let _ = Point::new_infinity(&curve_223) + Point::new_infinity(&curve_227);
+    }
+
+    // The point owns a handle to its curve, so it can leave the scope the curve was built in
+    fn make_point() -> Point<WeierstrassCurve> {
+        let curve = WeierstrassCurve::new(
+            FieldElement::new(BigInt::from(0), BigInt::from(223)),
+            FieldElement::new(BigInt::from(7), BigInt::from(223)),
+        )
+        .unwrap();
+        Point::new_point(
+            &curve,
+            &FieldElement::new(BigInt::from(47), BigInt::from(223)),
+            &FieldElement::new(BigInt::from(71), BigInt::from(223)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_points_own_their_curve() {
+        let p = make_point();
+        let doubled = std::thread::spawn(move || p.clone() + p).join().unwrap();
+        assert_eq!(
+            doubled.x(),
+            Some(&FieldElement::new(BigInt::from(36), BigInt::from(223)))
+        );
+
+        // Points built from a shared handle, and the points derived from them, reuse it
+        let curve = Arc::clone(make_point().curve());
+        let infinity = Point::new_infinity(&curve);
+        let p = Point::new_point(
+            Arc::clone(&curve),
+            &FieldElement::new(BigInt::from(47), BigInt::from(223)),
+            &FieldElement::new(BigInt::from(71), BigInt::from(223)),
+        )
+        .unwrap();
+        assert!(Arc::ptr_eq(infinity.curve(), &curve));
+        assert!(Arc::ptr_eq((p * 2).curve(), &curve));
+    }
+
+    #[test]
+    fn test_display() {
+        let p = make_point();
+        assert_eq!(p.to_string(), "Point(47, 71)_0_7 FieldElement(223)");
+        assert_eq!(
+            (p.clone() * 2).to_string(),
+            "Point(36, 111)_0_7 FieldElement(223)"
+        );
+        assert_eq!((p * 21).to_string(), "Infinity");
+
+        use crate::elliptic_curve::secp256k1::SECP256K1_GENERATOR;
+        assert_eq!(
+            SECP256K1_GENERATOR.to_string(),
+            "Point(\
+             0x79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798, \
+             0x483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8)_0x0_0x7 \
+             FieldElement(0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f)"
+        );
+    }
+
+    #[test]
+    fn test_from_x() {
+        let p = make_point();
+        let x = FieldElement::new(BigInt::from(47), BigInt::from(223));
+        // The roots are 71 and 152
+        assert_eq!(Point::from_x(p.curve(), &x, true), Ok(p.clone()));
+        assert_eq!(Point::from_x(p.curve(), &x, false), Ok(-p.clone()));
+
+        // x³ + 7 is not a square for these
+        for x in [4, 9, 12, 14, 19] {
+            let x = FieldElement::new(BigInt::from(x), BigInt::from(223));
+            assert_eq!(
+                Point::from_x(p.curve(), &x, false),
+                Err(CurveError::InvalidPoint)
+            );
+        }
+
+        use crate::elliptic_curve::secp256k1::{SECP256K1_CURVE, SECP256K1_GENERATOR};
+        let g = &*SECP256K1_GENERATOR;
+        assert_eq!(
+            Point::from_x(&*SECP256K1_CURVE, g.x().unwrap(), false).as_ref(),
+            Ok(g)
+        );
+        assert_eq!(
+            Point::from_x(&*SECP256K1_CURVE, g.x().unwrap(), true),
+            Ok(-g.clone())
+        );
+    }
+
+    #[test]
+    fn test_point_order() {
+        let point = |x: i64, y: i64| {
+            Point::new_point(
+                make_point().curve(),
+                &FieldElement::new(BigInt::from(x), BigInt::from(223)),
+                &FieldElement::new(BigInt::from(y), BigInt::from(223)),
+            )
+            .unwrap()
+        };
+        let (p, q) = (point(47, 71), point(15, 86));
+        assert_eq!(p.order(100), Some(BigInt::from(21)));
+        assert_eq!(q.order(100), Some(BigInt::from(7)));
+        assert_eq!(p.order(20), None);
+        assert_eq!(
+            Point::new_infinity(p.curve()).order(1),
+            Some(BigInt::from(1))
+        );
+
+        let [two, three, seven] = [2, 3, 7].map(BigInt::from);
+        assert!(p.has_order(&BigInt::from(21), &[three.clone(), seven.clone()]));
+        assert!(p.has_order(&BigInt::from(42), &[]));
+        assert!(!p.has_order(&BigInt::from(42), &[two, three.clone(), seven]));
+        assert!(!p.has_order(&BigInt::from(7), &[]));
+        assert!(!q.has_order(&BigInt::from(21), &[three]));
+
+        use crate::elliptic_curve::secp256k1::{SECP256K1_GENERATOR, SECP256K1_ORDER};
+        assert!(SECP256K1_GENERATOR.has_order(&SECP256K1_ORDER, &[]));
+        assert!(!SECP256K1_GENERATOR.has_order(&(SECP256K1_ORDER.clone() - 1), &[]));
+    }
+
+    #[test]
+    fn test_multi_scalar_mul_matches_separate_multiplications() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_GENERATOR;
+
+        let mut large = test_scalars(0x9e3779b97f4a7c15, 17).into_iter();
+        // 64-bit scalars for the small curve
+        let mut small = test_scalars(0xbb67ae8584caa73b, 16)
+            .into_iter()
+            .map(|scalar| scalar >> 192_u32);
+
+        let p = make_point();
+        let q = p.clone() * 5;
+        let g = SECP256K1_GENERATOR.clone();
+        let h = g.scalar_mul(&large.next().unwrap());
+        for _ in 0..8 {
+            let (u, v) = (small.next().unwrap(), -small.next().unwrap());
+            assert_eq!(
+                Point::multi_scalar_mul(&[(&u, &p), (&v, &q)]).unwrap(),
+                &p * &u + &q * &v
+            );
+            let (u, v) = (large.next().unwrap(), large.next().unwrap());
+            assert_eq!(
+                Point::multi_scalar_mul(&[(&u, &g), (&v, &h)]).unwrap(),
+                &g * &u + &h * &v
+            );
+        }
+
+        // Zero scalars and the point at infinity contribute nothing
+        let (zero, k) = (BigInt::from(0), BigInt::from(12345));
+        let infinity = Point::new_infinity(g.curve());
+        assert_eq!(
+            Point::multi_scalar_mul(&[(&zero, &g), (&k, &infinity)]).unwrap(),
+            infinity
+        );
+        assert_eq!(
+            Point::multi_scalar_mul(&[(&k, &g), (&k, &infinity), (&zero, &h)]).unwrap(),
+            &g * &k
+        );
+    }
+
+    #[test]
+    fn test_multi_scalar_mul_rejects_empty_input_and_mixed_curves() {
+        use crate::elliptic_curve::secp256k1::SECP256K1_GENERATOR;
+
+        assert!(matches!(
+            Point::<WeierstrassCurve>::multi_scalar_mul(&[]),
+            Err(CurveError::NoTerms)
+        ));
+        let k = BigInt::from(7);
+        assert!(matches!(
+            Point::multi_scalar_mul(&[(&k, &make_point()), (&k, &SECP256K1_GENERATOR)]),
+            Err(CurveError::DifferentCurves)
+        ));
+    }
+
+    #[test]
+    fn test_points_in_hash_set() {
+        use std::collections::HashSet;
+
+        let p = make_point();
+        let other_curve = WeierstrassCurve::new(
+            FieldElement::new(BigInt::from(1), BigInt::from(223)),
+            FieldElement::new(BigInt::from(7), BigInt::from(223)),
+        )
+        .unwrap();
+        let infinity = Point::new_infinity(p.curve());
+        let points = [
+            p.clone(),
+            p.clone(),
+            -p.clone(),
+            p.clone() * 22,
+            infinity.clone(),
+            p.clone() * 21,
+            Point::new_infinity(&other_curve),
+        ];
+
+        let set: HashSet<_> = points.into_iter().collect();
+        // P, -P and the two points at infinity
+        assert_eq!(set.len(), 4);
+        assert!(set.contains(&p));
+        assert!(set.contains(&infinity));
+    }
+
+    #[test]
+    fn test_double() {
+        let prime = BigInt::from(223);
+        let curve = make_point().curve().clone();
+        let montgomery_curve = Arc::new((*curve).clone().with_montgomery());
+        let element = |n: i64| FieldElement::new(BigInt::from(n), prime.clone());
+        let doublings = [
+            ((192, 105), (49, 71)),
+            ((143, 98), (64, 168)),
+            ((47, 71), (36, 111)),
+        ];
+
+        for curve in [&curve, &montgomery_curve] {
+            for ((x, y), (xr, yr)) in doublings {
+                let p = Point::new_point(curve, &element(x), &element(y)).unwrap();
+                let r = Point::new_point(curve, &element(xr), &element(yr)).unwrap();
+                assert_eq!(p.double(), r);
+                assert_eq!(p.clone() + p, r);
+            }
+
+            // The tangent is vertical where y = 0
+            let p = Point::new_point(curve, &element(6), &element(0)).unwrap();
+            assert!(p.double().is_infinity());
+            assert!(Point::new_infinity(curve).double().is_infinity());
+        }
+    }
+
+    #[test]
+    fn test_negative_scalars() {
+        let p = make_point();
+        let infinity = Point::new_infinity(p.curve());
+
+        assert_eq!(p.clone() * -1, -p.clone());
+        assert_eq!(p.clone() * -3, -(p.clone() * 3));
+        assert_eq!(p.clone() * BigInt::from(-1) + p.clone(), infinity);
+        assert_eq!(p.clone() * -21, infinity);
+        assert_eq!(p.mul_ct(&BigInt::from(-5)), -(p.clone() * 5));
+        assert_eq!(p.mul_wnaf(&BigInt::from(-5), 3), -(p.clone() * 5));
+
+        // Used to loop forever: shifting a negative BigInt right never reaches zero
+        let k = -(BigInt::from(1) << 300u32) - 7;
+        assert_eq!(&p * &k, -(&p * &(-&k)));
+    }
 }
@@ -1,8 +1,10 @@
-use crate::elliptic_curve::traits::{Coords, EllipticCurve, Point};
-use crate::types::errors::Errors;
-use core::ops::Add;
+use crate::elliptic_curve::traits::{self, Coords, CurveHandle, EllipticCurve, Point};
+use crate::types::errors::CurveError;
+use core::fmt;
+use core::ops::{Add, Div, Mul, Neg, Sub};
 use num_bigint::BigInt;
-use num_traits::Zero;
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
 
 // This module implements the `RealWeierstrassCurve` and associated `Point` operations
 // for elliptic curves defined over the real numbers using the Weierstrass form.
@@ -15,7 +17,8 @@ use num_traits::Zero;
 // point at infinity, and to perform point addition and doubling operations.
 //
 // The `RealWeierstrassCurve` struct represents the curve itself, defined by the
-// coefficients `a` and `b`.
+// coefficients `a` and `b`. Its points have rational coordinates, computed exactly with
+// `Rational`, so that sums with a fractional slope are still on the curve.
 //
 // The `Point` struct represents a point on the curve, which can be either a
 // coordinate pair (x, y) or the point at infinity. The module ensures that points
@@ -24,14 +27,14 @@ use num_traits::Zero;
 // The module also includes tests to verify the correctness of point creation and
 // arithmetic operations.
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 struct RealWeierstrassCurve {
-    a: BigInt,
-    b: BigInt,
+    a: Rational,
+    b: Rational,
 }
 
 impl EllipticCurve for RealWeierstrassCurve {
-    type Field = BigInt;
+    type Field = Rational;
 
     fn a(&self) -> Self::Field {
         self.a.clone()
@@ -42,15 +45,20 @@ impl EllipticCurve for RealWeierstrassCurve {
     }
 
     fn defining_equation(&self, x: &Self::Field, y: &Self::Field) -> Self::Field {
-        y.pow(2) - x.pow(3) - self.a() * x - self.b()
+        y.clone() * y.clone() - x.clone() * x.clone() * x.clone() - self.a() * x.clone() - self.b()
     }
 }
 
 #[allow(dead_code)]
-impl<'a> Point<'a, RealWeierstrassCurve> {
-    fn new_point(curve: &'a RealWeierstrassCurve, x: &BigInt, y: &BigInt) -> Result<Self, Errors> {
-        if curve.defining_equation(x, y) != BigInt::from(0) {
-            return Err(Errors::InvalidPoint);
+impl Point<RealWeierstrassCurve> {
+    fn new_point(
+        curve: impl CurveHandle<RealWeierstrassCurve>,
+        x: &Rational,
+        y: &Rational,
+    ) -> Result<Self, CurveError> {
+        let curve = curve.into_shared();
+        if !curve.contains(x, y) {
+            return Err(CurveError::InvalidPoint);
         }
 
         Ok(Point {
@@ -59,21 +67,20 @@ impl<'a> Point<'a, RealWeierstrassCurve> {
         })
     }
 
-    fn new_infinity(curve: &'a RealWeierstrassCurve) -> Self {
+    fn new_infinity(curve: impl CurveHandle<RealWeierstrassCurve>) -> Self {
         Point {
             coords: Coords::Infinity,
-            curve,
+            curve: curve.into_shared(),
         }
     }
 }
 
-impl PartialEq for Point<'_, RealWeierstrassCurve> {
+// Points on different curves are never equal.
+impl PartialEq for Point<RealWeierstrassCurve> {
     fn eq(&self, other: &Self) -> bool {
-        // TODO: Handle this case gracefully
-        assert!(
-            !(self.curve != other.curve),
-            "Cannot compare points on different curves"
-        );
+        if self.curve != other.curve {
+            return false;
+        }
 
         match (&self.coords, &other.coords) {
             (Coords::Point(x1, y1), Coords::Point(x2, y2)) => x1 == x2 && y1 == y2,
@@ -83,86 +90,147 @@ impl PartialEq for Point<'_, RealWeierstrassCurve> {
     }
 }
 
-impl Add for Point<'_, RealWeierstrassCurve> {
+// `Point(x, y)_a_b`, or `Infinity`.
+impl fmt::Display for Point<RealWeierstrassCurve> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.coords {
+            Coords::Point(x, y) => write!(f, "Point({x}, {y})_{}_{}", self.curve.a, self.curve.b),
+            Coords::Infinity => write!(f, "Infinity"),
+        }
+    }
+}
+
+// An exact fraction numer / denom, kept in lowest terms with a positive denominator so that
+// equal numbers have equal representations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rational {
+    numer: BigInt,
+    denom: BigInt,
+}
+
+impl Rational {
+    // Panics when `denom` is zero.
+    fn new(numer: BigInt, denom: BigInt) -> Self {
+        assert!(!denom.is_zero(), "Rational with a zero denominator");
+        let gcd = numer.gcd(&denom);
+        let sign = if denom.is_negative() { -1 } else { 1 };
+        Rational {
+            numer: sign * numer / &gcd,
+            denom: sign * denom / gcd,
+        }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(num: i64) -> Self {
+        Rational::new(BigInt::from(num), BigInt::one())
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denom.is_one() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+impl Add for Rational {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
-        let curve = self.curve; // Ensure curve is accessible
-        let curve_other = other.curve;
-        // TODO: Handle this case gracefully
-        assert!(
-            !(curve != curve_other),
-            "Cannot add points on different curves"
-        );
+        Rational::new(
+            self.numer * &other.denom + other.numer * &self.denom,
+            self.denom * other.denom,
+        )
+    }
+}
 
-        match (&self.coords, &other.coords) {
-            // If either operand is the identity (point at infinity), return the other.
-            (Coords::Infinity, _) => other,
-            (_, Coords::Infinity) => self,
-
-            // Both are actual points on the curve.
-            (Coords::Point(x1, y1), Coords::Point(x2, y2)) => {
-                if x1 == x2 {
-                    if y1 == y2 {
-                        // ---- Doubling case (P1 == P2) ----
-                        if y1.is_zero() {
-                            // Tangent line to the curve is vertical if y = 0, which results in infinity
-                            Self::new_infinity(curve)
-                        } else {
-                            // slope = (3*x1^2 + A) / (2*y1)
-                            let numerator = BigInt::from(3) * x1.pow(2_u32) + curve.a();
-                            let denominator = BigInt::from(2) * y1;
-                            let slope = numerator / denominator;
-
-                            let x3: BigInt = slope.pow(2_u32) - (BigInt::from(2) * x1);
-                            let y3: BigInt = &slope * (x1 - &x3) - y1;
-                            Self::new_point(curve, &x3, &y3).unwrap()
-                        }
-                    } else {
-                        // ---- P1 = -P2 => vertical line => infinity. ----
-                        Self::new_infinity(curve)
-                    }
-                } else {
-                    // ---- Addition case (x1 != x2) ----
-                    let slope = (y2 - y1) / (x2 - x1);
-                    let x3: BigInt = slope.pow(2_u32) - x1 - x2;
-                    let y3: BigInt = &slope * (x1 - &x3) - y1;
-                    Self::new_point(curve, &x3, &y3).unwrap()
-                }
-            }
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Rational::new(self.numer * other.numer, self.denom * other.denom)
+    }
+}
+
+// Panics when dividing by zero.
+impl Div for Rational {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        Rational::new(self.numer * other.denom, self.denom * other.numer)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Rational {
+            numer: -self.numer,
+            ..self
         }
     }
 }
 
+impl traits::Field for Rational {
+    fn zero_like(&self) -> Self {
+        Rational::from(0)
+    }
+
+    fn one_like(&self) -> Self {
+        Rational::from(1)
+    }
+
+    fn is_zero(&self) -> bool {
+        Zero::is_zero(&self.numer)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        (!Zero::is_zero(&self.numer)).then(|| Rational::new(self.denom.clone(), self.numer.clone()))
+    }
+}
+
 #[cfg(test)]
 mod elliptic_curve_tests {
     use super::*;
-    use num_bigint::ToBigInt;
+    use std::sync::Arc;
 
     #[test]
     fn test_create_valid_point() {
         let curve = RealWeierstrassCurve {
-            a: 5.to_bigint().unwrap(),
-            b: 7.to_bigint().unwrap(),
+            a: Rational::from(5),
+            b: Rational::from(7),
         };
         assert!(Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &-1.to_bigint().unwrap(),
-            &-1.to_bigint().unwrap()
+            &-Rational::from(1),
+            &-Rational::from(1)
         )
         .is_ok());
     }
 
     #[test]
     fn test_create_valid_point_and_check_result() {
-        let curve = RealWeierstrassCurve {
-            a: 5.to_bigint().unwrap(),
-            b: 7.to_bigint().unwrap(),
-        };
+        let curve = Arc::new(RealWeierstrassCurve {
+            a: Rational::from(5),
+            b: Rational::from(7),
+        });
         let result = Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &-1.to_bigint().unwrap(),
-            &-1.to_bigint().unwrap(),
+            &-Rational::from(1),
+            &-Rational::from(1),
         );
         assert!(result.is_ok());
 
@@ -170,80 +238,115 @@ mod elliptic_curve_tests {
         assert_eq!(
             point,
             Point {
-                coords: Coords::Point(-1.to_bigint().unwrap(), -1.to_bigint().unwrap()),
-                curve: &curve
+                coords: Coords::Point(-Rational::from(1), -Rational::from(1)),
+                curve: Arc::clone(&curve)
             }
         );
     }
 
     #[test]
     fn test_create_valid_point_at_infinity() {
-        let curve = RealWeierstrassCurve {
-            a: 5.to_bigint().unwrap(),
-            b: 7.to_bigint().unwrap(),
-        };
+        let curve = Arc::new(RealWeierstrassCurve {
+            a: Rational::from(5),
+            b: Rational::from(7),
+        });
         assert_eq!(
             Point::<RealWeierstrassCurve>::new_infinity(&curve),
             Point {
                 coords: Coords::Infinity,
-                curve: &curve
+                curve: Arc::clone(&curve)
             }
         );
     }
 
     #[test]
     fn test_eq() {
-        let curve = RealWeierstrassCurve {
-            a: 5.to_bigint().unwrap(),
-            b: 7.to_bigint().unwrap(),
-        };
+        let curve = Arc::new(RealWeierstrassCurve {
+            a: Rational::from(5),
+            b: Rational::from(7),
+        });
         assert!(
             Point::<RealWeierstrassCurve>::new_infinity(&curve)
                 == Point {
                     coords: Coords::Infinity,
-                    curve: &curve
+                    curve: Arc::clone(&curve)
                 }
         );
         assert!(
             Point {
-                coords: Coords::Point(-1.to_bigint().unwrap(), -1.to_bigint().unwrap()),
-                curve: &curve
+                coords: Coords::Point(-Rational::from(1), -Rational::from(1)),
+                curve: Arc::clone(&curve)
             } == Point {
-                coords: Coords::Point(-1.to_bigint().unwrap(), -1.to_bigint().unwrap()),
-                curve: &curve
+                coords: Coords::Point(-Rational::from(1), -Rational::from(1)),
+                curve: Arc::clone(&curve)
             }
         );
         assert!(
             Point {
-                coords: Coords::Point(-1.to_bigint().unwrap(), -1.to_bigint().unwrap()),
-                curve: &curve
+                coords: Coords::Point(-Rational::from(1), -Rational::from(1)),
+                curve: Arc::clone(&curve)
             } != Point {
-                coords: Coords::Point(-1.to_bigint().unwrap(), 1.to_bigint().unwrap()),
-                curve: &curve
+                coords: Coords::Point(-Rational::from(1), Rational::from(1)),
+                curve: Arc::clone(&curve)
             }
         );
         assert!(
             Point {
                 coords: Coords::Infinity,
-                curve: &curve
+                curve: Arc::clone(&curve)
             } != Point {
-                coords: Coords::Point(-1.to_bigint().unwrap(), 1.to_bigint().unwrap()),
-                curve: &curve
+                coords: Coords::Point(-Rational::from(1), Rational::from(1)),
+                curve: Arc::clone(&curve)
             }
         );
     }
 
+    #[test]
+    fn test_display() {
+        let curve = RealWeierstrassCurve {
+            a: Rational::from(5),
+            b: Rational::from(7),
+        };
+        let point = Point::<RealWeierstrassCurve>::new_point(
+            &curve,
+            &-Rational::from(1),
+            &-Rational::from(1),
+        )
+        .unwrap();
+        assert_eq!(point.to_string(), "Point(-1, -1)_5_7");
+        assert_eq!(
+            Point::<RealWeierstrassCurve>::new_infinity(&curve).to_string(),
+            "Infinity"
+        );
+    }
+
+    #[test]
+    fn test_eq_on_different_curves() {
+        let curve = RealWeierstrassCurve {
+            a: Rational::from(5),
+            b: Rational::from(7),
+        };
+        let other_curve = RealWeierstrassCurve {
+            a: Rational::from(5),
+            b: Rational::from(8),
+        };
+        assert!(
+            Point::<RealWeierstrassCurve>::new_infinity(&curve)
+                != Point::<RealWeierstrassCurve>::new_infinity(&other_curve)
+        );
+    }
+
     #[test]
     fn test_add_infinity_to_point() {
         let curve = RealWeierstrassCurve {
-            a: 5.to_bigint().unwrap(),
-            b: 7.to_bigint().unwrap(),
+            a: Rational::from(5),
+            b: Rational::from(7),
         };
         let infinity = Point::<RealWeierstrassCurve>::new_infinity(&curve);
         let point = Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &-1.to_bigint().unwrap(),
-            &-1.to_bigint().unwrap(),
+            &-Rational::from(1),
+            &-Rational::from(1),
         )
         .unwrap();
 
@@ -251,8 +354,8 @@ mod elliptic_curve_tests {
             infinity + point,
             Point::<RealWeierstrassCurve>::new_point(
                 &curve,
-                &-1.to_bigint().unwrap(),
-                &-1.to_bigint().unwrap()
+                &-Rational::from(1),
+                &-Rational::from(1)
             )
             .unwrap()
         );
@@ -261,14 +364,14 @@ mod elliptic_curve_tests {
     #[test]
     fn test_add_infinity_to_point_reverse() {
         let curve = RealWeierstrassCurve {
-            a: 5.to_bigint().unwrap(),
-            b: 7.to_bigint().unwrap(),
+            a: Rational::from(5),
+            b: Rational::from(7),
         };
         let infinity = Point::<RealWeierstrassCurve>::new_infinity(&curve);
         let point = Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &-1.to_bigint().unwrap(),
-            &-1.to_bigint().unwrap(),
+            &-Rational::from(1),
+            &-Rational::from(1),
         )
         .unwrap();
 
@@ -276,8 +379,8 @@ mod elliptic_curve_tests {
             point + infinity,
             Point::<RealWeierstrassCurve>::new_point(
                 &curve,
-                &-1.to_bigint().unwrap(),
-                &-1.to_bigint().unwrap()
+                &-Rational::from(1),
+                &-Rational::from(1)
             )
             .unwrap()
         );
@@ -287,19 +390,19 @@ mod elliptic_curve_tests {
     fn test_add_vertical_line() {
         // This happens when points have the same x and different y coordinates
         let curve = RealWeierstrassCurve {
-            a: 5.to_bigint().unwrap(),
-            b: 7.to_bigint().unwrap(),
+            a: Rational::from(5),
+            b: Rational::from(7),
         };
         let point1 = Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &-1.to_bigint().unwrap(),
-            &1.to_bigint().unwrap(),
+            &-Rational::from(1),
+            &Rational::from(1),
         )
         .unwrap();
         let point2 = Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &-1.to_bigint().unwrap(),
-            &-1.to_bigint().unwrap(),
+            &-Rational::from(1),
+            &-Rational::from(1),
         )
         .unwrap();
 
@@ -313,19 +416,19 @@ mod elliptic_curve_tests {
     fn test_add_same_point_with_vertical_slope() {
         // This happens when points are the same and have y == 0
         let curve = RealWeierstrassCurve {
-            a: 0.to_bigint().unwrap(),
-            b: 0.to_bigint().unwrap(),
+            a: Rational::from(0),
+            b: Rational::from(0),
         };
         let point1 = Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &0.to_bigint().unwrap(),
-            &0.to_bigint().unwrap(),
+            &Rational::from(0),
+            &Rational::from(0),
         )
         .unwrap();
         let point2 = Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &0.to_bigint().unwrap(),
-            &0.to_bigint().unwrap(),
+            &Rational::from(0),
+            &Rational::from(0),
         )
         .unwrap();
 
@@ -340,19 +443,19 @@ mod elliptic_curve_tests {
     fn test_add_same_point() {
         // p(-1,-1) + p(-1,-1) = p(18,77)
         let curve = RealWeierstrassCurve {
-            a: 5.to_bigint().unwrap(),
-            b: 7.to_bigint().unwrap(),
+            a: Rational::from(5),
+            b: Rational::from(7),
         };
         let point1 = Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &-1.to_bigint().unwrap(),
-            &-1.to_bigint().unwrap(),
+            &-Rational::from(1),
+            &-Rational::from(1),
         )
         .unwrap();
         let point2 = Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &-1.to_bigint().unwrap(),
-            &-1.to_bigint().unwrap(),
+            &-Rational::from(1),
+            &-Rational::from(1),
         )
         .unwrap();
 
@@ -361,8 +464,8 @@ mod elliptic_curve_tests {
             point1 + point2,
             Point::<RealWeierstrassCurve>::new_point(
                 &curve,
-                &18.to_bigint().unwrap(),
-                &77.to_bigint().unwrap()
+                &Rational::from(18),
+                &Rational::from(77)
             )
             .unwrap()
         );
@@ -372,19 +475,19 @@ mod elliptic_curve_tests {
     fn test_add_points_with_different_x() {
         // p(2,5) + p(-1,-1) = p(3,-7)
         let curve = RealWeierstrassCurve {
-            a: 5.to_bigint().unwrap(),
-            b: 7.to_bigint().unwrap(),
+            a: Rational::from(5),
+            b: Rational::from(7),
         };
         let point1 = Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &2.to_bigint().unwrap(),
-            &5.to_bigint().unwrap(),
+            &Rational::from(2),
+            &Rational::from(5),
         )
         .unwrap();
         let point2 = Point::<RealWeierstrassCurve>::new_point(
             &curve,
-            &-1.to_bigint().unwrap(),
-            &-1.to_bigint().unwrap(),
+            &-Rational::from(1),
+            &-Rational::from(1),
         )
         .unwrap();
 
@@ -393,10 +496,43 @@ mod elliptic_curve_tests {
             point1 + point2,
             Point::<RealWeierstrassCurve>::new_point(
                 &curve,
-                &3.to_bigint().unwrap(),
-                &-7.to_bigint().unwrap()
+                &Rational::from(3),
+                &-Rational::from(7)
             )
             .unwrap()
         );
     }
+
+    #[test]
+    fn test_add_points_with_fractional_slope() {
+        let curve = RealWeierstrassCurve {
+            a: Rational::from(5),
+            b: Rational::from(7),
+        };
+        let point = |x: Rational, y: Rational| {
+            Point::<RealWeierstrassCurve>::new_point(&curve, &x, &y).unwrap()
+        };
+        let fraction = |numer: i64, denom: i64| Rational::new(numer.into(), denom.into());
+        let p = point(Rational::from(2), Rational::from(5));
+        let q = point(Rational::from(18), Rational::from(77));
+
+        // The slope is 72/16 = 9/2
+        let sum = p.clone() + q;
+        assert_eq!(sum, point(fraction(1, 4), fraction(23, 8)));
+        assert_eq!(sum.to_string(), "Point(1/4, 23/8)_5_7");
+        // The tangent at (2, 5) has slope 17/10
+        assert_eq!(
+            p.clone() + p,
+            point(fraction(-111, 100), fraction(287, 1000))
+        );
+    }
+
+    #[test]
+    fn test_rational_lowest_terms() {
+        let fraction = |numer: i64, denom: i64| Rational::new(numer.into(), denom.into());
+        assert_eq!(fraction(6, -4), fraction(-3, 2));
+        assert_eq!(fraction(0, 5), Rational::from(0));
+        assert_eq!(fraction(1, 3) + fraction(1, 6), fraction(1, 2));
+        assert_eq!(fraction(1, 3) / fraction(-2, 3), fraction(-1, 2));
+    }
 }
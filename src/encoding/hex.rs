@@ -0,0 +1,87 @@
+use crate::types::errors::EncodingError;
+
+// Hex encoding helpers for byte strings (lowercase output, case-insensitive input).
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, EncodingError> {
+    let digits = hex
+        .chars()
+        .enumerate()
+        .map(|(position, c)| {
+            c.to_digit(16)
+                .map(|digit| digit as u8)
+                .ok_or(EncodingError::InvalidHex { position })
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    if !digits.len().is_multiple_of(2) {
+        return Err(EncodingError::InvalidHex {
+            position: digits.len(),
+        });
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair[1])
+        .collect())
+}
+
+// Hashes are shown byte-reversed, as explorers and Bitcoin Core print them. Input that isn't
+// exactly 32 bytes is invalid where the hash should have ended.
+pub fn hash_from_display_hex(hex: &str) -> Result<[u8; 32], EncodingError> {
+    let mut hash: [u8; 32] =
+        decode_hex(hex)?
+            .try_into()
+            .map_err(|_| EncodingError::InvalidHex {
+                position: hex.len().min(64),
+            })?;
+    hash.reverse();
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod hex_tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let bytes = decode_hex("00ffA0").unwrap();
+
+        assert_eq!(bytes, vec![0x00, 0xff, 0xa0]);
+        assert_eq!(encode_hex(&bytes), "00ffa0");
+    }
+
+    #[test]
+    fn test_decode_invalid_hex() {
+        assert!(matches!(
+            decode_hex("abc"),
+            Err(EncodingError::InvalidHex { position: 3 })
+        ));
+        assert!(matches!(
+            decode_hex("zz"),
+            Err(EncodingError::InvalidHex { position: 0 })
+        ));
+        assert!(matches!(
+            decode_hex("0+1"),
+            Err(EncodingError::InvalidHex { position: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_hash_from_display_hex() {
+        let hex = format!("01{}ff", "00".repeat(30));
+        let hash = hash_from_display_hex(&hex).unwrap();
+
+        assert_eq!(hash[0], 0xff);
+        assert_eq!(hash[31], 0x01);
+        assert!(matches!(
+            hash_from_display_hex("00ff"),
+            Err(EncodingError::InvalidHex { position: 4 })
+        ));
+        assert!(matches!(
+            hash_from_display_hex(&format!("{hex}00")),
+            Err(EncodingError::InvalidHex { position: 64 })
+        ));
+    }
+}
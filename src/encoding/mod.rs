@@ -0,0 +1,99 @@
+pub mod hex;
+pub mod varint;
+
+use std::io::{ErrorKind, Read};
+
+use crate::types::errors::EncodingError;
+
+// Reads exactly N bytes, failing with `UnexpectedEof` (and how many bytes were available) if the
+// reader runs out. Interrupted reads are retried; any other I/O error is returned as `Io`.
+pub fn read_array<R: Read, const N: usize>(reader: &mut R) -> Result<[u8; N], EncodingError> {
+    let mut buf = [0u8; N];
+    let mut read = 0;
+    while read < N {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        }
+    }
+    if read != N {
+        return Err(EncodingError::UnexpectedEof { expected: N, read });
+    }
+    Ok(buf)
+}
+
+// Same as `read_array`, for a length known at runtime.
+pub fn read_vec<R: Read>(reader: &mut R, len: usize) -> Result<Vec<u8>, EncodingError> {
+    let mut buf = Vec::new();
+    // `read_to_end` already retries interrupted reads
+    reader.take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() != len {
+        return Err(EncodingError::UnexpectedEof {
+            expected: len,
+            read: buf.len(),
+        });
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod encoding_tests {
+    use super::*;
+    use std::error::Error as _;
+
+    // Yields its data one byte at a time, failing with `errors` in turn before each byte.
+    struct FlakyReader {
+        data: Vec<u8>,
+        errors: Vec<ErrorKind>,
+    }
+
+    impl Read for FlakyReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if let Some(kind) = self.errors.pop() {
+                return Err(std::io::Error::new(kind, "flaky"));
+            }
+            if self.data.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.data.remove(0);
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn test_interrupted_reads_are_retried() {
+        let mut reader = FlakyReader {
+            data: vec![1, 2, 3, 4, 5, 6],
+            errors: vec![ErrorKind::Interrupted, ErrorKind::Interrupted],
+        };
+        assert_eq!(read_array::<_, 3>(&mut reader).unwrap(), [1, 2, 3]);
+        reader.errors = vec![ErrorKind::Interrupted];
+        assert_eq!(read_vec(&mut reader, 3).unwrap(), vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn test_io_errors_are_not_reported_as_short_reads() {
+        let mut reader = FlakyReader {
+            data: vec![1, 2, 3],
+            errors: vec![ErrorKind::ConnectionReset],
+        };
+        let error = read_array::<_, 3>(&mut reader).unwrap_err();
+        assert!(matches!(&error, EncodingError::Io(io) if io.kind() == ErrorKind::ConnectionReset));
+        assert_eq!(error.source().unwrap().to_string(), "flaky");
+
+        reader.errors = vec![ErrorKind::ConnectionReset];
+        assert!(matches!(
+            read_vec(&mut reader, 3),
+            Err(EncodingError::Io(io)) if io.kind() == ErrorKind::ConnectionReset
+        ));
+        assert!(matches!(
+            read_vec(&mut reader, 4),
+            Err(EncodingError::UnexpectedEof {
+                expected: 4,
+                read: 3
+            })
+        ));
+    }
+}
@@ -0,0 +1,82 @@
+use std::io::Read;
+
+use crate::encoding::read_array;
+use crate::types::errors::EncodingError;
+
+// This module implements Bitcoin's variable length integer ("varint" or CompactSize) encoding.
+//
+//     value < 0xfd          =>  1 byte
+//     value <= 0xffff       =>  0xfd followed by 2 bytes little-endian
+//     value <= 0xffffffff   =>  0xfe followed by 4 bytes little-endian
+//     otherwise             =>  0xff followed by 8 bytes little-endian
+
+pub fn encode_varint(value: u64) -> Vec<u8> {
+    if value < 0xfd {
+        vec![value as u8]
+    } else if value <= 0xffff {
+        let mut result = vec![0xfd];
+        result.extend_from_slice(&(value as u16).to_le_bytes());
+        result
+    } else if value <= 0xffffffff {
+        let mut result = vec![0xfe];
+        result.extend_from_slice(&(value as u32).to_le_bytes());
+        result
+    } else {
+        let mut result = vec![0xff];
+        result.extend_from_slice(&value.to_le_bytes());
+        result
+    }
+}
+
+pub fn read_varint<R: Read>(reader: &mut R) -> Result<u64, EncodingError> {
+    let [prefix] = read_array::<R, 1>(reader)?;
+    let value = match prefix {
+        0xfd => u64::from(u16::from_le_bytes(read_array(reader)?)),
+        0xfe => u64::from(u32::from_le_bytes(read_array(reader)?)),
+        0xff => u64::from_le_bytes(read_array(reader)?),
+        value => u64::from(value),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod varint_tests {
+    use super::*;
+
+    #[test]
+    fn test_varint_round_trip() {
+        let cases: [(u64, &[u8]); 5] = [
+            (0x01, &[0x01]),
+            (0xfc, &[0xfc]),
+            (0xfd, &[0xfd, 0xfd, 0x00]),
+            (0x10000, &[0xfe, 0x00, 0x00, 0x01, 0x00]),
+            (
+                0x100000000,
+                &[0xff, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00],
+            ),
+        ];
+
+        for (value, encoded) in cases {
+            assert_eq!(encode_varint(value), encoded);
+            assert_eq!(read_varint(&mut &encoded[..]).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_read_varint_truncated() {
+        assert!(matches!(
+            read_varint(&mut &[0xfd, 0x01][..]),
+            Err(EncodingError::UnexpectedEof {
+                expected: 2,
+                read: 1
+            })
+        ));
+        assert!(matches!(
+            read_varint(&mut &[][..]),
+            Err(EncodingError::UnexpectedEof {
+                expected: 1,
+                read: 0
+            })
+        ));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod sha256;
+
+pub use sha256::{hash256, sha256};
@@ -0,0 +1,31 @@
+//! Bitcoin in Rust: the core concepts of Bitcoin implemented following "Programming Bitcoin".
+//!
+//! The most commonly used items are re-exported from the crate root and from [`prelude`]:
+//!
+//! ```
+//! use bitcoin_in_rust::prelude::*;
+//!
+//! let infinity = Point::new_infinity(&*SECP256K1_CURVE);
+//! assert_eq!(SECP256K1_GENERATOR.clone() * SECP256K1_ORDER.clone(), infinity);
+//! ```
+#![deny(private_interfaces, private_bounds)]
+
+pub mod block;
+pub mod elliptic_curve;
+pub mod encoding;
+pub mod hash;
+pub mod network;
+pub mod prelude;
+pub mod transaction;
+pub mod types;
+
+pub use elliptic_curve::finite_field::FieldElement;
+pub use elliptic_curve::secp256k1::{
+    SECP256K1_CURVE, SECP256K1_GENERATOR, SECP256K1_ORDER, SECP256K1_PRIME,
+};
+pub use elliptic_curve::traits::{CurveHandle, EllipticCurve, Field, Point};
+pub use elliptic_curve::weierstrass_field_point::WeierstrassCurve;
+pub use network::params::Network;
+pub use types::errors::{
+    BlockError, CurveError, EncodingError, Error, Errors, FieldError, NetError,
+};
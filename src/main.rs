@@ -1,6 +1,3 @@
-mod elliptic_curve;
-mod types;
-
 fn main() {
     println!("Hello, world!");
 }
@@ -0,0 +1,573 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+use crate::encoding::hex::{decode_hex, encode_hex};
+use crate::encoding::varint::{encode_varint, read_varint};
+use crate::encoding::{read_array, read_vec};
+use crate::network::net_addr::NetAddr;
+use crate::network::traits::NetworkMessage;
+use crate::types::errors::NetError;
+
+// This module implements peer address gossip (`addr` and BIP155 `addrv2`) and an address book
+// that keeps the peers learned that way.
+//
+// `addr` entries are a 4-byte timestamp followed by a `NetAddr`, so they can only describe
+// IPv4/IPv6 peers. `addrv2` replaces the fixed 16-byte address with a network id and a
+// variable length address, which makes room for Tor v3, I2P and CJDNS addresses:
+//
+//     time (4) | services (varint) | network id (1) | addr (varint length + bytes) | port (2, BE)
+//
+// `getaddr` asks a peer for the addresses it knows. The book is capped, so gossip can't grow
+// it without bound: when it is full, a newly heard of peer replaces the one seen longest ago.
+
+pub const MAX_ADDR_TO_SEND: u64 = 1000;
+pub const MAX_ADDRV2_SIZE: u64 = 512;
+pub const MAX_ADDR_BOOK_SIZE: usize = 10_000;
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum NetworkAddress {
+    Ipv4([u8; 4]),
+    Ipv6([u8; 16]),
+    TorV3([u8; 32]),
+    I2p([u8; 32]),
+    Cjdns([u8; 16]),
+    // Networks this implementation doesn't know about (including the deprecated Tor v2).
+    Unknown(u8, Vec<u8>),
+}
+
+impl NetworkAddress {
+    pub fn network_id(&self) -> u8 {
+        match self {
+            NetworkAddress::Ipv4(_) => 1,
+            NetworkAddress::Ipv6(_) => 2,
+            NetworkAddress::TorV3(_) => 4,
+            NetworkAddress::I2p(_) => 5,
+            NetworkAddress::Cjdns(_) => 6,
+            NetworkAddress::Unknown(id, _) => *id,
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        match self {
+            NetworkAddress::Ipv4(addr) => addr,
+            NetworkAddress::Ipv6(addr) | NetworkAddress::Cjdns(addr) => addr,
+            NetworkAddress::TorV3(addr) | NetworkAddress::I2p(addr) => addr,
+            NetworkAddress::Unknown(_, addr) => addr,
+        }
+    }
+
+    // Known networks must use their fixed address length, and no address can be empty.
+    pub fn from_parts(network_id: u8, bytes: Vec<u8>) -> Result<Self, NetError> {
+        if bytes.is_empty() {
+            return Err(NetError::InvalidMessage {
+                command: "addrv2",
+                reason: "empty address",
+            });
+        }
+        let wrong_length = |_| NetError::InvalidMessage {
+            command: "addrv2",
+            reason: "wrong address length for network",
+        };
+        let address = match network_id {
+            1 => NetworkAddress::Ipv4(bytes.try_into().map_err(wrong_length)?),
+            2 => NetworkAddress::Ipv6(bytes.try_into().map_err(wrong_length)?),
+            4 => NetworkAddress::TorV3(bytes.try_into().map_err(wrong_length)?),
+            5 => NetworkAddress::I2p(bytes.try_into().map_err(wrong_length)?),
+            6 => NetworkAddress::Cjdns(bytes.try_into().map_err(wrong_length)?),
+            id => NetworkAddress::Unknown(id, bytes),
+        };
+        Ok(address)
+    }
+
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self {
+            NetworkAddress::Ipv4(addr) => Some(IpAddr::V4(Ipv4Addr::from(*addr))),
+            NetworkAddress::Ipv6(addr) => Some(IpAddr::V6(Ipv6Addr::from(*addr))),
+            _ => None,
+        }
+    }
+}
+
+impl From<IpAddr> for NetworkAddress {
+    fn from(ip: IpAddr) -> Self {
+        match ip {
+            IpAddr::V4(ip) => NetworkAddress::Ipv4(ip.octets()),
+            IpAddr::V6(ip) => NetworkAddress::Ipv6(ip.octets()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AddrEntry {
+    pub last_seen: u32,
+    pub services: u64,
+    pub address: NetworkAddress,
+    pub port: u16,
+}
+
+impl AddrEntry {
+    fn from_net_addr(last_seen: u32, net_addr: &NetAddr) -> Self {
+        AddrEntry {
+            last_seen,
+            services: net_addr.services,
+            address: NetworkAddress::from(net_addr.ip()),
+            port: net_addr.port,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct GetAddrMessage;
+
+impl NetworkMessage for GetAddrMessage {
+    fn command() -> &'static str {
+        "getaddr"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn parse<R: Read>(_reader: &mut R) -> Result<Self, NetError> {
+        Ok(GetAddrMessage)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct AddrMessage {
+    pub addresses: Vec<(u32, NetAddr)>,
+}
+
+impl NetworkMessage for AddrMessage {
+    fn command() -> &'static str {
+        "addr"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut result = encode_varint(self.addresses.len() as u64);
+        for (timestamp, net_addr) in &self.addresses {
+            result.extend_from_slice(&timestamp.to_le_bytes());
+            result.extend(net_addr.serialize());
+        }
+        result
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        let count = read_varint(reader)?;
+        if count > MAX_ADDR_TO_SEND {
+            return Err(NetError::InvalidMessage {
+                command: "addr",
+                reason: "too many addresses",
+            });
+        }
+        let addresses = (0..count)
+            .map(|_| {
+                let timestamp = u32::from_le_bytes(read_array(reader)?);
+                Ok((timestamp, NetAddr::parse(reader)?))
+            })
+            .collect::<Result<_, NetError>>()?;
+        Ok(AddrMessage { addresses })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct AddrV2Message {
+    pub addresses: Vec<AddrEntry>,
+}
+
+impl NetworkMessage for AddrV2Message {
+    fn command() -> &'static str {
+        "addrv2"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut result = encode_varint(self.addresses.len() as u64);
+        for entry in &self.addresses {
+            result.extend_from_slice(&entry.last_seen.to_le_bytes());
+            result.extend(encode_varint(entry.services));
+            result.push(entry.address.network_id());
+            result.extend(encode_varint(entry.address.bytes().len() as u64));
+            result.extend_from_slice(entry.address.bytes());
+            result.extend_from_slice(&entry.port.to_be_bytes());
+        }
+        result
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        let count = read_varint(reader)?;
+        if count > MAX_ADDR_TO_SEND {
+            return Err(NetError::InvalidMessage {
+                command: "addrv2",
+                reason: "too many addresses",
+            });
+        }
+        let addresses = (0..count)
+            .map(|_| {
+                let last_seen = u32::from_le_bytes(read_array(reader)?);
+                let services = read_varint(reader)?;
+                let [network_id] = read_array::<R, 1>(reader)?;
+                let len = read_varint(reader)?;
+                if len > MAX_ADDRV2_SIZE {
+                    return Err(NetError::InvalidMessage {
+                        command: "addrv2",
+                        reason: "address too long",
+                    });
+                }
+                let address =
+                    NetworkAddress::from_parts(network_id, read_vec(reader, len as usize)?)?;
+                let port = u16::from_be_bytes(read_array(reader)?);
+                Ok(AddrEntry {
+                    last_seen,
+                    services,
+                    address,
+                    port,
+                })
+            })
+            .collect::<Result<_, NetError>>()?;
+        Ok(AddrV2Message { addresses })
+    }
+}
+
+// Known peers, de-duplicated by address and port, remembering the most recent time each one
+// was seen. Holds at most `MAX_ADDR_BOOK_SIZE` of them.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct AddrBook {
+    entries: HashMap<(NetworkAddress, u16), AddrEntry>,
+}
+
+impl AddrBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn add(&mut self, entry: AddrEntry) {
+        let key = (entry.address.clone(), entry.port);
+        let full = self.entries.len() >= MAX_ADDR_BOOK_SIZE;
+        match self.entries.get_mut(&key) {
+            Some(existing) if existing.last_seen >= entry.last_seen => {}
+            Some(existing) => *existing = entry,
+            None if !full => {
+                self.entries.insert(key, entry);
+            }
+            None => {
+                let oldest = self
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, existing)| existing.last_seen)
+                    .map(|(key, existing)| (key.clone(), existing.last_seen));
+                if let Some((oldest, last_seen)) = oldest {
+                    if last_seen < entry.last_seen {
+                        self.entries.remove(&oldest);
+                        self.entries.insert(key, entry);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn add_addr(&mut self, message: &AddrMessage) {
+        for (timestamp, net_addr) in &message.addresses {
+            self.add(AddrEntry::from_net_addr(*timestamp, net_addr));
+        }
+    }
+
+    pub fn add_addrv2(&mut self, message: &AddrV2Message) {
+        for entry in &message.addresses {
+            self.add(entry.clone());
+        }
+    }
+
+    // Up to `count` peers to try connecting to, most recently seen first.
+    pub fn candidates(&self, count: usize) -> Vec<&AddrEntry> {
+        let mut entries: Vec<&AddrEntry> = self.entries.values().collect();
+        entries.sort_by(|a, b| {
+            b.last_seen
+                .cmp(&a.last_seen)
+                .then_with(|| a.address.bytes().cmp(b.address.bytes()))
+                .then_with(|| a.port.cmp(&b.port))
+        });
+        entries.truncate(count);
+        entries
+    }
+
+    // The IPv4 and IPv6 peers to answer a `getaddr` with, most recently seen first. `addr`
+    // can't describe the other networks.
+    pub fn addr_message(&self) -> AddrMessage {
+        let addresses = self
+            .candidates(self.len())
+            .into_iter()
+            .filter_map(|entry| {
+                let ip = entry.address.ip()?;
+                Some((
+                    entry.last_seen,
+                    NetAddr::new(ip, entry.port, entry.services),
+                ))
+            })
+            .take(MAX_ADDR_TO_SEND as usize)
+            .collect();
+        AddrMessage { addresses }
+    }
+
+    // One peer per line: `last_seen services network_id address_hex port`.
+    pub fn save(&self, path: &Path) -> Result<(), NetError> {
+        let contents: String = self
+            .candidates(self.len())
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} {} {} {} {}\n",
+                    entry.last_seen,
+                    entry.services,
+                    entry.address.network_id(),
+                    encode_hex(entry.address.bytes()),
+                    entry.port
+                )
+            })
+            .collect();
+        Ok(fs::write(path, contents)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, NetError> {
+        let contents = fs::read_to_string(path)?;
+        let mut book = AddrBook::new();
+        for (index, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = || NetError::InvalidAddrBook { line: index + 1 };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [last_seen, services, network_id, address, port] = fields[..] else {
+                return Err(invalid());
+            };
+            let entry = AddrEntry {
+                last_seen: last_seen.parse().map_err(|_| invalid())?,
+                services: services.parse().map_err(|_| invalid())?,
+                address: NetworkAddress::from_parts(
+                    network_id.parse().map_err(|_| invalid())?,
+                    decode_hex(address).map_err(|_| invalid())?,
+                )
+                .map_err(|_| invalid())?,
+                port: port.parse().map_err(|_| invalid())?,
+            };
+            book.add(entry);
+        }
+        Ok(book)
+    }
+}
+
+#[cfg(test)]
+mod addr_tests {
+    use super::*;
+
+    fn entry(last_seen: u32, ip: [u8; 4], port: u16) -> AddrEntry {
+        AddrEntry {
+            last_seen,
+            services: 1,
+            address: NetworkAddress::Ipv4(ip),
+            port,
+        }
+    }
+
+    #[test]
+    fn test_parse_addrv2_payload() {
+        let payload = decode_hex(
+            "0300f1536509010401020304208d64f1536501021020010db8000000000000000000000001208dc8f153\
+             65000420000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f235a",
+        )
+        .unwrap();
+
+        let message = AddrV2Message::parse(&mut payload.as_slice()).unwrap();
+
+        assert_eq!(message.addresses.len(), 3);
+        assert_eq!(message.addresses[0].last_seen, 1_700_000_000);
+        assert_eq!(message.addresses[0].services, 0x09);
+        assert_eq!(
+            message.addresses[0].address.ip(),
+            Some("1.2.3.4".parse().unwrap())
+        );
+        assert_eq!(message.addresses[0].port, 8333);
+        assert_eq!(
+            message.addresses[1].address.ip(),
+            Some("2001:db8::1".parse().unwrap())
+        );
+        let tor: [u8; 32] = core::array::from_fn(|i| i as u8);
+        assert_eq!(message.addresses[2].address, NetworkAddress::TorV3(tor));
+        assert_eq!(message.addresses[2].address.ip(), None);
+        assert_eq!(message.addresses[2].port, 9050);
+        assert_eq!(message.serialize(), payload);
+    }
+
+    #[test]
+    fn test_addrv2_rejects_wrong_length_for_known_network() {
+        // An IPv4 entry with a 5-byte address
+        let payload = decode_hex("0100f1536500010501020304052080").unwrap();
+
+        assert!(matches!(
+            AddrV2Message::parse(&mut payload.as_slice()),
+            Err(NetError::InvalidMessage {
+                command: "addrv2",
+                reason: "wrong address length for network"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_addr_round_trip_into_book() {
+        let message = AddrMessage {
+            addresses: vec![
+                (
+                    1_700_000_000,
+                    NetAddr::new("10.0.0.1".parse().unwrap(), 8333, 1),
+                ),
+                (
+                    1_700_000_500,
+                    NetAddr::new("10.0.0.1".parse().unwrap(), 8333, 1),
+                ),
+            ],
+        };
+        let parsed = AddrMessage::parse(&mut message.serialize().as_slice()).unwrap();
+        let mut book = AddrBook::new();
+        book.add_addr(&parsed);
+
+        assert_eq!(parsed, message);
+        assert_eq!(book.len(), 1);
+        assert_eq!(book.candidates(1)[0].last_seen, 1_700_000_500);
+        assert_eq!(
+            book.candidates(1)[0].address,
+            NetworkAddress::Ipv4([10, 0, 0, 1])
+        );
+    }
+
+    #[test]
+    fn test_candidates_prefer_recently_seen() {
+        let mut book = AddrBook::new();
+        book.add(entry(100, [1, 1, 1, 1], 8333));
+        book.add(entry(300, [2, 2, 2, 2], 8333));
+        book.add(entry(200, [3, 3, 3, 3], 8333));
+        // An older sighting doesn't override a newer one
+        book.add(entry(50, [2, 2, 2, 2], 8333));
+
+        let candidates = book.candidates(2);
+
+        assert_eq!(book.len(), 3);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].address, NetworkAddress::Ipv4([2, 2, 2, 2]));
+        assert_eq!(candidates[0].last_seen, 300);
+        assert_eq!(candidates[1].address, NetworkAddress::Ipv4([3, 3, 3, 3]));
+    }
+
+    #[test]
+    fn test_book_file_round_trip() {
+        let mut book = AddrBook::new();
+        book.add(entry(100, [1, 1, 1, 1], 8333));
+        book.add(AddrEntry {
+            last_seen: 200,
+            services: 0x409,
+            address: NetworkAddress::TorV3([7u8; 32]),
+            port: 9050,
+        });
+        let path = std::env::temp_dir().join(format!("addr_book_{}.txt", std::process::id()));
+
+        book.save(&path).unwrap();
+        let loaded = AddrBook::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, book);
+    }
+
+    #[test]
+    fn test_load_reports_malformed_line() {
+        let path = std::env::temp_dir().join(format!("bad_addr_book_{}.txt", std::process::id()));
+        fs::write(&path, "100 1 1 01010101 8333\n\n200 1 1 0101 8333\n").unwrap();
+
+        let result = AddrBook::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(NetError::InvalidAddrBook { line: 3 })));
+        assert!(matches!(
+            AddrBook::load(&path),
+            Err(NetError::Io(error)) if error.kind() == std::io::ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn test_empty_addresses_are_rejected() {
+        // An entry of the unknown network 9 with a zero-length address
+        let payload = decode_hex("0100f1536500090000208d").unwrap();
+
+        assert!(matches!(
+            AddrV2Message::parse(&mut payload.as_slice()),
+            Err(NetError::InvalidMessage {
+                command: "addrv2",
+                reason: "empty address"
+            })
+        ));
+        // Unknown networks with an address still round-trip through the file
+        let mut book = AddrBook::new();
+        book.add(AddrEntry {
+            last_seen: 100,
+            services: 0,
+            address: NetworkAddress::from_parts(9, vec![0xab]).unwrap(),
+            port: 8333,
+        });
+        let path = std::env::temp_dir().join(format!("unknown_addr_{}.txt", std::process::id()));
+        book.save(&path).unwrap();
+        let loaded = AddrBook::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, book);
+    }
+
+    #[test]
+    fn test_full_book_replaces_the_oldest_peer() {
+        let mut book = AddrBook::new();
+        for i in 0..MAX_ADDR_BOOK_SIZE as u32 {
+            book.add(entry(1_000 + i, i.to_be_bytes(), 8333));
+        }
+        assert_eq!(book.len(), MAX_ADDR_BOOK_SIZE);
+
+        // Older than everything in the book: ignored
+        book.add(entry(1, [10, 0, 0, 1], 8333));
+        assert_eq!(book.len(), MAX_ADDR_BOOK_SIZE);
+        assert!(book
+            .candidates(book.len())
+            .iter()
+            .all(|entry| entry.last_seen != 1));
+
+        // Newer: replaces the peer seen at 1_000
+        book.add(entry(50_000, [10, 0, 0, 2], 8333));
+        let candidates = book.candidates(book.len());
+        assert_eq!(book.len(), MAX_ADDR_BOOK_SIZE);
+        assert_eq!(candidates[0].address, NetworkAddress::Ipv4([10, 0, 0, 2]));
+        assert_eq!(candidates.last().unwrap().last_seen, 1_001);
+    }
+
+    #[test]
+    fn test_addr_message_only_holds_ip_peers() {
+        let mut book = AddrBook::new();
+        book.add(entry(100, [1, 1, 1, 1], 8333));
+        book.add(AddrEntry {
+            last_seen: 200,
+            services: 0x409,
+            address: NetworkAddress::TorV3([7u8; 32]),
+            port: 9050,
+        });
+
+        assert_eq!(
+            book.addr_message(),
+            AddrMessage {
+                addresses: vec![(100, NetAddr::new("1.1.1.1".parse().unwrap(), 8333, 1))]
+            }
+        );
+    }
+}
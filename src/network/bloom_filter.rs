@@ -0,0 +1,323 @@
+use std::f64::consts::LN_2;
+use std::io::Read;
+
+use crate::encoding::varint::{encode_varint, read_varint};
+use crate::encoding::{read_array, read_vec};
+use crate::network::traits::NetworkMessage;
+use crate::types::errors::NetError;
+
+// This module implements BIP37 bloom filters and the messages used to load them into a peer.
+//
+// A light client sends a bloom filter describing the items it is interested in (addresses,
+// outpoints...) and the peer only relays matching transactions. Each item sets
+// `function_count` bits, the i-th one chosen by murmur3 seeded with
+//
+//     i * 0xfba4c795 + tweak
+//
+// Bits are stored little-endian within each byte of the filter.
+
+pub const BIP37_CONSTANT: u32 = 0xfba4c795;
+pub const MAX_BLOOM_FILTER_SIZE: usize = 36_000;
+pub const MAX_HASH_FUNCS: u32 = 50;
+pub const MAX_FILTERADD_DATA_SIZE: usize = 520;
+
+// Whether the peer should add outpoints of matched outputs to the filter.
+pub const BLOOM_UPDATE_NONE: u8 = 0;
+pub const BLOOM_UPDATE_ALL: u8 = 1;
+pub const BLOOM_UPDATE_P2PUBKEY_ONLY: u8 = 2;
+
+// 32-bit MurmurHash3 (x86 variant).
+pub fn murmur3(data: &[u8], seed: u32) -> u32 {
+    const C1: u32 = 0xcc9e2d51;
+    const C2: u32 = 0x1b873593;
+
+    let mix = |k: u32| k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+
+    let mut h1 = seed;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        h1 ^= mix(u32::from_le_bytes(chunk.try_into().unwrap()));
+        h1 = h1.rotate_left(13).wrapping_mul(5).wrapping_add(0xe6546b64);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let k1 = tail
+            .iter()
+            .rev()
+            .fold(0u32, |acc, byte| (acc << 8) | u32::from(*byte));
+        h1 ^= mix(k1);
+    }
+
+    h1 ^= data.len() as u32;
+    h1 ^= h1 >> 16;
+    h1 = h1.wrapping_mul(0x85ebca6b);
+    h1 ^= h1 >> 13;
+    h1 = h1.wrapping_mul(0xc2b2ae35);
+    h1 ^= h1 >> 16;
+    h1
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BloomFilter {
+    pub bits: Vec<u8>,
+    pub function_count: u32,
+    pub tweak: u32,
+    pub flags: u8,
+}
+
+impl BloomFilter {
+    // `size` is in bytes. It must be at least one byte and both it and `function_count` are
+    // capped by the BIP37 maxima, so peers don't reject the filter.
+    pub fn new(size: usize, function_count: u32, tweak: u32) -> Result<Self, NetError> {
+        if size == 0 {
+            return Err(NetError::InvalidBloomFilter {
+                reason: "the filter is empty",
+            });
+        }
+        if size > MAX_BLOOM_FILTER_SIZE {
+            return Err(NetError::InvalidBloomFilter {
+                reason: "the filter is larger than 36000 bytes",
+            });
+        }
+        if function_count > MAX_HASH_FUNCS {
+            return Err(NetError::InvalidBloomFilter {
+                reason: "more than 50 hash functions",
+            });
+        }
+        Ok(BloomFilter {
+            bits: vec![0u8; size],
+            function_count,
+            tweak,
+            flags: BLOOM_UPDATE_ALL,
+        })
+    }
+
+    // Sizes the filter for `elements` items and a target false positive rate, using the
+    // formulas (and integer truncation) of BIP37 / Bitcoin Core, keeping at least one byte
+    // and one hash function.
+    pub fn with_fp_rate(elements: usize, fp_rate: f64, tweak: u32) -> Self {
+        let elements = elements.max(1);
+        let size_bits = (-1.0 / (LN_2 * LN_2) * elements as f64 * fp_rate.ln()) as usize;
+        let size = (size_bits.min(MAX_BLOOM_FILTER_SIZE * 8) / 8).max(1);
+        let function_count = ((size * 8 / elements) as f64 * LN_2) as u32;
+
+        BloomFilter::new(size, function_count.clamp(1, MAX_HASH_FUNCS), tweak)
+            .expect("the size and function count are clamped to the BIP37 limits")
+    }
+
+    fn bit_indexes<'a>(&'a self, item: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let bit_count = self.bits.len() * 8;
+        (0..self.function_count).map(move |i| {
+            let seed = i.wrapping_mul(BIP37_CONSTANT).wrapping_add(self.tweak);
+            murmur3(item, seed) as usize % bit_count
+        })
+    }
+
+    pub fn add(&mut self, item: &[u8]) {
+        let indexes: Vec<usize> = self.bit_indexes(item).collect();
+        for index in indexes {
+            self.bits[index / 8] |= 1 << (index % 8);
+        }
+    }
+
+    // May return false positives, never false negatives.
+    pub fn contains(&self, item: &[u8]) -> bool {
+        self.bit_indexes(item)
+            .all(|index| self.bits[index / 8] & (1 << (index % 8)) != 0)
+    }
+
+    pub fn filterload(&self) -> FilterLoadMessage {
+        FilterLoadMessage {
+            filter: self.bits.clone(),
+            function_count: self.function_count,
+            tweak: self.tweak,
+            flags: self.flags,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FilterLoadMessage {
+    pub filter: Vec<u8>,
+    pub function_count: u32,
+    pub tweak: u32,
+    pub flags: u8,
+}
+
+impl NetworkMessage for FilterLoadMessage {
+    fn command() -> &'static str {
+        "filterload"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut result = encode_varint(self.filter.len() as u64);
+        result.extend_from_slice(&self.filter);
+        result.extend_from_slice(&self.function_count.to_le_bytes());
+        result.extend_from_slice(&self.tweak.to_le_bytes());
+        result.push(self.flags);
+        result
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        let size = read_varint(reader)? as usize;
+        if size > MAX_BLOOM_FILTER_SIZE {
+            return Err(NetError::InvalidMessage {
+                command: "filterload",
+                reason: "filter too large",
+            });
+        }
+        let filter = read_vec(reader, size)?;
+        let function_count = u32::from_le_bytes(read_array(reader)?);
+        if function_count > MAX_HASH_FUNCS {
+            return Err(NetError::InvalidMessage {
+                command: "filterload",
+                reason: "too many hash functions",
+            });
+        }
+        let tweak = u32::from_le_bytes(read_array(reader)?);
+        let [flags] = read_array::<R, 1>(reader)?;
+
+        Ok(FilterLoadMessage {
+            filter,
+            function_count,
+            tweak,
+            flags,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FilterAddMessage {
+    pub data: Vec<u8>,
+}
+
+impl NetworkMessage for FilterAddMessage {
+    fn command() -> &'static str {
+        "filteradd"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut result = encode_varint(self.data.len() as u64);
+        result.extend_from_slice(&self.data);
+        result
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        let size = read_varint(reader)? as usize;
+        if size > MAX_FILTERADD_DATA_SIZE {
+            return Err(NetError::InvalidMessage {
+                command: "filteradd",
+                reason: "data element too large",
+            });
+        }
+        Ok(FilterAddMessage {
+            data: read_vec(reader, size)?,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct FilterClearMessage;
+
+impl NetworkMessage for FilterClearMessage {
+    fn command() -> &'static str {
+        "filterclear"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn parse<R: Read>(_reader: &mut R) -> Result<Self, NetError> {
+        Ok(FilterClearMessage)
+    }
+}
+
+#[cfg(test)]
+mod bloom_filter_tests {
+    use super::*;
+    use crate::encoding::hex::encode_hex;
+
+    #[test]
+    fn test_murmur3() {
+        assert_eq!(murmur3(b"", 0), 0);
+        assert_eq!(murmur3(&[0x00], 0), 0x514e28b7);
+        assert_eq!(murmur3(b"Hello World", 0), 0x197683ce);
+        assert_eq!(murmur3(b"abc", 0x9747b28c), 0xc84a62dd);
+    }
+
+    #[test]
+    fn test_add_programming_bitcoin_example() {
+        let mut bloom_filter = BloomFilter::new(10, 5, 99).unwrap();
+
+        bloom_filter.add(b"Hello World");
+        bloom_filter.add(b"Goodbye!");
+
+        assert_eq!(encode_hex(&bloom_filter.bits), "4000600a080000010940");
+        assert!(bloom_filter.contains(b"Hello World"));
+        assert!(bloom_filter.contains(b"Goodbye!"));
+    }
+
+    #[test]
+    fn test_filterload_programming_bitcoin_example() {
+        let mut bloom_filter = BloomFilter::new(10, 5, 99).unwrap();
+        bloom_filter.add(b"Hello World");
+        bloom_filter.add(b"Goodbye!");
+
+        let message = bloom_filter.filterload();
+
+        assert_eq!(FilterLoadMessage::command(), "filterload");
+        assert_eq!(
+            encode_hex(&message.serialize()),
+            "0a4000600a080000010940050000006300000001"
+        );
+        assert_eq!(
+            FilterLoadMessage::parse(&mut message.serialize().as_slice()).unwrap(),
+            message
+        );
+    }
+
+    #[test]
+    fn test_new_rejects_out_of_range_parameters() {
+        for (size, function_count) in [(0, 5), (MAX_BLOOM_FILTER_SIZE + 1, 5), (10, 51)] {
+            assert!(matches!(
+                BloomFilter::new(size, function_count, 0),
+                Err(NetError::InvalidBloomFilter { .. })
+            ));
+        }
+        assert!(BloomFilter::new(1, 0, 0).is_ok());
+        assert!(BloomFilter::new(MAX_BLOOM_FILTER_SIZE, MAX_HASH_FUNCS, 0).is_ok());
+    }
+
+    #[test]
+    fn test_with_fp_rate_sizing() {
+        let bloom_filter = BloomFilter::with_fp_rate(10, 0.0001, 0);
+        assert_eq!(bloom_filter.bits.len(), 23);
+        assert_eq!(bloom_filter.function_count, 12);
+
+        let bloom_filter = BloomFilter::with_fp_rate(1000, 0.01, 0);
+        assert_eq!(bloom_filter.bits.len(), 1198);
+        assert_eq!(bloom_filter.function_count, 6);
+
+        let bloom_filter = BloomFilter::with_fp_rate(1_000_000, 0.0001, 0);
+        assert_eq!(bloom_filter.bits.len(), MAX_BLOOM_FILTER_SIZE);
+        assert_eq!(bloom_filter.function_count, 1);
+    }
+
+    #[test]
+    fn test_filteradd_and_filterclear() {
+        let message = FilterAddMessage {
+            data: b"Hello World".to_vec(),
+        };
+        let serialized = message.serialize();
+
+        assert_eq!(serialized[0], 11);
+        assert_eq!(
+            FilterAddMessage::parse(&mut serialized.as_slice()).unwrap(),
+            message
+        );
+        assert!(FilterClearMessage.serialize().is_empty());
+        assert_eq!(FilterClearMessage::command(), "filterclear");
+    }
+}
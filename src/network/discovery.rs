@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::network::node::{SimpleNode, Timeouts};
+use crate::network::params::Network;
+use crate::types::errors::NetError;
+
+// This module implements peer discovery through the DNS seeds of each network.
+//
+// A DNS seed answers A/AAAA queries with the addresses of reachable nodes, so resolving the
+// seed hostnames is enough to bootstrap. Resolution goes through the `Resolver` trait so it
+// can be replaced in tests (or by a custom DNS client). Seeds also hand out nodes that are
+// gone or full, so `connect_any` works down the list until a handshake succeeds.
+
+pub trait Resolver {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>>;
+}
+
+// Resolver backed by the operating system (`std::net::ToSocketAddrs`).
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        Ok((host, port).to_socket_addrs()?.collect())
+    }
+}
+
+pub fn discover_peers(network: Network) -> Result<Vec<SocketAddr>, NetError> {
+    discover_peers_with(&SystemResolver, network)
+}
+
+// Resolves every seed of `network`, ignoring the ones that fail, and returns the
+// de-duplicated addresses in random order so peers don't all pick the same nodes.
+pub fn discover_peers_with<R: Resolver>(
+    resolver: &R,
+    network: Network,
+) -> Result<Vec<SocketAddr>, NetError> {
+    let mut seen = HashSet::new();
+    let mut peers: Vec<SocketAddr> = network
+        .dns_seeds()
+        .iter()
+        .filter_map(|seed| resolver.resolve(seed, network.default_port()).ok())
+        .flatten()
+        .filter(|addr| seen.insert(*addr))
+        .collect();
+
+    if peers.is_empty() {
+        return Err(NetError::NoPeersFound);
+    }
+    shuffle(&mut peers);
+    Ok(peers)
+}
+
+// Tries the first `attempts` of `peers` in order until one connects and completes the
+// handshake. Returns the error of the last one tried if none does.
+pub fn connect_any(
+    peers: &[SocketAddr],
+    attempts: usize,
+    network: Network,
+) -> Result<SimpleNode, NetError> {
+    let mut last_error = NetError::NoPeersFound;
+    for address in peers.iter().take(attempts) {
+        let connected =
+            SimpleNode::connect_to(address, network, Timeouts::default()).and_then(|mut node| {
+                node.handshake()?;
+                Ok(node)
+            });
+        match connected {
+            Ok(node) => return Ok(node),
+            Err(error) => last_error = error,
+        }
+    }
+    Err(last_error)
+}
+
+// Fisher-Yates with a time-seeded xorshift: the order only needs to differ between runs,
+// not to be unpredictable.
+fn shuffle<T>(items: &mut [T]) {
+    let mut state = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+        | 1;
+    for i in (1..items.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        items.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+}
+
+#[cfg(test)]
+mod discovery_tests {
+    use super::*;
+    use crate::network::envelope::NetworkEnvelope;
+    use crate::network::node::node_tests::{mock_peer, peer_version, send};
+    use crate::network::version::VerAckMessage;
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+
+    struct MockResolver {
+        answers: HashMap<&'static str, Vec<&'static str>>,
+    }
+
+    impl Resolver for MockResolver {
+        fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+            match self.answers.get(host) {
+                Some(ips) => Ok(ips
+                    .iter()
+                    .map(|ip| SocketAddr::new(ip.parse().unwrap(), port))
+                    .collect()),
+                None => Err(io::Error::new(io::ErrorKind::NotFound, "no such host")),
+            }
+        }
+    }
+
+    #[test]
+    fn test_discover_peers_dedups_and_applies_port() {
+        let resolver = MockResolver {
+            answers: HashMap::from([
+                ("seed.bitcoin.sipa.be", vec!["1.1.1.1", "2.2.2.2"]),
+                ("dnsseed.bluematt.me", vec!["2.2.2.2", "2001:db8::1"]),
+            ]),
+        };
+
+        let mut peers = discover_peers_with(&resolver, Network::Mainnet).unwrap();
+        peers.sort();
+
+        let expected: Vec<SocketAddr> = vec![
+            "1.1.1.1:8333".parse().unwrap(),
+            "2.2.2.2:8333".parse().unwrap(),
+            "[2001:db8::1]:8333".parse().unwrap(),
+        ];
+        assert_eq!(peers, expected);
+    }
+
+    #[test]
+    fn test_discover_peers_uses_network_seeds_and_port() {
+        let resolver = MockResolver {
+            answers: HashMap::from([("seed.tbtc.petertodd.net", vec!["3.3.3.3"])]),
+        };
+
+        assert_eq!(
+            discover_peers_with(&resolver, Network::Testnet).unwrap(),
+            vec!["3.3.3.3:18333".parse().unwrap()]
+        );
+        assert!(matches!(
+            discover_peers_with(&resolver, Network::Mainnet),
+            Err(NetError::NoPeersFound)
+        ));
+    }
+
+    #[test]
+    fn test_network_seed_lists_and_ports() {
+        assert_eq!(Network::Mainnet.default_port(), 8333);
+        assert_eq!(Network::Testnet.default_port(), 18333);
+        assert_eq!(Network::Signet.default_port(), 38333);
+        assert_eq!(Network::Regtest.default_port(), 18444);
+        assert!(Network::Mainnet
+            .dns_seeds()
+            .contains(&"seed.bitcoin.sipa.be"));
+        assert!(Network::Regtest.dns_seeds().is_empty());
+    }
+
+    #[test]
+    fn test_shuffle_keeps_elements() {
+        let mut items: Vec<u32> = (0..100).collect();
+
+        shuffle(&mut items);
+        items.sort();
+
+        assert_eq!(items, (0..100).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_connect_any_falls_through_a_failing_peer() {
+        // Nothing listens on the port of a listener that was dropped
+        let closed = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let (port, peer) = mock_peer(|stream| {
+            NetworkEnvelope::parse(stream, Network::Regtest).unwrap();
+            send(stream, &peer_version());
+            send(stream, &VerAckMessage);
+        });
+        let working = SocketAddr::from(([127, 0, 0, 1], port));
+
+        assert!(matches!(
+            connect_any(&[closed, working], 1, Network::Regtest),
+            Err(NetError::Io(_))
+        ));
+        let node = connect_any(&[closed, working], 2, Network::Regtest).unwrap();
+        assert_eq!(node.peer().unwrap().user_agent, "/mock:1.0/");
+        drop(node);
+        peer.join().unwrap();
+
+        assert!(matches!(
+            connect_any(&[], 3, Network::Regtest),
+            Err(NetError::NoPeersFound)
+        ));
+    }
+}
@@ -0,0 +1,224 @@
+// This module implements the envelope framing every P2P message on the wire:
+//
+//     magic (4) | command (12, ASCII padded with NULs) | payload length (4, LE)
+//     | checksum (4) | payload
+//
+// The magic tells which network the message belongs to and the checksum is the first 4 bytes
+// of the payload's hash256. The length is checked against `MAX_PAYLOAD_SIZE` before anything
+// is allocated, so a peer cannot make us reserve 4 GiB with a forged header.
+
+use std::io::Read;
+
+use crate::encoding::{read_array, read_vec};
+use crate::hash::hash256;
+use crate::network::params::Network;
+use crate::network::traits::NetworkMessage;
+use crate::types::errors::NetError;
+
+pub const COMMAND_SIZE: usize = 12;
+// Same limit as Bitcoin Core's MAX_SIZE.
+pub const MAX_PAYLOAD_SIZE: u32 = 32 * 1024 * 1024;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct NetworkEnvelope {
+    pub command: [u8; COMMAND_SIZE],
+    pub payload: Vec<u8>,
+}
+
+impl NetworkEnvelope {
+    // Pads `command` with NULs; it must be ASCII and at most 12 bytes long.
+    pub fn new(command: &str, payload: Vec<u8>) -> Result<Self, NetError> {
+        if command.len() > COMMAND_SIZE || !command.is_ascii() || command.contains('\0') {
+            return Err(NetError::InvalidCommand);
+        }
+        let mut padded = [0u8; COMMAND_SIZE];
+        padded[..command.len()].copy_from_slice(command.as_bytes());
+        Ok(Self {
+            command: padded,
+            payload,
+        })
+    }
+
+    pub fn from_message<M: NetworkMessage>(message: &M) -> Self {
+        Self::new(M::command(), message.serialize()).expect("message commands are valid")
+    }
+
+    // The command without its NUL padding.
+    pub fn command(&self) -> &str {
+        let len = self
+            .command
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(COMMAND_SIZE);
+        std::str::from_utf8(&self.command[..len]).unwrap_or_default()
+    }
+
+    // Parses the payload as `M`, which must be the message this envelope carries.
+    pub fn message<M: NetworkMessage>(&self) -> Result<M, NetError> {
+        if self.command() != M::command() {
+            return Err(NetError::UnexpectedCommand {
+                expected: M::command(),
+                actual: self.command().to_string(),
+            });
+        }
+        M::parse(&mut self.payload.as_slice())
+    }
+
+    pub fn parse<R: Read>(reader: &mut R, network: Network) -> Result<Self, NetError> {
+        network.check_magic(read_array(reader)?)?;
+
+        let command: [u8; COMMAND_SIZE] = read_array(reader)?;
+        if !is_valid_command(&command) {
+            return Err(NetError::InvalidCommand);
+        }
+
+        let length = u32::from_le_bytes(read_array(reader)?);
+        if length > MAX_PAYLOAD_SIZE {
+            return Err(NetError::PayloadTooLarge {
+                length,
+                max: MAX_PAYLOAD_SIZE,
+            });
+        }
+
+        let expected: [u8; 4] = read_array(reader)?;
+        let payload = read_vec(reader, length as usize)?;
+        let actual = checksum(&payload);
+        if actual != expected {
+            return Err(NetError::ChecksumMismatch { expected, actual });
+        }
+
+        Ok(Self { command, payload })
+    }
+
+    pub fn serialize(&self, network: Network) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(24 + self.payload.len());
+        bytes.extend_from_slice(&network.magic());
+        bytes.extend_from_slice(&self.command);
+        bytes.extend_from_slice(&(self.payload.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&checksum(&self.payload));
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+}
+
+fn checksum(payload: &[u8]) -> [u8; 4] {
+    hash256(payload)[..4].try_into().unwrap()
+}
+
+// Printable ASCII followed only by NUL padding, as Bitcoin Core requires.
+fn is_valid_command(command: &[u8; COMMAND_SIZE]) -> bool {
+    let len = command
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(COMMAND_SIZE);
+    command[..len].iter().all(|byte| byte.is_ascii_graphic())
+        && command[len..].iter().all(|&byte| byte == 0)
+}
+
+#[cfg(test)]
+mod envelope_tests {
+    use super::*;
+    use crate::encoding::hex::{decode_hex, encode_hex};
+    use crate::network::version::{VerAckMessage, VersionMessage};
+
+    const VERACK: &str = "f9beb4d976657261636b000000000000000000005df6e0e2";
+    const VERSION: &str = "f9beb4d976657273696f6e0000000000650000005f1a69d2721101000100000000000000bc8f5e5400000000010000000000000000000000000000000000ffffc61b6409208d010000000000000000000000000000000000ffffcb0071c0208d128035cbc97953f80f2f5361746f7368693a302e392e332fcf05050001";
+
+    fn parse(hex: &str, network: Network) -> Result<NetworkEnvelope, NetError> {
+        NetworkEnvelope::parse(&mut decode_hex(hex).unwrap().as_slice(), network)
+    }
+
+    #[test]
+    fn test_verack_round_trip() {
+        let envelope = parse(VERACK, Network::Mainnet).unwrap();
+        assert_eq!(envelope.command(), "verack");
+        assert!(envelope.payload.is_empty());
+        assert!(envelope.message::<VerAckMessage>().is_ok());
+        assert_eq!(encode_hex(&envelope.serialize(Network::Mainnet)), VERACK);
+        assert_eq!(NetworkEnvelope::from_message(&VerAckMessage), envelope);
+    }
+
+    #[test]
+    fn test_version_round_trip() {
+        let envelope = parse(VERSION, Network::Mainnet).unwrap();
+        assert_eq!(envelope.command(), "version");
+        assert_eq!(envelope.payload.len(), 101);
+        assert_eq!(encode_hex(&envelope.serialize(Network::Mainnet)), VERSION);
+
+        let version: VersionMessage = envelope.message().unwrap();
+        assert_eq!(version.user_agent, "/Satoshi:0.9.3/");
+        assert!(matches!(
+            envelope.message::<VerAckMessage>(),
+            Err(NetError::UnexpectedCommand {
+                expected: "verack",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_wrong_magic_is_rejected() {
+        assert!(matches!(
+            parse(VERACK, Network::Testnet),
+            Err(NetError::WrongNetwork { .. })
+        ));
+    }
+
+    #[test]
+    fn test_corrupted_checksum_is_rejected() {
+        let mut bytes = decode_hex(VERSION).unwrap();
+        bytes[20] ^= 1;
+        assert!(matches!(
+            NetworkEnvelope::parse(&mut bytes.as_slice(), Network::Mainnet),
+            Err(NetError::ChecksumMismatch { .. })
+        ));
+
+        let mut bytes = decode_hex(VERSION).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            NetworkEnvelope::parse(&mut bytes.as_slice(), Network::Mainnet),
+            Err(NetError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_oversized_length_is_rejected_before_reading_payload() {
+        let mut bytes = decode_hex(VERACK).unwrap();
+        bytes[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        // No payload follows: the length alone must be enough to reject the message
+        assert!(matches!(
+            NetworkEnvelope::parse(&mut bytes.as_slice(), Network::Mainnet),
+            Err(NetError::PayloadTooLarge {
+                length: u32::MAX,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_command_padding() {
+        let envelope = NetworkEnvelope::new("ping", vec![]).unwrap();
+        assert_eq!(&envelope.command, b"ping\0\0\0\0\0\0\0\0");
+        assert_eq!(envelope.command(), "ping");
+
+        let full = NetworkEnvelope::new("sendaddrv2xx", vec![]).unwrap();
+        assert_eq!(full.command(), "sendaddrv2xx");
+
+        for command in ["thirteenbytes", "pïng", "pi\0ng"] {
+            assert!(matches!(
+                NetworkEnvelope::new(command, vec![]),
+                Err(NetError::InvalidCommand)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_command_with_bytes_after_padding_is_rejected() {
+        let mut bytes = decode_hex(VERACK).unwrap();
+        bytes[14] = b'x';
+        assert!(matches!(
+            NetworkEnvelope::parse(&mut bytes.as_slice(), Network::Mainnet),
+            Err(NetError::InvalidCommand)
+        ));
+    }
+}
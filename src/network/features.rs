@@ -0,0 +1,177 @@
+use std::io::Read;
+
+use crate::encoding::read_array;
+use crate::network::envelope::NetworkEnvelope;
+use crate::network::traits::NetworkMessage;
+use crate::types::errors::NetError;
+
+// This module implements the protocol feature negotiation messages sent around the handshake.
+//
+//     sendheaders (BIP130)  announce new blocks with `headers` instead of `inv`
+//     feefilter   (BIP133)  don't relay transactions paying less than this fee rate
+//     wtxidrelay  (BIP339)  announce transactions by wtxid; must be sent before `verack`
+//
+// Which ones may be sent depends on the protocol version advertised by the peer.
+
+pub const SENDHEADERS_VERSION: i32 = 70012;
+pub const FEEFILTER_VERSION: i32 = 70013;
+pub const WTXID_RELAY_VERSION: i32 = 70016;
+// The fee rate we ask peers to filter by, Bitcoin Core's default minimum relay fee.
+pub const DEFAULT_FEE_FILTER: u64 = 1000;
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct SendHeadersMessage;
+
+impl NetworkMessage for SendHeadersMessage {
+    fn command() -> &'static str {
+        "sendheaders"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn parse<R: Read>(_reader: &mut R) -> Result<Self, NetError> {
+        Ok(SendHeadersMessage)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct WtxidRelayMessage;
+
+impl NetworkMessage for WtxidRelayMessage {
+    fn command() -> &'static str {
+        "wtxidrelay"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn parse<R: Read>(_reader: &mut R) -> Result<Self, NetError> {
+        Ok(WtxidRelayMessage)
+    }
+}
+
+// Minimum fee rate in satoshis per 1000 virtual bytes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct FeeFilterMessage {
+    pub fee_rate: u64,
+}
+
+impl FeeFilterMessage {
+    // Whether a transaction paying `fee` satoshis for `vsize` virtual bytes passes the filter.
+    pub fn accepts(&self, fee: u64, vsize: u64) -> bool {
+        fee.saturating_mul(1000) >= self.fee_rate.saturating_mul(vsize)
+    }
+}
+
+impl NetworkMessage for FeeFilterMessage {
+    fn command() -> &'static str {
+        "feefilter"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        self.fee_rate.to_le_bytes().to_vec()
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        Ok(FeeFilterMessage {
+            fee_rate: u64::from_le_bytes(read_array(reader)?),
+        })
+    }
+}
+
+// Features to negotiate with a peer given the protocol version from its `version` message.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct NegotiatedFeatures {
+    pub wtxid_relay: bool,
+    pub send_headers: bool,
+    pub fee_filter: bool,
+}
+
+impl NegotiatedFeatures {
+    pub fn for_peer_version(version: i32) -> Self {
+        NegotiatedFeatures {
+            wtxid_relay: version >= WTXID_RELAY_VERSION,
+            send_headers: version >= SENDHEADERS_VERSION,
+            fee_filter: version >= FEEFILTER_VERSION,
+        }
+    }
+
+    // Messages to send before our `verack` (BIP339 requires wtxidrelay there).
+    pub fn before_verack(&self) -> Vec<NetworkEnvelope> {
+        let mut messages = Vec::new();
+        if self.wtxid_relay {
+            messages.push(NetworkEnvelope::from_message(&WtxidRelayMessage));
+        }
+        messages
+    }
+
+    // Messages to send once the handshake is complete, asking the peer not to relay
+    // transactions below `fee_rate` satoshis per 1000 virtual bytes.
+    pub fn after_verack(&self, fee_rate: u64) -> Vec<NetworkEnvelope> {
+        let mut messages = Vec::new();
+        if self.send_headers {
+            messages.push(NetworkEnvelope::from_message(&SendHeadersMessage));
+        }
+        if self.fee_filter {
+            messages.push(NetworkEnvelope::from_message(&FeeFilterMessage {
+                fee_rate,
+            }));
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod features_tests {
+    use super::*;
+
+    fn commands(messages: &[NetworkEnvelope]) -> Vec<&str> {
+        messages.iter().map(NetworkEnvelope::command).collect()
+    }
+
+    #[test]
+    fn test_wtxidrelay_only_for_70016_and_later() {
+        let old = NegotiatedFeatures::for_peer_version(70015);
+        let new = NegotiatedFeatures::for_peer_version(70016);
+
+        assert!(old.before_verack().is_empty());
+        assert_eq!(commands(&new.before_verack()), vec!["wtxidrelay"]);
+        let after = old.after_verack(DEFAULT_FEE_FILTER);
+        assert_eq!(commands(&after), vec!["sendheaders", "feefilter"]);
+        assert_eq!(
+            after[1].message::<FeeFilterMessage>().unwrap().fee_rate,
+            DEFAULT_FEE_FILTER
+        );
+        assert!(NegotiatedFeatures::for_peer_version(70011)
+            .after_verack(DEFAULT_FEE_FILTER)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_feefilter_round_trip_and_threshold() {
+        // 5 sat/vB
+        let message = FeeFilterMessage { fee_rate: 5_000 };
+        let serialized = message.serialize();
+
+        assert_eq!(serialized, vec![0x88, 0x13, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            FeeFilterMessage::parse(&mut serialized.as_slice()).unwrap(),
+            message
+        );
+        assert!(message.accepts(705, 141));
+        assert!(!message.accepts(704, 141));
+    }
+
+    #[test]
+    fn test_empty_feature_messages() {
+        assert!(SendHeadersMessage.serialize().is_empty());
+        assert!(WtxidRelayMessage.serialize().is_empty());
+        assert_eq!(
+            SendHeadersMessage::parse(&mut &[][..]).unwrap(),
+            SendHeadersMessage
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::io::Read;
+
+use crate::block::header::BlockHeader;
+use crate::encoding::read_array;
+use crate::encoding::varint::{encode_varint, read_varint};
+use crate::network::traits::NetworkMessage;
+use crate::network::version::PROTOCOL_VERSION;
+use crate::types::errors::NetError;
+
+// This module implements the `getheaders` request used for header synchronization, and the
+// `headers` message that answers it.
+//
+// The request carries a block locator: a list of block hashes from our tip back to genesis,
+// dense near the tip and exponentially sparser further back. The peer finds the first hash
+// it knows and answers with the (up to 2000) headers following it, stopping early at
+// `hash_stop` unless it is all zeros. Hashes are in internal byte order.
+//
+// `headers` reuses the block serialization, so every header is followed by a transaction
+// count, which is always 0:
+//
+//     count (varint) | header (80) | 0x00 | header (80) | 0x00 | ...
+
+pub const MAX_HEADERS_RESULTS: u64 = 2000;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GetHeadersMessage {
+    pub version: i32,
+    pub locator: Vec<[u8; 32]>,
+    pub hash_stop: [u8; 32],
+}
+
+impl GetHeadersMessage {
+    pub fn new(locator: Vec<[u8; 32]>) -> Self {
+        GetHeadersMessage {
+            version: PROTOCOL_VERSION,
+            locator,
+            hash_stop: [0u8; 32],
+        }
+    }
+}
+
+impl NetworkMessage for GetHeadersMessage {
+    fn command() -> &'static str {
+        "getheaders"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        result.extend_from_slice(&self.version.to_le_bytes());
+        result.extend(encode_varint(self.locator.len() as u64));
+        for hash in &self.locator {
+            result.extend_from_slice(hash);
+        }
+        result.extend_from_slice(&self.hash_stop);
+        result
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        let version = i32::from_le_bytes(read_array(reader)?);
+        let count = read_varint(reader)?;
+        let locator = (0..count)
+            .map(|_| read_array(reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        let hash_stop = read_array(reader)?;
+
+        Ok(GetHeadersMessage {
+            version,
+            locator,
+            hash_stop,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct HeadersMessage {
+    pub headers: Vec<BlockHeader>,
+}
+
+impl NetworkMessage for HeadersMessage {
+    fn command() -> &'static str {
+        "headers"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut result = encode_varint(self.headers.len() as u64);
+        for header in &self.headers {
+            result.extend_from_slice(&header.serialize());
+            result.push(0);
+        }
+        result
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        let count = read_varint(reader)?;
+        if count > MAX_HEADERS_RESULTS {
+            return Err(NetError::InvalidMessage {
+                command: "headers",
+                reason: "too many headers",
+            });
+        }
+        let headers = (0..count)
+            .map(|_| {
+                let header = BlockHeader::parse(reader)?;
+                if read_varint(reader)? != 0 {
+                    return Err(NetError::InvalidMessage {
+                        command: "headers",
+                        reason: "headers must not carry transactions",
+                    });
+                }
+                Ok(header)
+            })
+            .collect::<Result<_, NetError>>()?;
+        Ok(HeadersMessage { headers })
+    }
+}
+
+// Heights whose hashes make up a block locator for a chain whose tip is at `tip_height`:
+// the most recent blocks one by one, then doubling the step back to genesis (as Core does).
+pub fn locator_heights(tip_height: u64) -> Vec<u64> {
+    let mut heights = Vec::new();
+    let mut height = tip_height;
+    let mut step = 1;
+
+    loop {
+        heights.push(height);
+        if height == 0 {
+            break;
+        }
+        height = height.saturating_sub(step);
+        if heights.len() > 10 {
+            step *= 2;
+        }
+    }
+    heights
+}
+
+#[cfg(test)]
+mod headers_tests {
+    use super::*;
+    use crate::encoding::hex::{decode_hex, encode_hex};
+    use crate::network::params::Network;
+
+    #[test]
+    fn test_serialize_getheaders_programming_bitcoin_example() {
+        let start_block: [u8; 32] =
+            decode_hex("a35bd0ca2f4a88c4eda6d213e2378a5758dfcd6af43712000000000000000000")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let message = GetHeadersMessage {
+            version: 70015,
+            ..GetHeadersMessage::new(vec![start_block])
+        };
+
+        assert_eq!(
+            encode_hex(&message.serialize()),
+            "7f11010001a35bd0ca2f4a88c4eda6d213e2378a5758dfcd6af437120000000000000000000000000000\
+             000000000000000000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_getheaders_round_trip() {
+        let message = GetHeadersMessage::new(vec![[1u8; 32], [2u8; 32], [0u8; 32]]);
+        let serialized = message.serialize();
+
+        assert_eq!(
+            GetHeadersMessage::parse(&mut serialized.as_slice()).unwrap(),
+            message
+        );
+    }
+
+    #[test]
+    fn test_headers_round_trip() {
+        let genesis = Network::Mainnet.genesis_header();
+        let message = HeadersMessage {
+            headers: vec![genesis.clone(), Network::Testnet.genesis_header()],
+        };
+        let serialized = message.serialize();
+
+        assert_eq!(serialized.len(), 1 + 2 * 81);
+        assert_eq!(serialized[1..81], genesis.serialize());
+        assert_eq!(serialized[81], 0);
+        assert_eq!(
+            HeadersMessage::parse(&mut serialized.as_slice()).unwrap(),
+            message
+        );
+    }
+
+    #[test]
+    fn test_headers_with_transactions_are_rejected() {
+        let mut serialized = HeadersMessage {
+            headers: vec![Network::Mainnet.genesis_header()],
+        }
+        .serialize();
+        serialized[81] = 1;
+
+        assert!(matches!(
+            HeadersMessage::parse(&mut serialized.as_slice()),
+            Err(NetError::InvalidMessage {
+                command: "headers",
+                reason: "headers must not carry transactions"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_locator_heights_back_off_exponentially() {
+        assert_eq!(locator_heights(0), vec![0]);
+        assert_eq!(locator_heights(5), vec![5, 4, 3, 2, 1, 0]);
+        assert_eq!(
+            locator_heights(100),
+            vec![100, 99, 98, 97, 96, 95, 94, 93, 92, 91, 90, 89, 87, 83, 75, 59, 27, 0]
+        );
+    }
+}
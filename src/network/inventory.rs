@@ -0,0 +1,260 @@
+use std::io::Read;
+
+use crate::encoding::read_array;
+use crate::encoding::varint::{encode_varint, read_varint};
+use crate::network::traits::NetworkMessage;
+use crate::types::errors::NetError;
+
+// This module implements the `inv` and `getdata` messages.
+//
+// Both carry a list of inventory vectors: a 4-byte type followed by a 32-byte hash in
+// internal byte order. A peer announces objects with `inv` and they are requested with
+// `getdata`. Unknown types are kept (so they can be ignored) rather than rejected.
+
+// Upper bound on entries per message, as enforced by Bitcoin Core.
+pub const MAX_INV_SIZE: u64 = 50_000;
+
+const WITNESS_FLAG: u32 = 1 << 30;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum InventoryType {
+    Error,
+    Tx,
+    Block,
+    FilteredBlock,
+    WitnessTx,
+    WitnessBlock,
+    Unknown(UnknownInventoryType),
+}
+
+// A type value this implementation doesn't understand. It can only be built through
+// `InventoryType::from`, so a known value like 1 can't hide as `Unknown` and compare unequal
+// to its own round trip.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct UnknownInventoryType(u32);
+
+impl UnknownInventoryType {
+    pub fn value(self) -> u32 {
+        self.0
+    }
+}
+
+impl InventoryType {
+    pub fn to_u32(self) -> u32 {
+        match self {
+            InventoryType::Error => 0,
+            InventoryType::Tx => 1,
+            InventoryType::Block => 2,
+            InventoryType::FilteredBlock => 3,
+            InventoryType::WitnessTx => WITNESS_FLAG | 1,
+            InventoryType::WitnessBlock => WITNESS_FLAG | 2,
+            InventoryType::Unknown(unknown) => unknown.value(),
+        }
+    }
+}
+
+impl From<u32> for InventoryType {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => InventoryType::Error,
+            1 => InventoryType::Tx,
+            2 => InventoryType::Block,
+            3 => InventoryType::FilteredBlock,
+            v if v == WITNESS_FLAG | 1 => InventoryType::WitnessTx,
+            v if v == WITNESS_FLAG | 2 => InventoryType::WitnessBlock,
+            value => InventoryType::Unknown(UnknownInventoryType(value)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Inventory {
+    pub inv_type: InventoryType,
+    pub hash: [u8; 32],
+}
+
+fn serialize_inventory(inventory: &[Inventory]) -> Vec<u8> {
+    let mut result = encode_varint(inventory.len() as u64);
+    for item in inventory {
+        result.extend_from_slice(&item.inv_type.to_u32().to_le_bytes());
+        result.extend_from_slice(&item.hash);
+    }
+    result
+}
+
+fn parse_inventory<R: Read>(
+    command: &'static str,
+    reader: &mut R,
+) -> Result<Vec<Inventory>, NetError> {
+    let count = read_varint(reader)?;
+    if count > MAX_INV_SIZE {
+        return Err(NetError::InvalidMessage {
+            command,
+            reason: "too many inventory entries",
+        });
+    }
+    (0..count)
+        .map(|_| {
+            let inv_type = InventoryType::from(u32::from_le_bytes(read_array(reader)?));
+            let hash = read_array(reader)?;
+            Ok(Inventory { inv_type, hash })
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct InvMessage {
+    pub inventory: Vec<Inventory>,
+}
+
+impl InvMessage {
+    // Entries of known types, skipping anything this implementation doesn't understand.
+    pub fn known(&self) -> impl Iterator<Item = &Inventory> {
+        self.inventory
+            .iter()
+            .filter(|item| !matches!(item.inv_type, InventoryType::Unknown(_)))
+    }
+}
+
+impl NetworkMessage for InvMessage {
+    fn command() -> &'static str {
+        "inv"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        serialize_inventory(&self.inventory)
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        Ok(InvMessage {
+            inventory: parse_inventory(Self::command(), reader)?,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct GetDataMessage {
+    pub inventory: Vec<Inventory>,
+}
+
+impl GetDataMessage {
+    pub fn add_data(&mut self, inv_type: InventoryType, hash: [u8; 32]) {
+        self.inventory.push(Inventory { inv_type, hash });
+    }
+}
+
+impl NetworkMessage for GetDataMessage {
+    fn command() -> &'static str {
+        "getdata"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        serialize_inventory(&self.inventory)
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        Ok(GetDataMessage {
+            inventory: parse_inventory(Self::command(), reader)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod inventory_tests {
+    use super::*;
+    use crate::encoding::hex::{decode_hex, encode_hex, hash_from_display_hex};
+
+    #[test]
+    fn test_serialize_getdata_programming_bitcoin_example() {
+        let mut message = GetDataMessage::default();
+        message.add_data(
+            InventoryType::FilteredBlock,
+            hash_from_display_hex(
+                "00000000000000cac712b726e4326e596170574c01a16001692510c44025eb30",
+            )
+            .unwrap(),
+        );
+        message.add_data(
+            InventoryType::FilteredBlock,
+            hash_from_display_hex(
+                "00000000000000beb88910c46f6b442312361c6693a7fb52065b583979844910",
+            )
+            .unwrap(),
+        );
+
+        let expected =
+            "020300000030eb2540c41025690160a1014c577061596e32e426b712c7ca00000000000000030000\
+                        001049847939585b0652fba793661c361223446b6fc41089b8be00000000000000";
+        assert_eq!(encode_hex(&message.serialize()), expected);
+        assert_eq!(
+            GetDataMessage::parse(&mut decode_hex(expected).unwrap().as_slice()).unwrap(),
+            message
+        );
+    }
+
+    #[test]
+    fn test_inventory_types_round_trip() {
+        let types = [
+            InventoryType::Error,
+            InventoryType::Tx,
+            InventoryType::Block,
+            InventoryType::FilteredBlock,
+            InventoryType::WitnessTx,
+            InventoryType::WitnessBlock,
+            InventoryType::from(5),
+        ];
+
+        for inv_type in types {
+            assert_eq!(InventoryType::from(inv_type.to_u32()), inv_type);
+        }
+        assert_eq!(InventoryType::WitnessTx.to_u32(), 0x40000001);
+    }
+
+    #[test]
+    fn test_known_values_never_decode_as_unknown() {
+        for value in [0, 1, 2, 3, 4, 0x40000001, 0x40000002, 0x40000003, u32::MAX] {
+            let inv_type = InventoryType::from(value);
+            assert_eq!(inv_type.to_u32(), value);
+            assert_eq!(InventoryType::from(inv_type.to_u32()), inv_type);
+        }
+        assert_eq!(InventoryType::from(1), InventoryType::Tx);
+        assert!(matches!(
+            InventoryType::from(4),
+            InventoryType::Unknown(unknown) if unknown.value() == 4
+        ));
+    }
+
+    #[test]
+    fn test_inv_skips_unknown_types() {
+        let message = InvMessage {
+            inventory: vec![
+                Inventory {
+                    inv_type: InventoryType::from(0x1234),
+                    hash: [1u8; 32],
+                },
+                Inventory {
+                    inv_type: InventoryType::Tx,
+                    hash: [2u8; 32],
+                },
+            ],
+        };
+        let parsed = InvMessage::parse(&mut message.serialize().as_slice()).unwrap();
+
+        assert_eq!(parsed, message);
+        assert_eq!(parsed.known().count(), 1);
+        assert_eq!(parsed.known().next().unwrap().hash, [2u8; 32]);
+    }
+
+    #[test]
+    fn test_inv_rejects_oversized_count() {
+        let payload = encode_varint(MAX_INV_SIZE + 1);
+
+        assert!(matches!(
+            InvMessage::parse(&mut payload.as_slice()),
+            Err(NetError::InvalidMessage {
+                command: "inv",
+                reason: "too many inventory entries"
+            })
+        ));
+    }
+}
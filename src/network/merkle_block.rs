@@ -0,0 +1,315 @@
+use std::io::Read;
+
+use crate::block::header::BlockHeader;
+use crate::block::merkle::merkle_parent;
+use crate::encoding::varint::{encode_varint, read_varint};
+use crate::encoding::{read_array, read_vec};
+use crate::network::traits::NetworkMessage;
+use crate::types::errors::NetError;
+
+// This module implements the BIP37 `merkleblock` message: a block header plus the partial
+// merkle tree proving which of its transactions matched our bloom filter.
+//
+// The tree is walked depth-first from the root. Each visited node consumes one flag bit:
+// 0 means the node's hash is given and nothing below it matters, 1 means a leaf that matched
+// (its txid is given) or an inner node to descend into. Hashes are consumed in the same order
+// as they are needed. A valid message uses every hash, and every flag byte, exactly.
+
+// Upper bound on transactions per block: the maximum block weight divided by the weight of
+// the smallest transaction.
+pub const MAX_TRANSACTIONS: u32 = 4_000_000 / 240;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MerkleBlock {
+    pub header: BlockHeader,
+    pub total_transactions: u32,
+    pub hashes: Vec<[u8; 32]>,
+    pub flags: Vec<u8>,
+}
+
+struct PartialMerkleTree<'a> {
+    total_transactions: u32,
+    height: u32,
+    hashes: &'a [[u8; 32]],
+    flags: &'a [u8],
+    hashes_used: usize,
+    bits_used: usize,
+    matched: Vec<[u8; 32]>,
+}
+
+impl PartialMerkleTree<'_> {
+    // Number of nodes at `depth`, where the leaves are at depth `self.height`.
+    fn width(&self, depth: u32) -> u64 {
+        let shift = self.height - depth;
+        (u64::from(self.total_transactions) + (1 << shift) - 1) >> shift
+    }
+
+    fn next_bit(&mut self) -> Result<bool, NetError> {
+        let byte = self
+            .flags
+            .get(self.bits_used / 8)
+            .ok_or_else(|| invalid("not enough flag bits"))?;
+        let bit = (byte >> (self.bits_used % 8)) & 1 == 1;
+        self.bits_used += 1;
+        Ok(bit)
+    }
+
+    fn next_hash(&mut self) -> Result<[u8; 32], NetError> {
+        let hash = *self
+            .hashes
+            .get(self.hashes_used)
+            .ok_or_else(|| invalid("not enough hashes"))?;
+        self.hashes_used += 1;
+        Ok(hash)
+    }
+
+    fn walk(&mut self, depth: u32, position: u64) -> Result<[u8; 32], NetError> {
+        let descend = self.next_bit()?;
+        if depth == self.height || !descend {
+            let hash = self.next_hash()?;
+            if depth == self.height && descend {
+                self.matched.push(hash);
+            }
+            return Ok(hash);
+        }
+
+        let left = self.walk(depth + 1, position * 2)?;
+        let right = if position * 2 + 1 < self.width(depth + 1) {
+            let right = self.walk(depth + 1, position * 2 + 1)?;
+            // An odd level duplicates its last hash itself; a right child equal to its
+            // sibling means someone duplicated a transaction (CVE-2012-2459)
+            if right == left {
+                return Err(invalid("duplicated hash in the tree"));
+            }
+            right
+        } else {
+            left
+        };
+        Ok(merkle_parent(&left, &right))
+    }
+}
+
+fn invalid(reason: &'static str) -> NetError {
+    NetError::InvalidMessage {
+        command: "merkleblock",
+        reason,
+    }
+}
+
+impl MerkleBlock {
+    // Rebuilds the merkle root from the partial tree, checks it against the header and
+    // returns the matched txids, in block order and internal byte order.
+    pub fn matched_txids(&self) -> Result<Vec<[u8; 32]>, NetError> {
+        if self.total_transactions == 0 || self.total_transactions > MAX_TRANSACTIONS {
+            return Err(invalid("invalid transaction count"));
+        }
+        if self.hashes.len() > self.total_transactions as usize {
+            return Err(invalid("more hashes than transactions"));
+        }
+        if self.flags.len() * 8 < self.hashes.len() {
+            return Err(invalid("fewer flag bits than hashes"));
+        }
+
+        let mut tree = PartialMerkleTree {
+            total_transactions: self.total_transactions,
+            height: u32::BITS - (self.total_transactions - 1).leading_zeros(),
+            hashes: &self.hashes,
+            flags: &self.flags,
+            hashes_used: 0,
+            bits_used: 0,
+            matched: Vec::new(),
+        };
+        let root = tree.walk(0, 0)?;
+
+        if tree.hashes_used != self.hashes.len() {
+            return Err(invalid("unconsumed hashes"));
+        }
+        if tree.bits_used.div_ceil(8) != self.flags.len() {
+            return Err(invalid("unconsumed flag bits"));
+        }
+        if root != self.header.merkle_root {
+            return Err(invalid("merkle root does not match the header"));
+        }
+        Ok(tree.matched)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.matched_txids().is_ok()
+    }
+}
+
+impl NetworkMessage for MerkleBlock {
+    fn command() -> &'static str {
+        "merkleblock"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut result = self.header.serialize().to_vec();
+        result.extend_from_slice(&self.total_transactions.to_le_bytes());
+        result.extend(encode_varint(self.hashes.len() as u64));
+        for hash in &self.hashes {
+            result.extend_from_slice(hash);
+        }
+        result.extend(encode_varint(self.flags.len() as u64));
+        result.extend_from_slice(&self.flags);
+        result
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        let header = BlockHeader::parse(reader)?;
+        let total_transactions = u32::from_le_bytes(read_array(reader)?);
+
+        let hash_count = read_varint(reader)?;
+        if hash_count > u64::from(MAX_TRANSACTIONS) {
+            return Err(invalid("too many hashes"));
+        }
+        let hashes = (0..hash_count)
+            .map(|_| read_array(reader))
+            .collect::<Result<_, _>>()?;
+
+        let flag_count = read_varint(reader)?;
+        if flag_count > u64::from(MAX_TRANSACTIONS).div_ceil(8) * 2 {
+            return Err(invalid("too many flag bytes"));
+        }
+        let flags = read_vec(reader, flag_count as usize)?;
+
+        Ok(MerkleBlock {
+            header,
+            total_transactions,
+            hashes,
+            flags,
+        })
+    }
+}
+
+#[cfg(test)]
+mod merkle_block_tests {
+    use super::*;
+    use crate::block::merkle::merkle_root;
+    use crate::encoding::hex::{decode_hex, encode_hex};
+
+    // From Programming Bitcoin chapter 11.
+    const MERKLE_BLOCK: &str = "00000020df3b053dc46f162a9b00c7f0d5124e2676d47bbe7c5d0793a500000000000000ef445fef2ed495c275892206ca533e7411907971013ab83e3b47bd0d692d14d4dc7c835b67d8001ac157e670bf0d00000aba412a0d1480e370173072c9562becffe87aa661c1e4a6dbc305d38ec5dc088a7cf92e6458aca7b32edae818f9c2c98c37e06bf72ae0ce80649a38655ee1e27d34d9421d940b16732f24b94023e9d572a7f9ab8023434a4feb532d2adfc8c2c2158785d1bd04eb99df2e86c54bc13e139862897217400def5d72c280222c4cbaee7261831e1550dbb8fa82853e9fe506fc5fda3f7b919d8fe74b6282f92763cef8e625f977af7c8619c32a369b832bc2d051ecd9c73c51e76370ceabd4f25097c256597fa898d404ed53425de608ac6bfe426f6e2bb457f1c554866eb69dcb8d6bf6f880e9a59b3cd053e6c7060eeacaacf4dac6697dac20e4bd3f38a2ea2543d1ab7953e3430790a9f81e1c67f5b58c825acf46bd02848384eebe9af917274cdfbb1a28a5d58a23a17977def0de10d644258d9c54f886d47d293a411cb6226103b55635";
+
+    fn fixture() -> MerkleBlock {
+        MerkleBlock::parse(&mut decode_hex(MERKLE_BLOCK).unwrap().as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let merkle_block = fixture();
+        assert_eq!(merkle_block.total_transactions, 3519);
+        assert_eq!(merkle_block.hashes.len(), 10);
+        assert_eq!(merkle_block.flags, vec![0xb5, 0x56, 0x35]);
+        assert_eq!(
+            merkle_block.header.display_hash(),
+            "00000000000000cac712b726e4326e596170574c01a16001692510c44025eb30"
+        );
+        assert_eq!(encode_hex(&merkle_block.serialize()), MERKLE_BLOCK);
+    }
+
+    #[test]
+    fn test_fixture_yields_matched_txid() {
+        let mut matched = fixture().matched_txids().unwrap();
+        assert_eq!(matched.len(), 1);
+        matched[0].reverse();
+        assert_eq!(
+            encode_hex(&matched[0]),
+            "6122b61c413a297dd486f8549c8d2544d610def0de7779a1238ad5a5281abbdf"
+        );
+    }
+
+    #[test]
+    fn test_mutated_flags_are_rejected() {
+        let matched = fixture().matched_txids().unwrap();
+        for bit in 0..22 {
+            let mut merkle_block = fixture();
+            merkle_block.flags[bit / 8] ^= 1 << (bit % 8);
+            // A leaf's bit only says whether it matched, so flipping it changes the matches
+            if let Ok(mutated) = merkle_block.matched_txids() {
+                assert_ne!(mutated, matched, "flipping bit {bit}");
+            }
+        }
+        let mut merkle_block = fixture();
+        merkle_block.flags[0] ^= 1;
+        assert!(!merkle_block.is_valid());
+    }
+
+    #[test]
+    fn test_extra_hash_is_rejected() {
+        let mut merkle_block = fixture();
+        merkle_block.hashes.push([0; 32]);
+        assert!(matches!(
+            merkle_block.matched_txids(),
+            Err(NetError::InvalidMessage {
+                reason: "unconsumed hashes",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_extra_flag_byte_is_rejected() {
+        let mut merkle_block = fixture();
+        merkle_block.flags.push(0);
+        assert!(matches!(
+            merkle_block.matched_txids(),
+            Err(NetError::InvalidMessage {
+                reason: "unconsumed flag bits",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_inconsistent_shape_is_rejected() {
+        let mut merkle_block = fixture();
+        merkle_block.total_transactions = 0;
+        assert!(!merkle_block.is_valid());
+
+        let mut merkle_block = fixture();
+        merkle_block.total_transactions = 5;
+        assert!(!merkle_block.is_valid());
+
+        let mut merkle_block = fixture();
+        merkle_block.header.merkle_root[0] ^= 1;
+        assert!(matches!(
+            merkle_block.matched_txids(),
+            Err(NetError::InvalidMessage {
+                reason: "merkle root does not match the header",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_duplicated_leaf_is_rejected() {
+        // Three transactions padded into four by duplicating the last one, as in
+        // CVE-2012-2459
+        let txids = [[1u8; 32], [2; 32], [3; 32], [3; 32]];
+        let mut header = fixture().header;
+        header.merkle_root = merkle_root(&txids).unwrap();
+        let merkle_block = MerkleBlock {
+            header,
+            total_transactions: 4,
+            hashes: txids.to_vec(),
+            // Descend everywhere: 3 inner nodes and 4 matched leaves
+            flags: vec![0x7f],
+        };
+        assert!(matches!(
+            merkle_block.matched_txids(),
+            Err(NetError::InvalidMessage {
+                reason: "duplicated hash in the tree",
+                ..
+            })
+        ));
+
+        let honest = MerkleBlock {
+            total_transactions: 3,
+            hashes: txids[..3].to_vec(),
+            flags: vec![0x3f],
+            ..merkle_block
+        };
+        assert_eq!(honest.matched_txids().unwrap(), txids[..3].to_vec());
+    }
+}
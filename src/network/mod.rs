@@ -0,0 +1,14 @@
+pub mod addr;
+pub mod bloom_filter;
+pub mod discovery;
+pub mod envelope;
+pub mod features;
+pub mod headers;
+pub mod inventory;
+pub mod merkle_block;
+pub mod net_addr;
+pub mod node;
+pub mod params;
+pub mod ping;
+pub mod traits;
+pub mod version;
@@ -0,0 +1,93 @@
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::encoding::read_array;
+use crate::types::errors::NetError;
+
+// This module implements `NetAddr`, the network address structure embedded in P2P messages.
+//
+// Addresses are always sent as 16 bytes: IPv4 addresses use the IPv4-mapped IPv6 form
+// ::ffff:a.b.c.d. Services are little-endian like the rest of the protocol, but the port is
+// big-endian (network byte order).
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct NetAddr {
+    pub services: u64,
+    pub ip: [u8; 16],
+    pub port: u16,
+}
+
+impl NetAddr {
+    pub fn new(ip: IpAddr, port: u16, services: u64) -> Self {
+        let ip = match ip {
+            IpAddr::V4(ip) => ip.to_ipv6_mapped().octets(),
+            IpAddr::V6(ip) => ip.octets(),
+        };
+        NetAddr { services, ip, port }
+    }
+
+    // IPv4-mapped addresses are returned as IPv4.
+    pub fn ip(&self) -> IpAddr {
+        let ip = Ipv6Addr::from(self.ip);
+        match ip.to_ipv4_mapped() {
+            Some(ip) => IpAddr::V4(ip),
+            None => IpAddr::V6(ip),
+        }
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(26);
+        result.extend_from_slice(&self.services.to_le_bytes());
+        result.extend_from_slice(&self.ip);
+        result.extend_from_slice(&self.port.to_be_bytes());
+        result
+    }
+
+    pub fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        let services = u64::from_le_bytes(read_array(reader)?);
+        let ip = read_array(reader)?;
+        let port = u16::from_be_bytes(read_array(reader)?);
+        Ok(NetAddr { services, ip, port })
+    }
+}
+
+impl Default for NetAddr {
+    fn default() -> Self {
+        NetAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 8333, 0)
+    }
+}
+
+#[cfg(test)]
+mod net_addr_tests {
+    use super::*;
+
+    #[test]
+    fn test_ipv4_is_mapped_into_ipv6() {
+        let addr = NetAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), 8333, 1);
+
+        assert_eq!(
+            addr.ip,
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 10, 0, 0, 1]
+        );
+        assert_eq!(addr.ip(), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+    }
+
+    #[test]
+    fn test_ipv6_is_kept() {
+        let ip: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        let addr = NetAddr::new(IpAddr::V6(ip), 18333, 0);
+
+        assert_eq!(addr.ip(), IpAddr::V6(ip));
+    }
+
+    #[test]
+    fn test_serialize_parse_round_trip() {
+        let addr = NetAddr::new(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 5)), 8333, 0x409);
+        let serialized = addr.serialize();
+
+        assert_eq!(serialized.len(), 26);
+        // Port is big-endian
+        assert_eq!(&serialized[24..], &[0x20, 0x8d]);
+        assert_eq!(NetAddr::parse(&mut serialized.as_slice()).unwrap(), addr);
+    }
+}
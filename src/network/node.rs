@@ -0,0 +1,1044 @@
+use std::io::{BufReader, ErrorKind, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::block::header::BlockHeader;
+use crate::block::header_chain::{HeaderChain, HeaderStatus};
+use crate::encoding::read_array;
+use crate::network::addr::{AddrBook, AddrMessage, AddrV2Message, GetAddrMessage};
+use crate::network::envelope::{NetworkEnvelope, MAX_PAYLOAD_SIZE};
+use crate::network::features::{FeeFilterMessage, NegotiatedFeatures, DEFAULT_FEE_FILTER};
+use crate::network::headers::{GetHeadersMessage, HeadersMessage, MAX_HEADERS_RESULTS};
+use crate::network::params::Network;
+use crate::network::ping::{PingMessage, PongMessage};
+use crate::network::traits::NetworkMessage;
+use crate::network::version::{VerAckMessage, VersionMessage, PROTOCOL_VERSION};
+use crate::types::errors::{BlockError, EncodingError, NetError};
+
+// This module implements `SimpleNode`, a blocking connection to a single peer.
+//
+// Messages are exchanged as envelopes over a `TcpStream`. While waiting for a given message
+// the node handles what the peer sends unprompted: it answers `ping`s (peers drop connections
+// that don't), records the peer's `feefilter`, keeps announced `headers` for
+// `connect_announced`, and skips anything else, like `inv` announcements. Connecting,
+// reading and writing each have a timeout, so a silent peer surfaces as `NetError::Timeout`
+// instead of hanging forever. In keepalive mode, a peer that stays quiet for a whole read
+// timeout is pinged first, and only counts as gone if it is still silent after another one.
+//
+// Malformed input doesn't end the conversation. Every frame or payload the peer gets wrong
+// is reported as a typed error and adds to its misbehavior score, and `wait_for` skips it.
+// Once the score reaches `MISBEHAVIOR_THRESHOLD` the connection is shut down, like Bitcoin
+// Core bans peers at 100 points. A frame with the wrong magic or a broken header leaves the
+// stream misaligned, so the next read first scans ahead for the network magic. The scan
+// gives up after `MAX_PAYLOAD_SIZE` bytes, the most a frame could have needed, and scores the
+// peer like an oversized payload.
+//
+// The handshake negotiates the optional features the peer's protocol version supports:
+// `wtxidrelay` goes before our `verack`, `sendheaders` and `feefilter` after the peer's.
+//
+// Headers are synchronized in batches: `getheaders` with the locator of our tip, then the
+// `headers` answer is validated through the `HeaderChain`, until the peer has nothing more.
+
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+pub const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub const MISBEHAVIOR_THRESHOLD: u32 = 100;
+pub const CHECKSUM_PENALTY: u32 = 10;
+pub const UNPARSEABLE_PENALTY: u32 = 20;
+pub const OVERSIZED_PENALTY: u32 = 50;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Timeouts {
+    pub connect: Duration,
+    pub read: Duration,
+    pub write: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts {
+            connect: DEFAULT_CONNECT_TIMEOUT,
+            read: DEFAULT_READ_TIMEOUT,
+            write: DEFAULT_WRITE_TIMEOUT,
+        }
+    }
+}
+
+// What the peer said about itself in its `version` message.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PeerInfo {
+    pub version: i32,
+    pub services: u64,
+    pub start_height: i32,
+    pub user_agent: String,
+}
+
+pub struct SimpleNode {
+    network: Network,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    peer: Option<PeerInfo>,
+    misbehavior: u32,
+    keepalive: bool,
+    features: Option<NegotiatedFeatures>,
+    peer_fee_filter: Option<FeeFilterMessage>,
+    // Headers the peer announced unprompted, waiting for `connect_announced`.
+    announced: Vec<BlockHeader>,
+    // Peers learned from the `addr` and `addrv2` gossip of this one.
+    addr_book: AddrBook,
+    // The last 4 bytes seen while looking for the magic of the next frame, if the stream is
+    // misaligned.
+    resync: Option<[u8; 4]>,
+    // Bytes skipped by the current scan, across reads cut short by a timeout.
+    resync_skipped: u32,
+}
+
+impl SimpleNode {
+    pub fn connect(host: &str, port: u16, network: Network) -> Result<Self, NetError> {
+        SimpleNode::connect_with_timeouts(host, port, network, Timeouts::default())
+    }
+
+    // Tries every address `host` resolves to, returning the last error if none accepts.
+    pub fn connect_with_timeouts(
+        host: &str,
+        port: u16,
+        network: Network,
+        timeouts: Timeouts,
+    ) -> Result<Self, NetError> {
+        let mut last_error = None;
+        for address in (host, port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, timeouts.connect) {
+                Ok(stream) => return SimpleNode::from_stream(stream, network, timeouts),
+                Err(error) => last_error = Some(error),
+            }
+        }
+        let error = last_error.unwrap_or_else(|| ErrorKind::AddrNotAvailable.into());
+        Err(timed_out(error.into()))
+    }
+
+    pub fn connect_to(
+        address: &SocketAddr,
+        network: Network,
+        timeouts: Timeouts,
+    ) -> Result<Self, NetError> {
+        let stream = TcpStream::connect_timeout(address, timeouts.connect)
+            .map_err(|error| timed_out(error.into()))?;
+        SimpleNode::from_stream(stream, network, timeouts)
+    }
+
+    fn from_stream(
+        stream: TcpStream,
+        network: Network,
+        timeouts: Timeouts,
+    ) -> Result<Self, NetError> {
+        stream.set_read_timeout(Some(timeouts.read))?;
+        stream.set_write_timeout(Some(timeouts.write))?;
+        Ok(SimpleNode {
+            network,
+            writer: stream.try_clone()?,
+            reader: BufReader::new(stream),
+            peer: None,
+            misbehavior: 0,
+            keepalive: false,
+            features: None,
+            peer_fee_filter: None,
+            announced: Vec::new(),
+            addr_book: AddrBook::new(),
+            resync: None,
+            resync_skipped: 0,
+        })
+    }
+
+    pub fn set_read_timeout(&self, read_timeout: Duration) -> Result<(), NetError> {
+        Ok(self.writer.set_read_timeout(Some(read_timeout))?)
+    }
+
+    pub fn set_write_timeout(&self, write_timeout: Duration) -> Result<(), NetError> {
+        Ok(self.writer.set_write_timeout(Some(write_timeout))?)
+    }
+
+    // With Some(interval), the peer is pinged after `interval` of silence, which becomes the
+    // read timeout. None stops the pings and keeps the current read timeout.
+    pub fn set_keepalive(&mut self, interval: Option<Duration>) -> Result<(), NetError> {
+        if let Some(interval) = interval {
+            self.set_read_timeout(interval)?;
+        }
+        self.keepalive = interval.is_some();
+        Ok(())
+    }
+
+    // None until the handshake received the peer's `version`.
+    pub fn peer(&self) -> Option<&PeerInfo> {
+        self.peer.as_ref()
+    }
+
+    pub fn misbehavior(&self) -> u32 {
+        self.misbehavior
+    }
+
+    // None until the handshake received the peer's `version`.
+    pub fn features(&self) -> Option<&NegotiatedFeatures> {
+        self.features.as_ref()
+    }
+
+    // The minimum fee rate the peer relays, once it sent a `feefilter`.
+    pub fn peer_fee_filter(&self) -> Option<&FeeFilterMessage> {
+        self.peer_fee_filter.as_ref()
+    }
+
+    pub fn addr_book(&self) -> &AddrBook {
+        &self.addr_book
+    }
+
+    pub fn send<M: NetworkMessage>(&mut self, message: &M) -> Result<(), NetError> {
+        self.send_envelope(&NetworkEnvelope::from_message(message))
+    }
+
+    pub fn send_envelope(&mut self, envelope: &NetworkEnvelope) -> Result<(), NetError> {
+        self.check_connected()?;
+        self.writer
+            .write_all(&envelope.serialize(self.network))
+            .map_err(|error| timed_out(error.into()))
+    }
+
+    // Reads the next frame. Malformed frames are scored before their error is returned.
+    pub fn read(&mut self) -> Result<NetworkEnvelope, NetError> {
+        self.check_connected()?;
+        let result = self.read_frame().map_err(timed_out);
+        if let Err(error) = &result {
+            match error {
+                NetError::WrongNetwork { actual, .. } => self.resync = Some(*actual),
+                NetError::InvalidCommand | NetError::PayloadTooLarge { .. } => {
+                    self.resync = Some([0; 4])
+                }
+                _ => {}
+            }
+            if let Some(penalty) = frame_penalty(error) {
+                self.misbehaving(penalty)?;
+            }
+        }
+        result
+    }
+
+    // Adds `penalty` to the peer's score, disconnecting once it reaches the threshold.
+    pub fn misbehaving(&mut self, penalty: u32) -> Result<(), NetError> {
+        self.misbehavior = self.misbehavior.saturating_add(penalty);
+        if self.misbehavior >= MISBEHAVIOR_THRESHOLD {
+            // The peer may already be gone, which is what we want anyway
+            let _ = self.writer.shutdown(Shutdown::Both);
+        }
+        self.check_connected()
+    }
+
+    // Reads until the peer sends one of `commands`, handling unsolicited messages along the
+    // way. Malformed frames and payloads are skipped.
+    pub fn wait_for(&mut self, commands: &[&str]) -> Result<NetworkEnvelope, NetError> {
+        let mut pinged = false;
+        loop {
+            let envelope = match self.read() {
+                Ok(envelope) => envelope,
+                Err(error) if frame_penalty(&error).is_some() => continue,
+                Err(NetError::Timeout) if self.keepalive && !pinged => {
+                    // The timeout may have cut a frame short
+                    self.resync.get_or_insert([0; 4]);
+                    self.send(&PingMessage {
+                        nonce: ping_nonce(),
+                    })?;
+                    pinged = true;
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+            pinged = false;
+            if commands.contains(&envelope.command()) {
+                return Ok(envelope);
+            }
+            self.handle_unsolicited(&envelope)?;
+        }
+    }
+
+    fn handle_unsolicited(&mut self, envelope: &NetworkEnvelope) -> Result<(), NetError> {
+        let handled = match envelope.command() {
+            "ping" => envelope
+                .message::<PingMessage>()
+                .map(|ping| Some(NetworkEnvelope::from_message(&ping.pong()))),
+            "feefilter" => envelope.message().map(|filter| {
+                self.peer_fee_filter = Some(filter);
+                None
+            }),
+            "headers" => envelope.message::<HeadersMessage>().map(|message| {
+                let room = MAX_HEADERS_RESULTS as usize - self.announced.len();
+                self.announced
+                    .extend(message.headers.into_iter().take(room));
+                None
+            }),
+            "addr" => envelope.message::<AddrMessage>().map(|message| {
+                self.addr_book.add_addr(&message);
+                None
+            }),
+            "addrv2" => envelope.message::<AddrV2Message>().map(|message| {
+                self.addr_book.add_addrv2(&message);
+                None
+            }),
+            "getaddr" => envelope.message::<GetAddrMessage>().map(|_| {
+                Some(NetworkEnvelope::from_message(
+                    &self.addr_book.addr_message(),
+                ))
+            }),
+            _ => Ok(None),
+        };
+        match handled {
+            Ok(Some(reply)) => self.send_envelope(&reply),
+            Ok(None) => Ok(()),
+            Err(_) => self.misbehaving(UNPARSEABLE_PENALTY),
+        }
+    }
+
+    // Sends a `ping` with a fresh nonce and returns the time until the `pong` echoing it.
+    // Pongs with other nonces are ignored, and if the matching one doesn't come within the
+    // read timeout the result is `Timeout`.
+    pub fn ping(&mut self) -> Result<Duration, NetError> {
+        let ping = PingMessage {
+            nonce: ping_nonce(),
+        };
+        let timeout = self.writer.read_timeout()?.unwrap_or(DEFAULT_READ_TIMEOUT);
+        let start = Instant::now();
+        self.send(&ping)?;
+        loop {
+            let envelope = self.wait_for(&[PongMessage::command()])?;
+            match envelope.message::<PongMessage>() {
+                Ok(pong) if pong.answers(&ping) => return Ok(start.elapsed()),
+                Ok(_) => {}
+                Err(_) => self.misbehaving(UNPARSEABLE_PENALTY)?,
+            }
+            if start.elapsed() >= timeout {
+                return Err(NetError::Timeout);
+            }
+        }
+    }
+
+    // Exchanges `version` and `verack` with the peer, in whatever order it sends them.
+    pub fn handshake(&mut self) -> Result<&PeerInfo, NetError> {
+        self.send(&VersionMessage::default())?;
+
+        let mut verack_received = false;
+        while self.peer.is_none() || !verack_received {
+            let envelope = self.wait_for(&[VersionMessage::command(), VerAckMessage::command()])?;
+            if envelope.command() == VerAckMessage::command() {
+                verack_received = true;
+                continue;
+            }
+            let version: VersionMessage = match envelope.message() {
+                Ok(version) => version,
+                Err(_) => {
+                    self.misbehaving(UNPARSEABLE_PENALTY)?;
+                    continue;
+                }
+            };
+            let features =
+                NegotiatedFeatures::for_peer_version(version.version.min(PROTOCOL_VERSION));
+            self.peer = Some(PeerInfo {
+                version: version.version,
+                services: version.services,
+                start_height: version.start_height,
+                user_agent: version.user_agent,
+            });
+            self.features = Some(features);
+            for envelope in features.before_verack() {
+                self.send_envelope(&envelope)?;
+            }
+            self.send(&VerAckMessage)?;
+        }
+
+        let features = self.features.unwrap();
+        for envelope in features.after_verack(DEFAULT_FEE_FILTER) {
+            self.send_envelope(&envelope)?;
+        }
+        Ok(self.peer.as_ref().unwrap())
+    }
+
+    // Downloads headers from the peer into `chain`, until the peer answers with no new
+    // headers or, with `until`, the chain reaches that height. Every request starts from the
+    // locator of the tip reached so far. Returns the height of the chain.
+    pub fn sync_headers(
+        &mut self,
+        chain: &mut HeaderChain,
+        until: Option<u64>,
+    ) -> Result<u64, NetError> {
+        while until.is_none_or(|height| chain.height() < height) {
+            let tip = chain.tip_hash();
+            self.send(&GetHeadersMessage::new(chain.locator()))?;
+            let envelope = self.wait_for(&[HeadersMessage::command()])?;
+            let headers = match envelope.message::<HeadersMessage>() {
+                Ok(message) => message.headers,
+                Err(error) => {
+                    self.misbehaving(UNPARSEABLE_PENALTY)?;
+                    return Err(error);
+                }
+            };
+            for header in headers {
+                accept_header(chain, header)?;
+                if until.is_some_and(|height| chain.height() >= height) {
+                    break;
+                }
+            }
+            // An empty batch, or one that only repeats what we have
+            if chain.tip_hash() == tip {
+                break;
+            }
+        }
+        Ok(chain.height())
+    }
+
+    // Connects the headers the peer announced since the last call. An announcement that
+    // doesn't connect means we are behind the peer, so the missing headers are synchronized
+    // from it. Returns the height of the chain.
+    pub fn connect_announced(&mut self, chain: &mut HeaderChain) -> Result<u64, NetError> {
+        for header in std::mem::take(&mut self.announced) {
+            if !chain.is_known(&header.prev_blockhash) {
+                return self.sync_headers(chain, None);
+            }
+            accept_header(chain, header)?;
+        }
+        Ok(chain.height())
+    }
+
+    fn check_connected(&self) -> Result<(), NetError> {
+        if self.misbehavior >= MISBEHAVIOR_THRESHOLD {
+            return Err(NetError::Misbehaving {
+                score: self.misbehavior,
+            });
+        }
+        Ok(())
+    }
+
+    fn read_frame(&mut self) -> Result<NetworkEnvelope, NetError> {
+        if self.resync.is_none() {
+            return NetworkEnvelope::parse(&mut self.reader, self.network);
+        }
+        let magic = self.network.magic();
+        while let Some(window) = self.resync.as_mut() {
+            if *window == magic {
+                self.resync = None;
+                self.resync_skipped = 0;
+                break;
+            }
+            if self.resync_skipped >= MAX_PAYLOAD_SIZE {
+                self.resync_skipped = 0;
+                return Err(NetError::MagicNotFound {
+                    skipped: MAX_PAYLOAD_SIZE,
+                });
+            }
+            let [byte] = read_array(&mut self.reader)?;
+            self.resync_skipped += 1;
+            window.rotate_left(1);
+            window[3] = byte;
+        }
+        NetworkEnvelope::parse(&mut magic.as_slice().chain(&mut self.reader), self.network)
+    }
+}
+
+// The score a malformed frame earns, None for errors that aren't the peer's fault.
+fn frame_penalty(error: &NetError) -> Option<u32> {
+    match error {
+        NetError::ChecksumMismatch { .. } => Some(CHECKSUM_PENALTY),
+        NetError::WrongNetwork { .. } | NetError::InvalidCommand => Some(UNPARSEABLE_PENALTY),
+        NetError::PayloadTooLarge { .. } | NetError::MagicNotFound { .. } => {
+            Some(OVERSIZED_PENALTY)
+        }
+        _ => None,
+    }
+}
+
+// Accepts a header from the peer, which must connect to a block `chain` already knows: a
+// batch of headers is sent in order, so an orphan means the peer broke the chain.
+fn accept_header(chain: &mut HeaderChain, header: BlockHeader) -> Result<HeaderStatus, NetError> {
+    let hash = header.display_hash();
+    let result = if chain.is_known(&header.prev_blockhash) {
+        chain.accept(header, unix_time())
+    } else {
+        Err(BlockError::UnknownParent)
+    };
+    result.map_err(|source| NetError::RejectedHeader { hash, source })
+}
+
+// Only needs to tell our pings apart.
+fn ping_nonce() -> [u8; 8] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    nanos.to_le_bytes()
+}
+
+// The local clock stands in for the network-adjusted time.
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn timed_out(error: NetError) -> NetError {
+    let io = match &error {
+        NetError::Io(io) | NetError::Encoding(EncodingError::Io(io)) => io,
+        _ => return error,
+    };
+    if matches!(io.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) {
+        NetError::Timeout
+    } else {
+        error
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod node_tests {
+    use super::*;
+    use crate::block::header_chain::header_chain_tests::{
+        extend, genesis, retargeting_params, EASY_BITS,
+    };
+    use crate::network::headers::locator_heights;
+    use crate::network::net_addr::NetAddr;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    pub(crate) type PeerThread = thread::JoinHandle<Vec<NetworkEnvelope>>;
+
+    // A peer on localhost that answers the handshake with `script` and returns the commands
+    // it received.
+    pub(crate) fn mock_peer<F>(script: F) -> (u16, PeerThread)
+    where
+        F: FnOnce(&mut TcpStream) + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            script(&mut stream);
+            // Collect what the node sends until it hangs up
+            let mut received = Vec::new();
+            while let Ok(envelope) = NetworkEnvelope::parse(&mut stream, Network::Regtest) {
+                received.push(envelope);
+            }
+            received
+        });
+        (port, handle)
+    }
+
+    pub(crate) fn send<M: NetworkMessage>(stream: &mut TcpStream, message: &M) {
+        let envelope = NetworkEnvelope::from_message(message);
+        stream
+            .write_all(&envelope.serialize(Network::Regtest))
+            .unwrap();
+    }
+
+    fn send_raw(stream: &mut TcpStream, bytes: &[u8]) {
+        stream.write_all(bytes).unwrap();
+    }
+
+    // A valid `verack` frame with `edit` applied to its bytes.
+    fn malformed(edit: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+        let mut frame = NetworkEnvelope::from_message(&VerAckMessage).serialize(Network::Regtest);
+        edit(&mut frame);
+        frame
+    }
+
+    fn bad_checksum() -> Vec<u8> {
+        malformed(|frame| frame[20] ^= 1)
+    }
+
+    fn bad_command() -> Vec<u8> {
+        malformed(|frame| frame[4] = 0x01)
+    }
+
+    fn oversized() -> Vec<u8> {
+        malformed(|frame| frame[16..20].copy_from_slice(&u32::MAX.to_le_bytes()))
+    }
+
+    fn garbled_ping() -> Vec<u8> {
+        NetworkEnvelope::new("ping", vec![1, 2, 3])
+            .unwrap()
+            .serialize(Network::Regtest)
+    }
+
+    pub(crate) fn peer_version() -> VersionMessage {
+        VersionMessage {
+            version: 70015,
+            services: 1 | 8,
+            start_height: 812_345,
+            user_agent: "/mock:1.0/".to_string(),
+            ..VersionMessage::default()
+        }
+    }
+
+    #[test]
+    fn test_handshake_with_mock_peer() {
+        let (port, peer) = mock_peer(|stream| {
+            let version = NetworkEnvelope::parse(stream, Network::Regtest).unwrap();
+            assert_eq!(version.command(), "version");
+            send(stream, &peer_version());
+            send(stream, &PingMessage { nonce: [7; 8] });
+            send(stream, &VerAckMessage);
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        let info = node.handshake().unwrap().clone();
+        assert_eq!(
+            info,
+            PeerInfo {
+                version: 70015,
+                services: 9,
+                start_height: 812_345,
+                user_agent: "/mock:1.0/".to_string(),
+            }
+        );
+        assert_eq!(node.peer(), Some(&info));
+        drop(node);
+
+        let received = peer.join().unwrap();
+        let commands: Vec<&str> = received.iter().map(NetworkEnvelope::command).collect();
+        assert_eq!(commands, vec!["verack", "pong", "sendheaders", "feefilter"]);
+        let pong: PongMessage = received[1].message().unwrap();
+        assert!(pong.answers(&PingMessage { nonce: [7; 8] }));
+    }
+
+    #[test]
+    fn test_missing_verack_times_out() {
+        let (port, peer) = mock_peer(|stream| {
+            NetworkEnvelope::parse(stream, Network::Regtest).unwrap();
+            send(stream, &peer_version());
+        });
+
+        let mut node = connect_with_short_timeout(port);
+        assert!(matches!(node.handshake(), Err(NetError::Timeout)));
+        drop(node);
+        peer.join().unwrap();
+    }
+
+    #[test]
+    fn test_wait_for_skips_unsolicited_messages() {
+        let (port, peer) = mock_peer(|stream| {
+            send(stream, &VerAckMessage);
+            send(stream, &PingMessage { nonce: [1; 8] });
+            send(stream, &PongMessage { nonce: [2; 8] });
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        let envelope = node.wait_for(&["pong"]).unwrap();
+        let pong: PongMessage = envelope.message().unwrap();
+        assert_eq!(pong.nonce, [2; 8]);
+        drop(node);
+
+        let received = peer.join().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].command(), "pong");
+    }
+
+    #[test]
+    fn test_wrong_network_is_rejected() {
+        let (port, peer) = mock_peer(|stream| {
+            let envelope = NetworkEnvelope::from_message(&VerAckMessage);
+            stream
+                .write_all(&envelope.serialize(Network::Mainnet))
+                .unwrap();
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        assert!(matches!(node.read(), Err(NetError::WrongNetwork { .. })));
+        drop(node);
+        peer.join().unwrap();
+    }
+
+    type ErrorCheck = fn(&NetError) -> bool;
+
+    #[test]
+    fn test_each_malformed_frame_is_a_typed_error() {
+        let wrong_network =
+            NetworkEnvelope::from_message(&VerAckMessage).serialize(Network::Mainnet);
+        let fixtures: [(Vec<u8>, ErrorCheck, u32); 4] = [
+            (
+                wrong_network,
+                |error| matches!(error, NetError::WrongNetwork { .. }),
+                UNPARSEABLE_PENALTY,
+            ),
+            (
+                bad_command(),
+                |error| matches!(error, NetError::InvalidCommand),
+                UNPARSEABLE_PENALTY,
+            ),
+            (
+                oversized(),
+                |error| matches!(error, NetError::PayloadTooLarge { .. }),
+                OVERSIZED_PENALTY,
+            ),
+            (
+                bad_checksum(),
+                |error| matches!(error, NetError::ChecksumMismatch { .. }),
+                CHECKSUM_PENALTY,
+            ),
+        ];
+
+        for (frame, expected, penalty) in fixtures {
+            let (port, peer) = mock_peer(move |stream| {
+                send_raw(stream, &frame);
+                send(stream, &PongMessage { nonce: [9; 8] });
+                send_raw(stream, &garbled_ping());
+            });
+
+            let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+            assert!(expected(&node.read().unwrap_err()));
+            assert_eq!(node.misbehavior(), penalty);
+            // The stream is realigned on the frame that follows
+            let pong: PongMessage = node.read().unwrap().message().unwrap();
+            assert_eq!(pong.nonce, [9; 8]);
+            // A well-framed payload that doesn't parse
+            let ping = node.read().unwrap();
+            assert!(matches!(
+                ping.message::<PingMessage>(),
+                Err(NetError::Encoding(_))
+            ));
+            drop(node);
+            peer.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_handshake_survives_interleaved_garbage() {
+        let (port, peer) = mock_peer(|stream| {
+            NetworkEnvelope::parse(stream, Network::Regtest).unwrap();
+            send_raw(stream, b"\x00\xfa\xbf garbage");
+            send_raw(stream, &bad_checksum());
+            send(stream, &peer_version());
+            send_raw(stream, &garbled_ping());
+            send_raw(stream, &bad_command());
+            send(stream, &VerAckMessage);
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        assert_eq!(node.handshake().unwrap().user_agent, "/mock:1.0/");
+        assert_eq!(
+            node.misbehavior(),
+            CHECKSUM_PENALTY + 3 * UNPARSEABLE_PENALTY
+        );
+        drop(node);
+
+        let received = peer.join().unwrap();
+        let commands: Vec<&str> = received.iter().map(NetworkEnvelope::command).collect();
+        assert_eq!(commands, vec!["verack", "sendheaders", "feefilter"]);
+    }
+
+    #[test]
+    fn test_repeated_garbage_disconnects_the_peer() {
+        let (port, peer) = mock_peer(|stream| {
+            for _ in 0..MISBEHAVIOR_THRESHOLD / CHECKSUM_PENALTY {
+                send_raw(stream, &bad_checksum());
+            }
+            send(stream, &VerAckMessage);
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        assert!(matches!(
+            node.wait_for(&["verack"]),
+            Err(NetError::Misbehaving { score: 100 })
+        ));
+        // The connection stays closed
+        assert!(matches!(
+            node.send(&PingMessage { nonce: [0; 8] }),
+            Err(NetError::Misbehaving { .. })
+        ));
+        assert!(peer.join().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stream_without_magic_disconnects_the_peer() {
+        let (port, peer) = mock_peer(|stream| {
+            // Wrong magic, then nothing that could be a frame
+            send_raw(stream, &[0u8; 24]);
+            let zeros = vec![0u8; 1 << 20];
+            for _ in 0..=2 * MAX_PAYLOAD_SIZE / zeros.len() as u32 {
+                // The node hangs up before reading it all
+                if stream.write_all(&zeros).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        assert!(matches!(
+            node.wait_for(&["verack"]),
+            Err(NetError::Misbehaving { score: 120 })
+        ));
+        assert_eq!(
+            node.misbehavior(),
+            UNPARSEABLE_PENALTY + 2 * OVERSIZED_PENALTY
+        );
+        drop(node);
+        peer.join().unwrap();
+    }
+
+    // A source chain of `height` headers, and a node's chain at its genesis.
+    fn fixture_chains(height: usize) -> (HeaderChain, HeaderChain) {
+        let mut source = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        extend(&mut source, height, 600);
+        let chain = HeaderChain::new(genesis(EASY_BITS), retargeting_params()).unwrap();
+        (source, chain)
+    }
+
+    fn batch(source: &HeaderChain, heights: std::ops::RangeInclusive<u64>) -> Vec<BlockHeader> {
+        heights
+            .map(|height| source.header_at(height).unwrap().clone())
+            .collect()
+    }
+
+    // A peer answering each `getheaders` with the next of `batches`. The locators it was sent
+    // come out of the returned channel.
+    fn headers_peer(
+        batches: Vec<Vec<BlockHeader>>,
+    ) -> (u16, PeerThread, mpsc::Receiver<Vec<[u8; 32]>>) {
+        let (locators, received) = mpsc::channel();
+        let (port, peer) = mock_peer(move |stream| {
+            for headers in batches {
+                let request = NetworkEnvelope::parse(stream, Network::Regtest).unwrap();
+                let request: GetHeadersMessage = request.message().unwrap();
+                locators.send(request.locator).unwrap();
+                send(stream, &HeadersMessage { headers });
+            }
+        });
+        (port, peer, received)
+    }
+
+    #[test]
+    fn test_sync_headers_in_batches() {
+        let (source, mut chain) = fixture_chains(40);
+        let batches = vec![
+            batch(&source, 1..=10),
+            batch(&source, 11..=20),
+            batch(&source, 21..=30),
+            batch(&source, 31..=40),
+            Vec::new(),
+        ];
+        let (port, peer, locators) = headers_peer(batches);
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        assert_eq!(node.sync_headers(&mut chain, None).unwrap(), 40);
+        assert_eq!(chain.tip(), source.tip());
+        drop(node);
+        peer.join().unwrap();
+        assert_eq!(locators.iter().count(), 5);
+    }
+
+    #[test]
+    fn test_sync_headers_aborts_on_broken_link() {
+        let (source, mut chain) = fixture_chains(20);
+        let mut broken = batch(&source, 11..=20);
+        broken[4].prev_blockhash = [0xab; 32];
+        while !broken[4].check_pow() {
+            broken[4].nonce += 1;
+        }
+        let offending = broken[4].display_hash();
+        let (port, peer, _locators) = headers_peer(vec![batch(&source, 1..=10), broken]);
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        match node.sync_headers(&mut chain, None) {
+            Err(NetError::RejectedHeader { hash, source }) => {
+                assert_eq!(hash, offending);
+                assert_eq!(source, BlockError::UnknownParent);
+            }
+            result => panic!("expected a rejected header, got {result:?}"),
+        }
+        assert_eq!(chain.height(), 14);
+        drop(node);
+        peer.join().unwrap();
+    }
+
+    #[test]
+    fn test_sync_headers_requests_from_the_new_tip() {
+        let (source, mut chain) = fixture_chains(20);
+        let batches = vec![batch(&source, 1..=10), batch(&source, 11..=20)];
+        let (port, peer, locators) = headers_peer(batches);
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        assert_eq!(node.sync_headers(&mut chain, Some(15)).unwrap(), 15);
+        drop(node);
+        peer.join().unwrap();
+
+        let locators: Vec<_> = locators.iter().collect();
+        assert_eq!(locators[0], vec![source.header_at(0).unwrap().hash()]);
+        let expected: Vec<_> = locator_heights(10)
+            .into_iter()
+            .map(|height| source.header_at(height).unwrap().hash())
+            .collect();
+        assert_eq!(locators[1][0], source.header_at(10).unwrap().hash());
+        assert_eq!(locators[1], expected);
+    }
+
+    fn short_read_timeout() -> Timeouts {
+        Timeouts {
+            read: Duration::from_millis(200),
+            ..Timeouts::default()
+        }
+    }
+
+    fn connect_with_short_timeout(port: u16) -> SimpleNode {
+        SimpleNode::connect_with_timeouts("127.0.0.1", port, Network::Regtest, short_read_timeout())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_ping_measures_latency() {
+        let (port, peer) = mock_peer(|stream| {
+            let ping: PingMessage = NetworkEnvelope::parse(stream, Network::Regtest)
+                .unwrap()
+                .message()
+                .unwrap();
+            // A stale pong first, which the node must skip
+            send(stream, &PongMessage { nonce: [0xee; 8] });
+            send(stream, &ping.pong());
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        let latency = node.ping().unwrap();
+        assert!(latency < Duration::from_secs(5));
+        drop(node);
+        assert!(peer.join().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_ping_with_wrong_nonce_times_out() {
+        let (port, peer) = mock_peer(|stream| {
+            let ping: PingMessage = NetworkEnvelope::parse(stream, Network::Regtest)
+                .unwrap()
+                .message()
+                .unwrap();
+            let mut nonce = ping.nonce;
+            nonce[0] ^= 1;
+            send(stream, &PongMessage { nonce });
+        });
+
+        let mut node = connect_with_short_timeout(port);
+        assert!(matches!(node.ping(), Err(NetError::Timeout)));
+        drop(node);
+        peer.join().unwrap();
+    }
+
+    #[test]
+    fn test_keepalive_pings_a_quiet_peer() {
+        let (port, peer) = mock_peer(|stream| {
+            let ping: PingMessage = NetworkEnvelope::parse(stream, Network::Regtest)
+                .unwrap()
+                .message()
+                .unwrap();
+            send(stream, &ping.pong());
+            send(stream, &VerAckMessage);
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        node.set_keepalive(Some(Duration::from_millis(100)))
+            .unwrap();
+        assert_eq!(node.wait_for(&["verack"]).unwrap().command(), "verack");
+        drop(node);
+        assert!(peer.join().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_keepalive_gives_up_on_a_silent_peer() {
+        let (port, peer) = mock_peer(|stream| {
+            let ping = NetworkEnvelope::parse(stream, Network::Regtest).unwrap();
+            assert_eq!(ping.command(), "ping");
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        node.set_keepalive(Some(Duration::from_millis(100)))
+            .unwrap();
+        assert!(matches!(node.wait_for(&["verack"]), Err(NetError::Timeout)));
+        drop(node);
+        peer.join().unwrap();
+    }
+
+    #[test]
+    fn test_handshake_negotiates_features() {
+        let (port, peer) = mock_peer(|stream| {
+            NetworkEnvelope::parse(stream, Network::Regtest).unwrap();
+            let version = VersionMessage {
+                version: 70016,
+                ..peer_version()
+            };
+            send(stream, &version);
+            send(stream, &VerAckMessage);
+            // 5 sat/vB
+            send(stream, &FeeFilterMessage { fee_rate: 5_000 });
+            send(stream, &PongMessage { nonce: [3; 8] });
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        node.handshake().unwrap();
+        assert!(node.features().unwrap().wtxid_relay);
+        assert_eq!(node.peer_fee_filter(), None);
+        node.wait_for(&["pong"]).unwrap();
+        let filter = node.peer_fee_filter().unwrap();
+        assert_eq!(filter.fee_rate, 5_000);
+        // A transaction paying 4.99 sat/vB would not be relayed by the peer
+        assert!(!filter.accepts(704, 141));
+        drop(node);
+
+        let received = peer.join().unwrap();
+        let commands: Vec<&str> = received.iter().map(NetworkEnvelope::command).collect();
+        assert_eq!(
+            commands,
+            vec!["wtxidrelay", "verack", "sendheaders", "feefilter"]
+        );
+        let filter: FeeFilterMessage = received[3].message().unwrap();
+        assert_eq!(filter.fee_rate, DEFAULT_FEE_FILTER);
+    }
+
+    #[test]
+    fn test_unsolicited_headers_extend_the_chain() {
+        let (source, mut chain) = fixture_chains(3);
+        let announcements = [batch(&source, 1..=1), batch(&source, 3..=3)];
+        let missing = batch(&source, 2..=3);
+        let (port, peer) = mock_peer(move |stream| {
+            for headers in announcements {
+                send(stream, &HeadersMessage { headers });
+            }
+            send(stream, &VerAckMessage);
+            // The announcement of height 3 doesn't connect, so the node asks for the gap
+            for headers in [missing, Vec::new()] {
+                let request = NetworkEnvelope::parse(stream, Network::Regtest).unwrap();
+                assert_eq!(request.command(), "getheaders");
+                send(stream, &HeadersMessage { headers });
+            }
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        node.wait_for(&["verack"]).unwrap();
+        assert_eq!(node.connect_announced(&mut chain).unwrap(), 3);
+        assert_eq!(chain.tip(), source.tip());
+        drop(node);
+        peer.join().unwrap();
+    }
+
+    #[test]
+    fn test_addr_gossip_fills_the_book_and_answers_getaddr() {
+        let gossip = AddrMessage {
+            addresses: vec![(
+                1_700_000_000,
+                NetAddr::new("1.2.3.4".parse().unwrap(), 8333, 1),
+            )],
+        };
+        let (port, peer) = mock_peer(move |stream| {
+            send(stream, &gossip);
+            send(stream, &GetAddrMessage);
+            send(stream, &VerAckMessage);
+            let reply = NetworkEnvelope::parse(stream, Network::Regtest).unwrap();
+            assert_eq!(reply.command(), "addr");
+            assert_eq!(reply.message::<AddrMessage>().unwrap(), gossip);
+        });
+
+        let mut node = SimpleNode::connect("127.0.0.1", port, Network::Regtest).unwrap();
+        node.wait_for(&["verack"]).unwrap();
+        assert_eq!(node.addr_book().len(), 1);
+        assert_eq!(node.misbehavior(), 0);
+        drop(node);
+        peer.join().unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+use crate::block::genesis::{MAINNET_GENESIS, REGTEST_GENESIS, SIGNET_GENESIS, TESTNET_GENESIS};
+use crate::block::header::BlockHeader;
+use crate::types::errors::NetError;
+
+// This module defines the `Network` enum and the per-network P2P parameters.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+impl Network {
+    pub const ALL: [Network; 4] = [
+        Network::Mainnet,
+        Network::Testnet,
+        Network::Signet,
+        Network::Regtest,
+    ];
+
+    pub fn from_magic(magic: [u8; 4]) -> Option<Network> {
+        Network::ALL
+            .into_iter()
+            .find(|network| network.magic() == magic)
+    }
+
+    // Rejects traffic whose magic belongs to another (or no) network.
+    pub fn check_magic(&self, magic: [u8; 4]) -> Result<(), NetError> {
+        if magic == self.magic() {
+            Ok(())
+        } else {
+            Err(NetError::WrongNetwork {
+                expected: self.magic(),
+                actual: magic,
+            })
+        }
+    }
+
+    // Regtest blocks can be mined with trivial proof-of-work.
+    pub fn max_target_bits(&self) -> u32 {
+        match self {
+            Network::Regtest => 0x207fffff,
+            Network::Signet => 0x1e0377ae,
+            Network::Mainnet | Network::Testnet => 0x1d00ffff,
+        }
+    }
+
+    pub fn genesis_header(&self) -> BlockHeader {
+        match self {
+            Network::Mainnet => MAINNET_GENESIS,
+            Network::Testnet => TESTNET_GENESIS,
+            Network::Signet => SIGNET_GENESIS,
+            Network::Regtest => REGTEST_GENESIS,
+        }
+    }
+
+    // First 4 bytes of every message envelope.
+    pub fn magic(&self) -> [u8; 4] {
+        match self {
+            Network::Mainnet => [0xf9, 0xbe, 0xb4, 0xd9],
+            Network::Testnet => [0x0b, 0x11, 0x09, 0x07],
+            Network::Signet => [0x0a, 0x03, 0xcf, 0x40],
+            Network::Regtest => [0xfa, 0xbf, 0xb5, 0xda],
+        }
+    }
+
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Network::Mainnet => 8333,
+            Network::Testnet => 18333,
+            Network::Signet => 38333,
+            Network::Regtest => 18444,
+        }
+    }
+
+    pub fn dns_seeds(&self) -> &'static [&'static str] {
+        match self {
+            Network::Mainnet => &[
+                "seed.bitcoin.sipa.be",
+                "dnsseed.bluematt.me",
+                "seed.bitcoinstats.com",
+                "seed.bitcoin.jonasschnelli.ch",
+                "seed.btc.petertodd.net",
+                "seed.bitcoin.sprovoost.nl",
+                "dnsseed.emzy.de",
+                "seed.bitcoin.wiz.biz",
+            ],
+            Network::Testnet => &[
+                "testnet-seed.bitcoin.jonasschnelli.ch",
+                "seed.tbtc.petertodd.net",
+                "seed.testnet.bitcoin.sprovoost.nl",
+                "testnet-seed.bluematt.me",
+            ],
+            Network::Signet => &["seed.signet.bitcoin.sprovoost.nl"],
+            Network::Regtest => &[],
+        }
+    }
+}
+
+#[cfg(test)]
+mod params_tests {
+    use super::*;
+
+    #[test]
+    fn test_magic_round_trip_per_network() {
+        for network in Network::ALL {
+            assert_eq!(Network::from_magic(network.magic()), Some(network));
+            assert!(network.check_magic(network.magic()).is_ok());
+        }
+        assert_eq!(Network::from_magic([0, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn test_cross_network_magic_is_rejected() {
+        assert!(matches!(
+            Network::Mainnet.check_magic(Network::Testnet.magic()),
+            Err(NetError::WrongNetwork {
+                expected: [0xf9, 0xbe, 0xb4, 0xd9],
+                actual: [0x0b, 0x11, 0x09, 0x07],
+            })
+        ));
+        assert!(matches!(
+            Network::Regtest.check_magic(Network::Signet.magic()),
+            Err(NetError::WrongNetwork { .. })
+        ));
+    }
+
+    #[test]
+    fn test_regtest_accepts_trivial_pow() {
+        use crate::block::pow::check_pow;
+
+        let mut hash = [0xffu8; 32];
+        hash[31] = 0x3f;
+
+        assert!(check_pow(&hash, Network::Regtest.max_target_bits()));
+        assert!(!check_pow(&hash, Network::Mainnet.max_target_bits()));
+    }
+}
@@ -0,0 +1,100 @@
+use std::io::Read;
+
+use crate::encoding::read_array;
+use crate::network::traits::NetworkMessage;
+use crate::types::errors::NetError;
+
+// This module implements the `ping` and `pong` keepalive messages.
+//
+// A `ping` carries a random 8-byte nonce and the peer must answer with a `pong` echoing the
+// same nonce, which lets the sender match replies and measure round-trip latency.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PingMessage {
+    pub nonce: [u8; 8],
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PongMessage {
+    pub nonce: [u8; 8],
+}
+
+impl PingMessage {
+    pub fn pong(&self) -> PongMessage {
+        PongMessage { nonce: self.nonce }
+    }
+}
+
+impl PongMessage {
+    pub fn answers(&self, ping: &PingMessage) -> bool {
+        self.nonce == ping.nonce
+    }
+}
+
+impl NetworkMessage for PingMessage {
+    fn command() -> &'static str {
+        "ping"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        self.nonce.to_vec()
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        Ok(PingMessage {
+            nonce: read_array(reader)?,
+        })
+    }
+}
+
+impl NetworkMessage for PongMessage {
+    fn command() -> &'static str {
+        "pong"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        self.nonce.to_vec()
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        Ok(PongMessage {
+            nonce: read_array(reader)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod ping_tests {
+    use super::*;
+    use crate::types::errors::EncodingError;
+
+    #[test]
+    fn test_pong_echoes_ping_nonce() {
+        let ping = PingMessage {
+            nonce: [1, 2, 3, 4, 5, 6, 7, 8],
+        };
+        let pong = ping.pong();
+
+        assert_eq!(pong.serialize(), ping.serialize());
+        assert!(pong.answers(&ping));
+        assert!(!PongMessage { nonce: [0; 8] }.answers(&ping));
+    }
+
+    #[test]
+    fn test_ping_pong_round_trip() {
+        let payload = [0xaa, 0xbb, 0xcc, 0xdd, 0x00, 0x11, 0x22, 0x33];
+
+        let ping = PingMessage::parse(&mut &payload[..]).unwrap();
+        let pong = PongMessage::parse(&mut &payload[..]).unwrap();
+
+        assert_eq!(ping.nonce, payload);
+        assert_eq!(pong.nonce, payload);
+        assert!(matches!(
+            PingMessage::parse(&mut &payload[..7]),
+            Err(NetError::Encoding(EncodingError::UnexpectedEof {
+                expected: 8,
+                read: 7
+            }))
+        ));
+    }
+}
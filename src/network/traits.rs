@@ -0,0 +1,15 @@
+// This module defines the `NetworkMessage` trait implemented by every P2P message payload.
+//
+// A message is identified on the wire by its ASCII command (e.g. "version", "verack"), which
+// the network envelope pads to 12 bytes. The trait only deals with the payload: framing,
+// magic bytes and checksums belong to the envelope.
+
+use std::io::Read;
+
+use crate::types::errors::NetError;
+
+pub trait NetworkMessage: Sized {
+    fn command() -> &'static str;
+    fn serialize(&self) -> Vec<u8>;
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError>;
+}
@@ -0,0 +1,197 @@
+use std::io::Read;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::encoding::varint::{encode_varint, read_varint};
+use crate::encoding::{read_array, read_vec};
+use crate::network::net_addr::NetAddr;
+use crate::network::traits::NetworkMessage;
+use crate::types::errors::NetError;
+
+// This module implements the `version` and `verack` messages used in the connection handshake.
+//
+// Each side sends a `version` describing itself (protocol version, services, best height...)
+// and acknowledges the other's with an empty `verack`.
+
+pub const PROTOCOL_VERSION: i32 = 70016;
+pub const USER_AGENT: &str = "/bitcoin_in_rust:0.1/";
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct VersionMessage {
+    pub version: i32,
+    pub services: u64,
+    pub timestamp: i64,
+    pub receiver: NetAddr,
+    pub sender: NetAddr,
+    pub nonce: u64,
+    pub user_agent: String,
+    pub start_height: i32,
+    pub relay: bool,
+}
+
+impl Default for VersionMessage {
+    fn default() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        VersionMessage {
+            version: PROTOCOL_VERSION,
+            services: 0,
+            timestamp: now.as_secs() as i64,
+            receiver: NetAddr::default(),
+            sender: NetAddr::default(),
+            // Only used to detect connections to ourselves, so it doesn't need a CSPRNG
+            nonce: now.as_nanos() as u64,
+            user_agent: USER_AGENT.to_string(),
+            start_height: 0,
+            relay: false,
+        }
+    }
+}
+
+impl NetworkMessage for VersionMessage {
+    fn command() -> &'static str {
+        "version"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        result.extend_from_slice(&self.version.to_le_bytes());
+        result.extend_from_slice(&self.services.to_le_bytes());
+        result.extend_from_slice(&self.timestamp.to_le_bytes());
+        result.extend(self.receiver.serialize());
+        result.extend(self.sender.serialize());
+        result.extend_from_slice(&self.nonce.to_le_bytes());
+        result.extend(encode_varint(self.user_agent.len() as u64));
+        result.extend_from_slice(self.user_agent.as_bytes());
+        result.extend_from_slice(&self.start_height.to_le_bytes());
+        result.push(self.relay as u8);
+        result
+    }
+
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        let version = i32::from_le_bytes(read_array(reader)?);
+        let services = u64::from_le_bytes(read_array(reader)?);
+        let timestamp = i64::from_le_bytes(read_array(reader)?);
+        let receiver = NetAddr::parse(reader)?;
+        let sender = NetAddr::parse(reader)?;
+        let nonce = u64::from_le_bytes(read_array(reader)?);
+        let user_agent_len = read_varint(reader)?;
+        let user_agent =
+            String::from_utf8(read_vec(reader, user_agent_len as usize)?).map_err(|_| {
+                NetError::InvalidMessage {
+                    command: "version",
+                    reason: "user agent is not valid UTF-8",
+                }
+            })?;
+        let start_height = i32::from_le_bytes(read_array(reader)?);
+        let [relay] = read_array::<R, 1>(reader)?;
+
+        Ok(VersionMessage {
+            version,
+            services,
+            timestamp,
+            receiver,
+            sender,
+            nonce,
+            user_agent,
+            start_height,
+            relay: relay != 0,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct VerAckMessage;
+
+impl NetworkMessage for VerAckMessage {
+    fn command() -> &'static str {
+        "verack"
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn parse<R: Read>(_reader: &mut R) -> Result<Self, NetError> {
+        Ok(VerAckMessage)
+    }
+}
+
+#[cfg(test)]
+mod version_tests {
+    use super::*;
+    use crate::encoding::hex::{decode_hex, encode_hex};
+    use crate::types::errors::EncodingError;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn test_serialize_version_programming_bitcoin_example() {
+        let message = VersionMessage {
+            version: 70015,
+            timestamp: 0,
+            nonce: 0,
+            user_agent: "/programmingbitcoin:0.1/".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            encode_hex(&message.serialize()),
+            "7f11010000000000000000000000000000000000000000000000000000000000000000000000ffff000000\
+             00208d000000000000000000000000000000000000ffff00000000208d0000000000000000182f7072\
+             6f6772616d6d696e67626974636f696e3a302e312f0000000000"
+        );
+    }
+
+    #[test]
+    fn test_parse_version_payload() {
+        let payload = decode_hex(
+            "80110100090400000000000000f1536500000000090400000000000000000000000000000000ffffcb00\
+             7105208d000000000000000000000000000000000000ffff0000000000008877665544332211102f53\
+             61746f7368693a32372e302e302f40d10c0001",
+        )
+        .unwrap();
+
+        let message = VersionMessage::parse(&mut payload.as_slice()).unwrap();
+
+        assert_eq!(message.version, 70016);
+        assert_eq!(message.services, 0x409);
+        assert_eq!(message.timestamp, 1_700_000_000);
+        assert_eq!(
+            message.receiver.ip(),
+            IpAddr::V4(Ipv4Addr::new(203, 0, 113, 5))
+        );
+        assert_eq!(message.receiver.port, 8333);
+        assert_eq!(message.receiver.services, 0x409);
+        assert_eq!(message.sender.ip(), IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert_eq!(message.sender.port, 0);
+        assert_eq!(message.nonce, 0x1122334455667788);
+        assert_eq!(message.user_agent, "/Satoshi:27.0.0/");
+        assert_eq!(message.start_height, 840_000);
+        assert!(message.relay);
+        assert_eq!(message.serialize(), payload);
+    }
+
+    #[test]
+    fn test_parse_truncated_version() {
+        let payload = VersionMessage::default().serialize();
+
+        assert!(matches!(
+            VersionMessage::parse(&mut &payload[..payload.len() - 1]),
+            Err(NetError::Encoding(EncodingError::UnexpectedEof {
+                expected: 1,
+                read: 0
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_default_version_and_verack() {
+        let message = VersionMessage::default();
+
+        assert_eq!(message.version, PROTOCOL_VERSION);
+        assert_eq!(message.user_agent, "/bitcoin_in_rust:0.1/");
+        assert_eq!(VersionMessage::command(), "version");
+        assert_eq!(VerAckMessage::command(), "verack");
+        assert!(VerAckMessage.serialize().is_empty());
+    }
+}
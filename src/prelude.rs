@@ -0,0 +1,18 @@
+//! Glob-importable set of the most commonly used types and traits.
+//!
+//! ```
+//! use bitcoin_in_rust::prelude::*;
+//! ```
+
+pub use crate::elliptic_curve::finite_field::FieldElement;
+pub use crate::elliptic_curve::secp256k1::{
+    SECP256K1_CURVE, SECP256K1_GENERATOR, SECP256K1_ORDER, SECP256K1_PRIME,
+};
+pub use crate::elliptic_curve::traits::{EllipticCurve, Point};
+pub use crate::elliptic_curve::weierstrass_field_point::WeierstrassCurve;
+pub use crate::network::params::Network;
+pub use crate::network::traits::NetworkMessage;
+pub use crate::types::errors::{
+    BlockError, CurveError, EncodingError, Error, Errors, FieldError, NetError, StoreError,
+};
+pub use num_bigint::BigInt;
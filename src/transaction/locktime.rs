@@ -0,0 +1,137 @@
+use std::cmp::Ordering;
+
+// This module implements the `LockTime` newtype used by the nLockTime transaction field.
+//
+// A locktime is a single u32 that is interpreted in one of two ways:
+//
+//     value <  500_000_000  =>  block height
+//     value >= 500_000_000  =>  unix timestamp (compared against median-time-past)
+//
+// A value of 0 means the transaction is not locked at all. Locktimes of different kinds
+// are never comparable: a height lock can't be satisfied by a time and vice versa.
+
+// Values below this threshold are block heights, values at or above it are unix times.
+pub const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum LockTime {
+    Height(u32),
+    Time(u32),
+}
+
+impl LockTime {
+    pub fn from_consensus(value: u32) -> Self {
+        if value < LOCKTIME_THRESHOLD {
+            LockTime::Height(value)
+        } else {
+            LockTime::Time(value)
+        }
+    }
+
+    pub fn to_consensus_u32(self) -> u32 {
+        match self {
+            LockTime::Height(value) | LockTime::Time(value) => value,
+        }
+    }
+
+    pub fn is_block_height(&self) -> bool {
+        matches!(self, LockTime::Height(_))
+    }
+
+    pub fn is_block_time(&self) -> bool {
+        matches!(self, LockTime::Time(_))
+    }
+
+    // Consensus rule: the lock is satisfied if it is 0 or strictly lower than the height of
+    // the block including the transaction (for heights) or its median-time-past (for times).
+    pub fn is_satisfied_by(&self, height: u64, mtp: u64) -> bool {
+        match *self {
+            LockTime::Height(0) => true,
+            LockTime::Height(value) => u64::from(value) < height,
+            LockTime::Time(value) => u64::from(value) < mtp,
+        }
+    }
+
+    // Whether a transaction locked at `self` is also unlocked whenever `other` is, as used
+    // by OP_CHECKLOCKTIMEVERIFY. Locks of different kinds never imply each other.
+    pub fn is_implied_by(&self, other: LockTime) -> bool {
+        matches!(
+            self.partial_cmp(&other),
+            Some(Ordering::Less | Ordering::Equal)
+        )
+    }
+}
+
+impl PartialOrd for LockTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (LockTime::Height(a), LockTime::Height(b)) | (LockTime::Time(a), LockTime::Time(b)) => {
+                Some(a.cmp(b))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<u32> for LockTime {
+    fn from(value: u32) -> Self {
+        LockTime::from_consensus(value)
+    }
+}
+
+#[cfg(test)]
+mod locktime_tests {
+    use super::*;
+
+    #[test]
+    fn test_from_consensus_splits_at_threshold() {
+        assert_eq!(LockTime::from_consensus(0), LockTime::Height(0));
+        assert_eq!(
+            LockTime::from_consensus(LOCKTIME_THRESHOLD - 1),
+            LockTime::Height(LOCKTIME_THRESHOLD - 1)
+        );
+        assert_eq!(
+            LockTime::from_consensus(LOCKTIME_THRESHOLD),
+            LockTime::Time(LOCKTIME_THRESHOLD)
+        );
+        assert_eq!(
+            LockTime::from(1_700_000_000).to_consensus_u32(),
+            1_700_000_000
+        );
+    }
+
+    #[test]
+    fn test_height_lock_satisfied_only_after_height() {
+        let lock = LockTime::from_consensus(800_000);
+
+        assert!(!lock.is_satisfied_by(799_999, u64::MAX));
+        assert!(!lock.is_satisfied_by(800_000, u64::MAX));
+        assert!(lock.is_satisfied_by(800_001, 0));
+    }
+
+    #[test]
+    fn test_time_lock_satisfied_only_after_mtp() {
+        let lock = LockTime::from_consensus(1_700_000_000);
+
+        assert!(!lock.is_satisfied_by(u64::MAX, 1_699_999_999));
+        assert!(!lock.is_satisfied_by(u64::MAX, 1_700_000_000));
+        assert!(lock.is_satisfied_by(0, 1_700_000_001));
+    }
+
+    #[test]
+    fn test_zero_lock_is_always_satisfied() {
+        assert!(LockTime::from_consensus(0).is_satisfied_by(0, 0));
+    }
+
+    #[test]
+    fn test_cross_type_comparisons_never_satisfy() {
+        let height = LockTime::from_consensus(100);
+        let time = LockTime::from_consensus(1_700_000_000);
+
+        assert_eq!(height.partial_cmp(&time), None);
+        assert!(!height.is_implied_by(time));
+        assert!(!time.is_implied_by(height));
+        assert!(height.is_implied_by(LockTime::Height(100)));
+        assert!(!time.is_implied_by(LockTime::Time(1_600_000_000)));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod locktime;
+pub mod sequence;
@@ -0,0 +1,63 @@
+// This module implements the `Sequence` newtype used by the nSequence field of transaction inputs.
+//
+// Besides its original (never enforced) purpose, the sequence number carries two signals:
+//
+//     sequence <  0xfffffffe  =>  the input opts in to BIP125 replace-by-fee
+//     sequence != 0xffffffff  =>  the transaction locktime is enforced
+//
+// 0xfffffffd is the conventional value for "replaceable, locktime enabled".
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Sequence(pub u32);
+
+impl Sequence {
+    // Final sequence: no RBF, and locktime is ignored if every input uses it.
+    pub const MAX: Sequence = Sequence(0xffffffff);
+    // Highest value that still enforces the locktime without signaling RBF.
+    pub const ENABLE_LOCKTIME_NO_RBF: Sequence = Sequence(0xfffffffe);
+    // Highest value that signals RBF.
+    pub const ENABLE_RBF_NO_LOCKTIME: Sequence = Sequence(0xfffffffd);
+
+    pub fn is_final(&self) -> bool {
+        *self == Self::MAX
+    }
+
+    pub fn is_rbf_signaling(&self) -> bool {
+        *self < Self::ENABLE_LOCKTIME_NO_RBF
+    }
+
+    pub fn enables_absolute_lock_time(&self) -> bool {
+        !self.is_final()
+    }
+
+    pub fn to_consensus_u32(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for Sequence {
+    fn from(value: u32) -> Self {
+        Sequence(value)
+    }
+}
+
+#[cfg(test)]
+mod sequence_tests {
+    use super::*;
+
+    #[test]
+    fn test_rbf_signaling_boundaries() {
+        assert!(!Sequence(0xffffffff).is_rbf_signaling());
+        assert!(!Sequence(0xfffffffe).is_rbf_signaling());
+        assert!(Sequence(0xfffffffd).is_rbf_signaling());
+        assert!(Sequence(0).is_rbf_signaling());
+    }
+
+    #[test]
+    fn test_locktime_enabled_unless_final() {
+        assert!(Sequence::MAX.is_final());
+        assert!(!Sequence::MAX.enables_absolute_lock_time());
+        assert!(Sequence::ENABLE_LOCKTIME_NO_RBF.enables_absolute_lock_time());
+        assert!(Sequence::ENABLE_RBF_NO_LOCKTIME.enables_absolute_lock_time());
+    }
+}
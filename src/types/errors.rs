@@ -1,7 +1,214 @@
+use num_bigint::BigInt;
 use thiserror::Error;
 
-#[derive(Debug, Error, PartialEq)]
-pub enum Errors {
+// Errors are grouped per domain. Every public fallible function returns the enum of its own
+// domain, and all of them convert into the top-level `Error` with `?`.
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum FieldError {
+    #[error("Cannot operate on elements of different fields ({left} and {right})")]
+    DifferentFields { left: BigInt, right: BigInt },
+    #[error(
+        "Cannot operate on elements of extensions by different non-residues ({left} and {right})"
+    )]
+    DifferentNonResidues { left: BigInt, right: BigInt },
+    #[error("Cannot divide by zero")]
+    DivisionByZero,
+    #[error("{num} has no inverse modulo {modulus}")]
+    NotInvertible { num: BigInt, modulus: BigInt },
+    #[error("{num} is not in the range [0, {prime})")]
+    OutOfRange { num: BigInt, prime: BigInt },
+    #[error("{prime} cannot be the order of a prime field")]
+    InvalidPrime { prime: BigInt },
+    #[error("Invalid hex digit at position {position}")]
+    InvalidHex { position: usize },
+    #[error("Expected a field element as \"num/prime\"")]
+    MissingPrime,
+    #[error("{num} does not fit in a {bits}-bit integer")]
+    IntegerOverflow { num: BigInt, bits: u32 },
+    #[error("{num} is not a quadratic non-residue modulo {prime}")]
+    NotANonResidue { num: BigInt, prime: BigInt },
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum CurveError {
     #[error("Point is not included in the curve")]
     InvalidPoint,
+    #[error("The curve is singular: 4a³ + 27b² is zero")]
+    SingularCurve,
+    #[error("Cannot operate on points of different curves")]
+    DifferentCurves,
+    #[error("A multi-scalar multiplication needs at least one term")]
+    NoTerms,
+    #[error(transparent)]
+    Field(#[from] FieldError),
+}
+
+// Not `PartialEq` or `Clone`, as it can carry the `std::io::Error` of the reader.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum EncodingError {
+    #[error("Unexpected end of data: expected {expected} bytes, got {read}")]
+    UnexpectedEof { expected: usize, read: usize },
+    #[error("Invalid hex string at position {position}")]
+    InvalidHex { position: usize },
+    #[error("I/O error while reading")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum BlockError {
+    #[error("Compact target {bits:#010x} has the sign bit set")]
+    NegativeTarget { bits: u32 },
+    #[error("Compact target {bits:#010x} does not fit in 256 bits")]
+    TargetOverflow { bits: u32 },
+    #[error(
+        "Block timestamp {timestamp} is not later than the median time past {median_time_past}"
+    )]
+    TimestampTooOld {
+        timestamp: u64,
+        median_time_past: u64,
+    },
+    #[error("Block timestamp {timestamp} is later than the maximum allowed {max_allowed}")]
+    TimestampTooNew { timestamp: u64, max_allowed: u64 },
+    #[error("Header hash does not meet the target of its bits {bits:#010x}")]
+    InsufficientPow { bits: u32 },
+    #[error("Header bits {actual:#010x} do not match the expected {expected:#010x}")]
+    UnexpectedBits { expected: u32, actual: u32 },
+    #[error("Version {version} blocks are no longer accepted at height {height}")]
+    BadVersion { version: i32, height: u64 },
+    #[error("Header at height {height} does not match the checkpoint")]
+    CheckpointMismatch { height: u64 },
+    #[error("Header does not connect to a known block")]
+    UnknownParent,
+    #[error("Compact target {bits:#010x} is easier than the network allows")]
+    TargetTooEasy { bits: u32 },
+    #[error("Invalid deployment: {reason}")]
+    InvalidDeployment { reason: &'static str },
+    #[error("{needed} headers are needed but only {available} are available")]
+    MissingHeaders { needed: u64, available: u64 },
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum NetError {
+    #[error("Malformed message payload")]
+    Encoding(#[from] EncodingError),
+    #[error("Invalid {command} message: {reason}")]
+    InvalidMessage {
+        command: &'static str,
+        reason: &'static str,
+    },
+    #[error("Message magic {actual:02x?} does not match the expected {expected:02x?}")]
+    WrongNetwork { expected: [u8; 4], actual: [u8; 4] },
+    #[error("Message command is not NUL-padded ASCII of at most 12 bytes")]
+    InvalidCommand,
+    #[error("Payload of {length} bytes exceeds the maximum of {max}")]
+    PayloadTooLarge { length: u32, max: u32 },
+    #[error("No message magic found in {skipped} bytes")]
+    MagicNotFound { skipped: u32 },
+    #[error("Message checksum {actual:02x?} does not match the declared {expected:02x?}")]
+    ChecksumMismatch { expected: [u8; 4], actual: [u8; 4] },
+    #[error("Expected a {expected} message, got {actual}")]
+    UnexpectedCommand {
+        expected: &'static str,
+        actual: String,
+    },
+    #[error("Invalid bloom filter: {reason}")]
+    InvalidBloomFilter { reason: &'static str },
+    #[error("Peer sent invalid header {hash}")]
+    RejectedHeader { hash: String, source: BlockError },
+    #[error("Timed out waiting for the peer")]
+    Timeout,
+    #[error("Peer disconnected for misbehaving (score {score})")]
+    Misbehaving { score: u32 },
+    #[error("No peers could be discovered")]
+    NoPeersFound,
+    #[error("Malformed address book entry on line {line}")]
+    InvalidAddrBook { line: usize },
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum StoreError {
+    #[error("File is not a header store")]
+    NotAHeaderStore,
+    #[error("Unsupported header store version {version}")]
+    UnsupportedVersion { version: u32 },
+    #[error("The chain does not extend the stored header at height {height}")]
+    Unlinked { height: u64 },
+    #[error("The header store is empty")]
+    Empty,
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Field(#[from] FieldError),
+    #[error(transparent)]
+    Curve(#[from] CurveError),
+    #[error(transparent)]
+    Encoding(#[from] EncodingError),
+    #[error(transparent)]
+    Block(#[from] BlockError),
+    #[error(transparent)]
+    Net(#[from] NetError),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+// The crate used to have a single `Errors` enum, and the name is kept as an alias. Its variants
+// moved to the domain enums (`Errors::InvalidPoint` is now
+// `Error::Curve(CurveError::InvalidPoint)`), and since I/O errors can be wrapped, `Error` is
+// no longer `PartialEq`: match on it instead.
+pub type Errors = Error;
+
+#[cfg(test)]
+mod errors_tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_domain_errors_convert_into_error() {
+        fn fails() -> Result<(), Error> {
+            Err(BlockError::NegativeTarget { bits: 0x04923456 })?;
+            Ok(())
+        }
+
+        assert!(matches!(
+            fails(),
+            Err(Error::Block(BlockError::NegativeTarget {
+                bits: 0x04923456
+            }))
+        ));
+        assert!(matches!(
+            Errors::from(CurveError::InvalidPoint),
+            Error::Curve(CurveError::InvalidPoint)
+        ));
+    }
+
+    #[test]
+    fn test_source_chaining() {
+        let error = NetError::from(EncodingError::UnexpectedEof {
+            expected: 4,
+            read: 1,
+        });
+
+        assert_eq!(error.to_string(), "Malformed message payload");
+        assert_eq!(
+            error.source().unwrap().to_string(),
+            "Unexpected end of data: expected 4 bytes, got 1"
+        );
+
+        let io = NetError::from(std::io::Error::new(std::io::ErrorKind::NotFound, "missing"));
+        assert_eq!(io.source().unwrap().to_string(), "missing");
+    }
 }
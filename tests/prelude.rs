@@ -0,0 +1,68 @@
+use bitcoin_in_rust::prelude::*;
+
+fn field_element(num: i64) -> FieldElement {
+    FieldElement::new(BigInt::from(num), BigInt::from(223))
+}
+
+#[test]
+fn test_point_arithmetic_through_prelude() {
+    let curve = WeierstrassCurve::new(field_element(0), field_element(7)).unwrap();
+
+    let p1 = Point::new_point(&curve, &field_element(192), &field_element(105)).unwrap();
+    let p2 = Point::new_point(&curve, &field_element(17), &field_element(56)).unwrap();
+    let sum = Point::new_point(&curve, &field_element(170), &field_element(142)).unwrap();
+
+    assert_eq!(p1 + p2, sum);
+
+    let p = Point::new_point(&curve, &field_element(47), &field_element(71)).unwrap();
+    assert_eq!(p * 21, Point::new_infinity(&curve));
+}
+
+#[test]
+fn test_invalid_point_through_prelude() {
+    let curve = WeierstrassCurve::new(field_element(0), field_element(7)).unwrap();
+
+    assert_eq!(
+        Point::new_point(&curve, &field_element(200), &field_element(119)),
+        Err(CurveError::InvalidPoint)
+    );
+}
+
+#[test]
+fn test_curve_construction_through_prelude() {
+    assert_eq!(
+        WeierstrassCurve::new(field_element(0), field_element(0)).unwrap_err(),
+        CurveError::SingularCurve
+    );
+
+    let curve = WeierstrassCurve::new(field_element(0), field_element(7))
+        .unwrap()
+        .with_order(BigInt::from(21));
+    let p = Point::new_point(&curve, &field_element(47), &field_element(71)).unwrap();
+    let doubled = p.clone() + p.clone();
+
+    assert_eq!(doubled.x(), Some(&field_element(36)));
+    assert_eq!(doubled.y(), Some(&field_element(111)));
+    assert_eq!(p.clone() * 22, p);
+    assert!((p * 21).is_infinity());
+}
+
+#[test]
+fn test_secp256k1_generator_through_prelude() {
+    assert_eq!(
+        SECP256K1_GENERATOR.clone() * SECP256K1_ORDER.clone(),
+        Point::new_infinity(&*SECP256K1_CURVE)
+    );
+    assert_eq!(
+        SECP256K1_CURVE.b(),
+        FieldElement::new(BigInt::from(7), SECP256K1_PRIME.clone())
+    );
+}
+
+#[test]
+fn test_network_through_prelude() {
+    assert_eq!(
+        Network::from_magic(Network::Mainnet.magic()),
+        Some(Network::Mainnet)
+    );
+}